@@ -63,6 +63,43 @@ impl RequestBuilder<'_, marker::Http> {
         }
     }
 
+    #[cfg(feature = "msgpack")]
+    /// Use messagepack object as request body. struct fields are serialized as map keys.
+    ///
+    /// [CONTENT_TYPE] header would be set with value: `application/msgpack`.
+    pub fn msgpack(mut self, body: impl serde::ser::Serialize) -> Self {
+        match rmp_serde::to_vec_named(&body) {
+            Ok(body) => {
+                self.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/msgpack"));
+                self.body(body)
+            }
+            Err(e) => {
+                self.push_error(e.into());
+                self
+            }
+        }
+    }
+
+    #[cfg(feature = "cbor")]
+    /// Use cbor object as request body.
+    ///
+    /// [CONTENT_TYPE] header would be set with value: `application/cbor`.
+    pub fn cbor(mut self, body: impl serde::ser::Serialize) -> Self {
+        let mut buf = Vec::new();
+        match ciborium::into_writer(&body, &mut buf) {
+            Ok(_) => {
+                self.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/cbor"));
+                self.body(buf)
+            }
+            Err(e) => {
+                self.push_error(e.into());
+                self
+            }
+        }
+    }
+
     /// Use pre allocated bytes as request body.
     ///
     /// Input type must implement [From] trait with [Bytes].
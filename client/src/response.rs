@@ -117,6 +117,34 @@ impl<const PAYLOAD_LIMIT: usize> Response<PAYLOAD_LIMIT> {
         Ok(serde_json::from_slice(bytes.chunk())?)
     }
 
+    #[cfg(feature = "msgpack")]
+    /// Collect response body as messagepack object. Response is consumed.
+    ///
+    /// The output type must impl [serde::de::DeserializeOwned] trait.
+    pub async fn msgpack<T>(self) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        use xitca_http::bytes::Buf;
+
+        let bytes = self.collect::<BytesMut>().await?;
+        Ok(rmp_serde::from_slice(bytes.chunk())?)
+    }
+
+    #[cfg(feature = "cbor")]
+    /// Collect response body as cbor object. Response is consumed.
+    ///
+    /// The output type must impl [serde::de::DeserializeOwned] trait.
+    pub async fn cbor<T>(self) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        use xitca_http::bytes::Buf;
+
+        let bytes = self.collect::<BytesMut>().await?;
+        Ok(ciborium::from_reader(bytes.chunk())?)
+    }
+
     async fn collect<B>(self) -> Result<B, Error>
     where
         B: Collectable,
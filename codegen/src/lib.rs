@@ -1,5 +1,6 @@
 mod error;
 mod route;
+mod schema;
 mod service;
 mod state;
 
@@ -12,6 +13,12 @@ pub fn state_impl(item: TokenStream) -> TokenStream {
     state::state(item).unwrap_or_else(|e| e.to_compile_error().into())
 }
 
+#[proc_macro_derive(Schema, attributes(serde))]
+pub fn schema_impl(item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item);
+    schema::schema(item).unwrap_or_else(|e| e.to_compile_error().into())
+}
+
 /// attribute macro for `xitca-web` application.
 ///
 /// # Pattern
@@ -26,6 +33,7 @@ pub fn state_impl(item: TokenStream) -> TokenStream {
 ///   `method = get` for example.
 /// - `enclosed = <type>`: typed middleware applied to route.
 /// - `enclosed_fn = <async function>`: async function as middleware applied to route
+/// - `openapi`: describe route in openapi document. requires `openapi` feature of `xitca-web`
 /// ```
 #[proc_macro_attribute]
 pub fn route(attr: TokenStream, item: TokenStream) -> TokenStream {
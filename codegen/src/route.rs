@@ -21,8 +21,16 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
     let method = &*method.right;
 
     let mut middlewares = quote! {};
+    let mut openapi = false;
 
     for attr in attrs {
+        if let Expr::Path(ref path) = attr {
+            if path.path.is_ident("openapi") {
+                openapi = true;
+                continue;
+            }
+        }
+
         let Expr::Assign(pair) = attr else {
             return Err(Error::new(input.span(), "expect '<name> = <value>' expression"));
         };
@@ -180,10 +188,33 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
         quote! { ::xitca_web::handler::handler_sync_service }
     };
 
+    let api = openapi.then(|| {
+        quote! {
+            ::xitca_web::codegen::__private::typed_route_api! {
+                #ident,
+                #path,
+                {
+                    use xitca_web::route::#method;
+                    use xitca_web::service::ServiceExt;
+
+                    #method(#handler(Self::#ident)#middlewares)
+                }
+            }
+        }
+    });
+
     Ok(quote! {
+        #api
+
         #[allow(non_camel_case_types)]
         #vis struct #ident;
 
+        // handler function lives in struct's namespace so it does not conflict with struct's name.
+        impl #ident {
+            #[doc(hidden)]
+            #input
+        }
+
         impl #generic_arg ::xitca_web::codegen::__private::TypedRoute<#state_ident> for #ident
         #where_clause
         {
@@ -202,14 +233,12 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
             }
 
             fn route() -> Self::Route {
-                #input
-
                 use xitca_web::codegen::__private::IntoObject;
                 use xitca_web::WebContext;
                 use xitca_web::route::#method;
                 use xitca_web::service::ServiceExt;
 
-                WebContext::<'_, #state_ident>::into_object(#method(#handler(#ident)#middlewares))
+                WebContext::<'_, #state_ident>::into_object(#method(#handler(Self::#ident)#middlewares))
             }
         }
     }
@@ -0,0 +1,290 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, GenericParam, Lit, LitStr, Meta, ext::IdentExt,
+    parse_quote, spanned::Spanned,
+};
+
+pub(crate) fn schema(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let ty_ident = &input.ident;
+    let container = SerdeAttr::parse(&input.attrs)?;
+    let desc = doc(&input.attrs);
+
+    let is_generic = input.generics.type_params().next().is_some();
+
+    let body = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => {
+                let mut props = Vec::new();
+                for field in fields.named.iter() {
+                    let attr = SerdeAttr::parse(&field.attrs)?;
+                    if attr.skip {
+                        continue;
+                    }
+
+                    let ident = field.ident.as_ref().unwrap();
+                    let name = match attr.rename {
+                        Some(rename) => rename,
+                        None => rename_field(ident.unraw().to_string().as_str(), container.rename_all.as_deref())
+                            .ok_or_else(|| Error::new(ident.span(), "unsupported rename_all rule"))?,
+                    };
+
+                    let ty = &field.ty;
+                    let default = attr.default || container.default;
+
+                    let mut schema = quote! { <#ty as ::xitca_web::openapi::Schema>::schema(obj.components()) };
+                    if let Some(desc) = doc(&field.attrs) {
+                        schema = quote! { ::xitca_web::openapi::with_description(#schema, #desc) };
+                    }
+
+                    props.push(quote! {
+                        let schema = #schema;
+                        obj = obj.property(#name, schema, !#default && <#ty as ::xitca_web::openapi::Schema>::required());
+                    });
+                }
+
+                let desc = desc.map(|desc| quote! { obj = obj.description(#desc); });
+
+                quote! {
+                    let mut obj = ::xitca_web::openapi::ObjectSchema::new(components);
+                    #desc
+                    #(#props)*
+                    obj.finish()
+                }
+            }
+            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed.first().unwrap().ty;
+                // newtype is transparent and described by it's inner type.
+                generic_bounds(&mut input.generics);
+                let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+                return Ok(quote! {
+                    impl #impl_generics ::xitca_web::openapi::Schema for #ty_ident #ty_generics #where_clause {
+                        fn schema(
+                            components: &mut ::xitca_web::openapi::Components
+                        ) -> ::xitca_web::codegen::__private::serde_json::Value {
+                            <#ty as ::xitca_web::openapi::Schema>::schema(components)
+                        }
+
+                        fn required() -> bool {
+                            <#ty as ::xitca_web::openapi::Schema>::required()
+                        }
+                    }
+                }
+                .into());
+            }
+            _ => {
+                return Err(Error::new(
+                    ty_ident.span(),
+                    "expect struct with named fields or newtype struct",
+                ));
+            }
+        },
+        Data::Enum(ref data) => {
+            let mut names = Vec::new();
+            for variant in data.variants.iter() {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(Error::new(variant.span(), "expect enum with unit variants only"));
+                }
+
+                let attr = SerdeAttr::parse(&variant.attrs)?;
+                if attr.skip {
+                    continue;
+                }
+
+                let name = match attr.rename {
+                    Some(rename) => rename,
+                    None => rename_variant(&variant.ident.to_string(), container.rename_all.as_deref())
+                        .ok_or_else(|| Error::new(variant.span(), "unsupported rename_all rule"))?,
+                };
+                names.push(name);
+            }
+
+            let desc = desc.map(|desc| quote! { "description": #desc, });
+
+            quote! {
+                let _ = components;
+                ::xitca_web::codegen::__private::serde_json::json!({ #desc "type": "string", "enum": [#(#names),*] })
+            }
+        }
+        Data::Union(_) => return Err(Error::new(ty_ident.span(), "expect struct or enum")),
+    };
+
+    // generic type is described inline as it's schema can be different for every type parameter.
+    let body = if is_generic {
+        body
+    } else {
+        let name = container.rename.unwrap_or_else(|| ty_ident.to_string());
+        quote! {
+            components.reference(#name, |components| {
+                #body
+            })
+        }
+    };
+
+    generic_bounds(&mut input.generics);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::xitca_web::openapi::Schema for #ty_ident #ty_generics #where_clause {
+            fn schema(
+                components: &mut ::xitca_web::openapi::Components
+            ) -> ::xitca_web::codegen::__private::serde_json::Value {
+                #body
+            }
+        }
+    }
+    .into())
+}
+
+fn generic_bounds(generics: &mut syn::Generics) {
+    let params = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(ty) => Some(ty.ident.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let where_clause = generics.make_where_clause();
+    for ident in params {
+        where_clause
+            .predicates
+            .push(parse_quote! { #ident: ::xitca_web::openapi::Schema });
+    }
+}
+
+// collect doc comments as description.
+fn doc(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match attr.meta {
+            Meta::NameValue(ref meta) if meta.path.is_ident("doc") => match meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref lit), ..
+                }) => Some(lit.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+// subset of serde attributes affecting schema.
+#[derive(Default)]
+struct SerdeAttr {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    default: bool,
+}
+
+impl SerdeAttr {
+    fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut res = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                let assign = meta.input.peek(syn::Token![=]);
+                if meta.path.is_ident("rename") && assign {
+                    res.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("rename_all") && assign {
+                    res.rename_all = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                    res.skip = true;
+                } else if meta.path.is_ident("default") {
+                    res.default = true;
+                    if assign {
+                        meta.value()?.parse::<LitStr>()?;
+                    }
+                } else if assign {
+                    meta.value()?.parse::<Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.input.peek(syn::Token![=]) {
+                            meta.value()?.parse::<Expr>()?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(res)
+    }
+}
+
+// rename snake_case field name with serde's rename_all rule.
+fn rename_field(name: &str, rule: Option<&str>) -> Option<String> {
+    let Some(rule) = rule else {
+        return Some(name.to_owned());
+    };
+
+    let pascal = || {
+        name.split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect::<String>()
+    };
+
+    Some(match rule {
+        "lowercase" | "snake_case" => name.to_owned(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|c| c.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.replace('_', "-").to_uppercase(),
+        _ => return None,
+    })
+}
+
+// rename PascalCase variant name with serde's rename_all rule.
+fn rename_variant(name: &str, rule: Option<&str>) -> Option<String> {
+    let Some(rule) = rule else {
+        return Some(name.to_owned());
+    };
+
+    let snake = || {
+        let mut snake = String::new();
+        for (i, c) in name.char_indices() {
+            if c.is_uppercase() && i != 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        }
+        snake
+    };
+
+    Some(match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "PascalCase" => name.to_owned(),
+        "camelCase" => {
+            let mut chars = name.chars();
+            chars
+                .next()
+                .map(|c| c.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        "snake_case" => snake(),
+        "SCREAMING_SNAKE_CASE" => snake().to_uppercase(),
+        "kebab-case" => snake().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake().replace('_', "-").to_uppercase(),
+        _ => return None,
+    })
+}
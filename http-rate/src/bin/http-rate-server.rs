@@ -0,0 +1,14 @@
+//! limiter process for `http_rate::remote::RemoteStore`.
+//!
+//! usage: `http-rate-server [address]`. address defaults to `127.0.0.1:6464`.
+
+use http_rate::remote::Server;
+
+fn main() -> std::io::Result<()> {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("127.0.0.1:6464"));
+    let server = Server::bind(addr)?;
+    println!("http-rate-server listening on {}", server.local_addr()?);
+    server.run()
+}
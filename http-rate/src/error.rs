@@ -1,23 +1,24 @@
-use core::fmt;
+use core::{fmt, time::Duration};
 
 use std::{error, time::Instant};
 
-use http::{HeaderName, HeaderValue, Response, StatusCode};
+use http::{header::RETRY_AFTER, HeaderName, HeaderValue, Response, StatusCode};
 
 use crate::{
     gcra::NotUntil,
+    snapshot::{ceil_secs, HeaderStyle},
     timer::{DefaultTimer, Timer},
 };
 
 /// Error happen when client exceeds rate limit.
 #[derive(Debug)]
 pub struct TooManyRequests {
-    after_seconds: u64,
+    wait: Duration,
 }
 
 impl fmt::Display for TooManyRequests {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "too many requests. wait for {}", self.after_seconds)
+        write!(f, "too many requests. wait for {}", self.wait.as_secs())
     }
 }
 
@@ -25,21 +26,45 @@ impl error::Error for TooManyRequests {}
 
 impl From<NotUntil<Instant>> for TooManyRequests {
     fn from(e: NotUntil<Instant>) -> Self {
-        let after_seconds = e.wait_time_from(DefaultTimer.now()).as_secs();
-        Self { after_seconds }
+        Self::new(e.wait_time_from(DefaultTimer.now()))
     }
 }
 
 const X_RT_AFTER: HeaderName = HeaderName::from_static("x-ratelimit-after");
 
 impl TooManyRequests {
+    pub(crate) const fn new(wait: Duration) -> Self {
+        Self { wait }
+    }
+
+    // minimum amount of time must pass before client can be allowed again.
+    #[cfg(feature = "remote")]
+    pub(crate) const fn wait(&self) -> Duration {
+        self.wait
+    }
+
     /// extend response headers with status code and headers
     /// StatusCode: 429
     /// Header: `x-ratelimit-after: <num in second>`
+    /// Header: `retry-after: <num in second rounded up>`
     pub fn extend_response<Ext>(&self, res: &mut Response<Ext>) {
+        self.extend_response_with(res, HeaderStyle::Legacy)
+    }
+
+    /// extend response headers with status code and headers of given style.
+    ///
+    /// [HeaderStyle::Legacy] is the same as [TooManyRequests::extend_response].
+    ///
+    /// [HeaderStyle::Ietf]:
+    /// StatusCode: 429
+    /// Header: `retry-after: <num in second rounded up>`
+    pub fn extend_response_with<Ext>(&self, res: &mut Response<Ext>, style: HeaderStyle) {
         *res.status_mut() = StatusCode::TOO_MANY_REQUESTS;
-        res.headers_mut()
-            .insert(X_RT_AFTER, HeaderValue::from(self.after_seconds));
+        let headers = res.headers_mut();
+        headers.insert(RETRY_AFTER, HeaderValue::from(ceil_secs(self.wait)));
+        if let HeaderStyle::Legacy = style {
+            headers.insert(X_RT_AFTER, HeaderValue::from(self.wait.as_secs()));
+        }
     }
 }
 
@@ -62,7 +87,24 @@ impl error::Error for InsufficientCapacity {}
 
 #[cfg(test)]
 mod test {
-    use super::InsufficientCapacity;
+    use super::*;
+
+    #[test]
+    fn retry_after() {
+        let e = TooManyRequests::new(Duration::from_millis(1500));
+
+        let mut res = Response::new(());
+        e.extend_response_with(&mut res, HeaderStyle::Ietf);
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "2");
+        assert!(res.headers().get(X_RT_AFTER).is_none());
+
+        let mut res = Response::new(());
+        e.extend_response(&mut res);
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(X_RT_AFTER).unwrap(), "1");
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "2");
+    }
 
     #[test]
     fn coverage() {
@@ -0,0 +1,91 @@
+use core::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use std::sync::Arc;
+
+/// Policy for evicting keys from state of keyed [RateLimit].
+///
+/// By default keys are swept every 60 seconds and the number of keys is not bounded. Sweeping only
+/// removes keys which are indistinguishable from fresh keys and therefore never changes the outcome
+/// of rate limiting. Bounding the number of keys evicts the least recently checked keys when state is
+/// full, which resets their quota.
+///
+/// Policy shares a counter of evicted keys among all rate limiters constructed from it and it's clones.
+///
+/// # Examples
+/// ```rust
+/// # use core::time::Duration;
+/// # use http_rate::{EvictionPolicy, Quota, RateLimit};
+/// let policy = EvictionPolicy::new()
+///     // sweep stale keys every 10 seconds.
+///     .sweep_interval(Duration::from_secs(10))
+///     // keep at most 100_000 keys in state.
+///     .max_keys(100_000);
+///
+/// let limiter = RateLimit::new(Quota::per_second(1)).with_eviction(policy.clone());
+///
+/// // number of evicted keys of all rate limiters using the policy.
+/// assert_eq!(policy.evicted(), 0);
+/// ```
+///
+/// [RateLimit]: crate::RateLimit
+#[derive(Clone, Debug)]
+pub struct EvictionPolicy {
+    pub(crate) max_keys: Option<NonZeroUsize>,
+    pub(crate) sweep_interval: Duration,
+    pub(crate) evicted: Arc<AtomicU64>,
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EvictionPolicy {
+    /// Construct a default policy.
+    pub fn new() -> Self {
+        Self {
+            max_keys: None,
+            sweep_interval: Duration::from_secs(60),
+            evicted: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Set the maximum number of keys kept in state. When a new key is observed with a full state
+    /// a batch of least recently checked keys are evicted.
+    ///
+    /// Zero is clamped to one as state must be able to hold the key being checked.
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = Some(NonZeroUsize::new(max_keys).unwrap_or(NonZeroUsize::MIN));
+        self
+    }
+
+    /// Set the interval between sweeps of stale keys. By default sweeping is started by rate limit check
+    /// happening after interval has elapsed since last sweep and is spread over following checks
+    /// in bounded batches. See [RateLimit::with_external_sweep] for sweeping with a timer.
+    ///
+    /// [RateLimit::with_external_sweep]: crate::RateLimit::with_external_sweep
+    ///
+    /// # Panics
+    /// - When the Duration is zero.
+    pub fn sweep_interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "sweep interval must be greater than zero");
+        self.sweep_interval = interval;
+        self
+    }
+
+    /// Total number of keys evicted by rate limiters using this policy.
+    pub fn evicted(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_evicted(&self, n: usize) {
+        if n > 0 {
+            self.evicted.fetch_add(n as u64, Ordering::Relaxed);
+        }
+    }
+}
@@ -2,36 +2,226 @@
 #![allow(clippy::declare_interior_mutable_const)]
 
 mod error;
+mod eviction;
 mod gcra;
 mod nanos;
 mod quota;
 mod snapshot;
 mod state;
+mod store;
 mod timer;
 
+#[cfg(feature = "remote")]
+pub mod remote;
+
 pub use error::TooManyRequests;
+pub use eviction::EvictionPolicy;
 pub use quota::Quota;
-pub use snapshot::RateSnapshot;
+pub use snapshot::{HeaderStyle, RateSnapshot};
+pub use store::AsyncStateStore;
 
-use core::net::{IpAddr, SocketAddr};
+use core::{
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
 
 use std::sync::Arc;
 
 use http::header::{HeaderMap, HeaderName, FORWARDED};
 
-use crate::state::{keyed::DefaultKeyedStateStore, RateLimiter};
+use crate::{
+    nanos::Nanos,
+    state::{keyed::DefaultKeyedStateStore, RateLimiter},
+};
+
+/// keyed rate limiter. every distinct key owns it's own quota.
+///
+/// by default client's ip address is used as key. see [RateLimit::rate_limit] for detail.
+/// any type implementing `Clone + Hash + Eq` can be used as key. e.g. api key, user id, route
+/// or combination of them in a tuple.
+///
+/// # Examples
+/// ```rust
+/// # use http_rate::{Quota, RateLimit};
+/// // rate limit keyed on api key.
+/// let limiter = RateLimit::<String>::keyed(Quota::per_second(1));
+///
+/// assert!(limiter.check(&"key_1".to_string()).is_ok());
+/// assert!(limiter.check(&"key_1".to_string()).is_err());
+/// // different key has it's own quota.
+/// assert!(limiter.check(&"key_2".to_string()).is_ok());
+/// ```
+pub struct RateLimit<K = IpAddr>
+where
+    K: Clone + Hash + Eq,
+{
+    inner: Arc<Inner<K>>,
+    // sweeping is driven by caller with RateLimit::sweep_batch instead of rate limit checks.
+    external_sweep: bool,
+}
 
-#[derive(Clone)]
-pub struct RateLimit {
-    limit: Arc<RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>>>,
+struct Inner<K>
+where
+    K: Clone + Hash + Eq,
+{
+    quota: Quota,
+    policy: EvictionPolicy,
+    limit: RateLimiter<K, DefaultKeyedStateStore<K>>,
+    // time of next sweep in nanoseconds since creation of limiter.
+    next_sweep: AtomicU64,
+    // a sweep round is in progress and keys are examined in bounded batches.
+    sweeping: AtomicBool,
 }
 
-impl RateLimit {
-    /// Construct a new RateLimit with given quota.
-    pub fn new(quota: Quota) -> Self {
+// number of keys examined by one step of sweep round.
+const SWEEP_BATCH: usize = 64;
+
+impl<K> Clone for RateLimit<K>
+where
+    K: Clone + Hash + Eq,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            external_sweep: self.external_sweep,
+        }
+    }
+}
+
+impl<K> RateLimit<K>
+where
+    K: Clone + Hash + Eq,
+{
+    /// Construct a new RateLimit with given quota and custom key type.
+    pub fn keyed(quota: Quota) -> Self {
+        Self::with_policy(quota, EvictionPolicy::default())
+    }
+
+    /// Replace [EvictionPolicy] of RateLimit. State of the RateLimit is reset.
+    pub fn with_eviction(self, policy: EvictionPolicy) -> Self {
+        Self {
+            external_sweep: self.external_sweep,
+            ..Self::with_policy(self.inner.quota, policy)
+        }
+    }
+
+    /// Stop sweeping stale keys from inside [RateLimit::check]. Sweeping has to be driven by calling
+    /// [RateLimit::sweep_batch] from a timer ticking every sweep interval of [EvictionPolicy] so the cost
+    /// of sweeping does not add to latency of rate limit checks.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use std::{thread, time::Duration};
+    /// # use http_rate::{Quota, RateLimit};
+    /// let limiter = RateLimit::new(Quota::per_second(1)).with_external_sweep();
+    ///
+    /// let sweeper = limiter.clone();
+    /// thread::spawn(move || loop {
+    ///     thread::sleep(Duration::from_secs(60));
+    ///     // sweep all stale keys in batches.
+    ///     while !sweeper.sweep_batch() {}
+    /// });
+    /// ```
+    pub fn with_external_sweep(mut self) -> Self {
+        self.external_sweep = true;
+        self
+    }
+
+    fn with_policy(quota: Quota, policy: EvictionPolicy) -> Self {
+        let next_sweep = Nanos::from(policy.sweep_interval).as_u64();
         Self {
-            limit: Arc::new(RateLimiter::hashmap(quota)),
+            inner: Arc::new(Inner {
+                quota,
+                limit: RateLimiter::hashmap_with_policy(quota, policy.clone()),
+                policy,
+                next_sweep: AtomicU64::new(next_sweep),
+                sweeping: AtomicBool::new(false),
+            }),
+            external_sweep: false,
+        }
+    }
+
+    /// Rate limit with given key.
+    ///
+    /// Unless [RateLimit::with_external_sweep] is used stale keys are swept when sweep interval of
+    /// [EvictionPolicy] has elapsed since last sweep. Sweeping is spread over following checks where each
+    /// check examines a small bounded batch of keys.
+    pub fn check(&self, key: &K) -> Result<RateSnapshot, TooManyRequests> {
+        if !self.external_sweep {
+            self.maybe_sweep();
+        }
+        self.inner.limit.check_key(key).map_err(TooManyRequests::from)
+    }
+
+    /// Examine a small bounded batch of keys and remove stale ones. A new sweep round over all keys is
+    /// started when there is none in progress. Returns `true` when the sweep round is finished.
+    pub fn sweep_batch(&self) -> bool {
+        if !self.inner.sweeping.swap(true, Ordering::Relaxed) {
+            self.inner.limit.start_sweep();
         }
+        let finished = !self.inner.limit.retain_recent_batch(SWEEP_BATCH);
+        if finished {
+            self.inner.sweeping.store(false, Ordering::Relaxed);
+        }
+        finished
+    }
+
+    /// Remove all keys with state indistinguishable from fresh key at once. Removed keys are counted as
+    /// evicted. Rate limit checks are blocked until sweeping is finished.
+    pub fn sweep(&self) {
+        self.inner.limit.retain_recent();
+        self.inner.limit.shrink_to_fit();
+    }
+
+    /// Number of keys currently kept in state.
+    pub fn len(&self) -> usize {
+        self.inner.limit.len()
+    }
+
+    /// Returns `true` when no key is kept in state.
+    pub fn is_empty(&self) -> bool {
+        self.inner.limit.is_empty()
+    }
+
+    /// Interval between sweeps of stale keys set by [EvictionPolicy].
+    pub fn sweep_interval(&self) -> Duration {
+        self.inner.policy.sweep_interval
+    }
+
+    /// Total number of keys evicted by rate limiters sharing the same [EvictionPolicy].
+    pub fn evicted(&self) -> u64 {
+        self.inner.policy.evicted()
+    }
+
+    fn maybe_sweep(&self) {
+        let now = self.inner.limit.elapsed().as_u64();
+        let next = self.inner.next_sweep.load(Ordering::Relaxed);
+        if now >= next {
+            let interval = Nanos::from(self.inner.policy.sweep_interval).as_u64();
+            // only one caller wins the start of sweep round when racing.
+            if self
+                .inner
+                .next_sweep
+                .compare_exchange(next, now + interval, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
+                self.inner.limit.start_sweep();
+                self.inner.sweeping.store(true, Ordering::Relaxed);
+            }
+        }
+
+        if self.inner.sweeping.load(Ordering::Relaxed) && !self.inner.limit.retain_recent_batch(SWEEP_BATCH) {
+            self.inner.sweeping.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+impl RateLimit {
+    /// Construct a new RateLimit with given quota. client's ip address is used as key.
+    pub fn new(quota: Quota) -> Self {
+        Self::keyed(quota)
     }
 
     /// Rate limit [Request] based on it's [HeaderMap] state and given client [SocketAddr]
@@ -39,16 +229,23 @@ impl RateLimit {
     /// from left to determine client's socket address. Received [SocketAddr] will be used
     /// as fallback when all headers are absent or can't provide valid client address.
     ///
+    /// headers are trusted unconditionally and can be spoofed by client when not set by a trusted
+    /// reverse proxy. prefer [RateLimit::check] with client ip address resolved against a list of
+    /// trusted proxies when service is directly exposed to clients.
+    ///
     /// [Request]: http::Request
     pub fn rate_limit(&self, headers: &HeaderMap, addr: &SocketAddr) -> Result<RateSnapshot, TooManyRequests> {
-        let addr = maybe_x_forwarded_for(headers)
-            .or_else(|| maybe_x_real_ip(headers))
-            .or_else(|| maybe_forwarded(headers))
-            .unwrap_or_else(|| addr.ip());
-        self.limit.check_key(&addr).map_err(TooManyRequests::from)
+        self.check(&client_ip(headers, addr))
     }
 }
 
+fn client_ip(headers: &HeaderMap, addr: &SocketAddr) -> IpAddr {
+    maybe_x_forwarded_for(headers)
+        .or_else(|| maybe_x_real_ip(headers))
+        .or_else(|| maybe_forwarded(headers))
+        .unwrap_or_else(|| addr.ip())
+}
+
 const X_REAL_IP: HeaderName = HeaderName::from_static("x-real-ip");
 const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
 
@@ -114,6 +311,104 @@ mod test {
         assert_eq!(maybe_forwarded(&headers).unwrap().to_string(), "192.0.2.60");
     }
 
+    #[test]
+    fn evict_max_keys() {
+        let policy = EvictionPolicy::new().max_keys(4);
+        let limiter = RateLimit::<u32>::keyed(Quota::per_hour(1)).with_eviction(policy.clone());
+
+        for key in 0..4 {
+            assert!(limiter.check(&key).is_ok());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(limiter.len(), 4);
+        assert_eq!(limiter.evicted(), 0);
+
+        // state is full. least recently active key is evicted.
+        assert!(limiter.check(&4).is_ok());
+        assert_eq!(limiter.len(), 4);
+        assert_eq!(limiter.evicted(), 1);
+        assert_eq!(policy.evicted(), 1);
+
+        // evicted key has a fresh quota while the other keys are still limited.
+        assert!(limiter.check(&0).is_ok());
+        assert!(limiter.check(&3).is_err());
+
+        // rejected check is an access too. key 2 is the least recently accessed one.
+        assert!(limiter.check(&4).is_err());
+        assert!(limiter.check(&5).is_ok());
+        assert!(limiter.check(&2).is_ok());
+        assert!(limiter.check(&4).is_err());
+    }
+
+    #[test]
+    fn evict_sweep() {
+        let policy = EvictionPolicy::new().sweep_interval(Duration::from_millis(200));
+        let limiter = RateLimit::<u32>::keyed(Quota::per_second(1000)).with_eviction(policy);
+
+        let n = SWEEP_BATCH as u32 * 2;
+        for key in 0..n {
+            assert!(limiter.check(&key).is_ok());
+        }
+        assert_eq!(limiter.len(), n as usize);
+        assert_eq!(limiter.evicted(), 0);
+
+        // quota of all keys are fully replenished. sweep round starts on next check and every check
+        // examines a bounded batch of keys.
+        thread::sleep(Duration::from_millis(250));
+        assert!(limiter.check(&n).is_ok());
+        assert_eq!(limiter.len(), n as usize - SWEEP_BATCH + 1);
+        assert_eq!(limiter.evicted(), SWEEP_BATCH as u64);
+
+        assert!(limiter.check(&n).is_ok());
+        assert_eq!(limiter.len(), 1);
+        assert_eq!(limiter.evicted(), n as u64);
+
+        // sweep round is finished and key inserted during the round is kept.
+        assert!(limiter.check(&n).is_ok());
+        assert_eq!(limiter.len(), 1);
+
+        // explicit sweep removes all stale keys at once.
+        thread::sleep(Duration::from_millis(10));
+        limiter.sweep();
+        assert!(limiter.is_empty());
+        assert_eq!(limiter.evicted(), n as u64 + 1);
+    }
+
+    #[test]
+    fn evict_external_sweep() {
+        let policy = EvictionPolicy::new().sweep_interval(Duration::from_millis(1));
+        let limiter = RateLimit::<u32>::keyed(Quota::per_second(1000))
+            .with_eviction(policy)
+            .with_external_sweep();
+
+        let n = SWEEP_BATCH as u32 * 2;
+        for key in 0..n {
+            assert!(limiter.check(&key).is_ok());
+        }
+
+        // check does not sweep even when sweep interval has elapsed.
+        thread::sleep(Duration::from_millis(10));
+        assert!(limiter.check(&n).is_ok());
+        assert_eq!(limiter.len(), n as usize + 1);
+
+        assert!(!limiter.sweep_batch());
+        assert_eq!(limiter.evicted(), SWEEP_BATCH as u64);
+        assert!(!limiter.sweep_batch());
+        assert!(limiter.sweep_batch());
+        assert_eq!(limiter.len(), 1);
+        assert_eq!(limiter.evicted(), n as u64);
+    }
+
+    #[test]
+    fn evict_max_keys_zero() {
+        let limiter = RateLimit::<u32>::keyed(Quota::per_hour(1)).with_eviction(EvictionPolicy::new().max_keys(0));
+
+        assert!(limiter.check(&0).is_ok());
+        assert!(limiter.check(&1).is_ok());
+        assert_eq!(limiter.len(), 1);
+        assert_eq!(limiter.evicted(), 1);
+    }
+
     #[test]
     fn rejects_too_many() {
         let clock = FakeRelativeClock::default();
@@ -305,8 +600,8 @@ mod test {
         limiter: RateLimiter<T, HashMapStateStore<T>, FakeRelativeClock>,
     ) -> Vec<T> {
         let state = limiter.into_state_store();
-        let map = state.lock().unwrap();
-        let mut keys: Vec<T> = map.keys().copied().collect();
+        let state = state.lock().unwrap();
+        let mut keys: Vec<T> = state.map.keys().copied().collect();
         keys.sort();
         keys
     }
@@ -0,0 +1,568 @@
+//! rate limit state shared by multiple processes through a limiter process.
+//!
+//! [Server] is the limiter process keeping keyed rate limit state in it's memory and [RemoteStore] is
+//! the [AsyncStateStore] talking to it. Rate limiting decision is made by server with the same GCRA
+//! algorithm and clock for all clients so the quota is enforced across them.
+//!
+//! # Protocol
+//! Client and server exchange utf-8 encoded lines terminated with `\n`. All durations are in
+//! nanoseconds.
+//! ```text
+//! request:
+//! CHECK <replenish interval> <max burst> <key>
+//!
+//! response:
+//! OK <replenish interval> <burst capacity> <time of measurement> <theoretical arrival time>
+//! LIMITED <wait time>
+//! ERR <reason>
+//! ```
+//! key is the rest of request line after max burst. It's escaped by client so it never contains line
+//! break: `\` is sent as `\\`, line feed as `\n` and carriage return as `\r`. Every distinct quota
+//! owns it's own keyed state on server.
+//!
+//! Server answers `ERR` and closes connection when request line is longer than [Server::max_line_len]
+//! or there are more than [Server::max_connections] connections. Request with quota not registered by
+//! [Server::quota] or exceeding [Server::max_quotas] is answered with `ERR`.
+//!
+//! # Examples
+//! ```rust,no_run
+//! # use std::thread;
+//! # use http_rate::{remote::{RemoteStore, Server}, AsyncStateStore, Quota};
+//! # async fn run() -> std::io::Result<()> {
+//! // start limiter process. it can also be started with the `http-rate-server` binary of this crate.
+//! let server = Server::bind("127.0.0.1:0")?;
+//! let addr = server.local_addr()?;
+//! thread::spawn(move || server.run());
+//!
+//! // every replica of service constructs it's own store connecting to the same limiter process.
+//! let store = RemoteStore::new(addr, Quota::per_second(10));
+//!
+//! match store.check(&"client_1").await? {
+//!     Ok(_snap) => {}  // client still have quota left.
+//!     Err(_e) => {}    // client ran out of quota.
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use core::{fmt, num::NonZeroU32, time::Duration};
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    net::{self, SocketAddr, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+    thread,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufStream},
+    net::TcpStream,
+    time::timeout,
+};
+
+use crate::{
+    error::TooManyRequests, eviction::EvictionPolicy, nanos::Nanos, quota::Quota, snapshot::RateSnapshot,
+    store::AsyncStateStore, RateLimit,
+};
+
+/// [AsyncStateStore] backed by a limiter [Server].
+///
+/// Connections to server are pooled and reused. A connection failed with io error is dropped and a new
+/// one is made on next check.
+pub struct RemoteStore {
+    addr: SocketAddr,
+    quota: Quota,
+    timeout: Duration,
+    conns: Mutex<Vec<BufStream<TcpStream>>>,
+}
+
+impl RemoteStore {
+    /// Construct a new RemoteStore connecting to server listening on given address. Keys are rate
+    /// limited with given quota.
+    pub fn new(addr: SocketAddr, quota: Quota) -> Self {
+        Self {
+            addr,
+            quota,
+            timeout: Duration::from_secs(1),
+            conns: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Set timeout of a check including connecting to server and receiving response. Check not finished
+    /// in time fails with [io::ErrorKind::TimedOut] error and it's connection is dropped.
+    ///
+    /// Default to 1 second.
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    fn request<K>(&self, key: &K) -> String
+    where
+        K: fmt::Display,
+    {
+        let mut req = format!(
+            "CHECK {} {} ",
+            Nanos::from(self.quota.replenish_1_per).as_u64(),
+            self.quota.max_burst
+        );
+        for c in key.to_string().chars() {
+            match c {
+                '\\' => req.push_str("\\\\"),
+                '\n' => req.push_str("\\n"),
+                '\r' => req.push_str("\\r"),
+                c => req.push(c),
+            }
+        }
+        req.push('\n');
+        req
+    }
+}
+
+impl<K> AsyncStateStore<K> for RemoteStore
+where
+    K: fmt::Display,
+{
+    type Error = io::Error;
+
+    async fn check(&self, key: &K) -> Result<Result<RateSnapshot, TooManyRequests>, Self::Error> {
+        let req = self.request(key);
+        timeout(self.timeout, self.exchange(req.as_bytes()))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?
+    }
+}
+
+impl RemoteStore {
+    async fn exchange(&self, req: &[u8]) -> io::Result<Result<RateSnapshot, TooManyRequests>> {
+        let conn = self.conns.lock().unwrap().pop();
+        let mut conn = match conn {
+            Some(conn) => conn,
+            None => BufStream::new(TcpStream::connect(self.addr).await?),
+        };
+
+        conn.write_all(req).await?;
+        conn.flush().await?;
+
+        let mut res = String::new();
+        if conn.read_line(&mut res).await? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let res = parse_response(res.trim_end())?;
+
+        // connection is only returned to pool when a full and valid response is received. a check
+        // cancelled or failed in the middle of exchange drops it's connection.
+        self.conns.lock().unwrap().push(conn);
+
+        Ok(res)
+    }
+}
+
+fn parse_response(res: &str) -> io::Result<Result<RateSnapshot, TooManyRequests>> {
+    fn nanos<'a>(parts: &mut impl Iterator<Item = &'a str>) -> io::Result<Nanos> {
+        parts
+            .next()
+            .and_then(|n| n.parse::<u64>().ok())
+            .map(Nanos::new)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed response"))
+    }
+
+    let (kind, rest) = res.split_once(' ').unwrap_or((res, ""));
+    let mut parts = rest.split(' ');
+
+    match kind {
+        "OK" => {
+            let t = nanos(&mut parts)?;
+            let tau = nanos(&mut parts)?;
+            let time_of_measurement = nanos(&mut parts)?;
+            let tat = nanos(&mut parts)?;
+            Ok(Ok(RateSnapshot::new(t, tau, time_of_measurement, tat)))
+        }
+        "LIMITED" => nanos(&mut parts).map(|wait| Err(TooManyRequests::new(wait.into()))),
+        "ERR" => Err(io::Error::other(rest.to_owned())),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "malformed response")),
+    }
+}
+
+const MAX_LINE_LEN: usize = 1024;
+const MAX_QUOTAS: usize = 64;
+const MAX_CONNECTIONS: usize = 1024;
+
+/// Limiter process serving [RemoteStore] clients.
+///
+/// Server is runtime agnostic and serves every connection with a dedicated thread.
+pub struct Server {
+    listener: net::TcpListener,
+    policy: EvictionPolicy,
+    quotas: Vec<Quota>,
+    max_quotas: usize,
+    max_line_len: usize,
+    max_connections: usize,
+}
+
+impl Server {
+    /// Bind server to given address.
+    pub fn bind<A>(addr: A) -> io::Result<Self>
+    where
+        A: ToSocketAddrs,
+    {
+        net::TcpListener::bind(addr).map(|listener| Self {
+            listener,
+            policy: EvictionPolicy::default(),
+            quotas: Vec::new(),
+            max_quotas: MAX_QUOTAS,
+            max_line_len: MAX_LINE_LEN,
+            max_connections: MAX_CONNECTIONS,
+        })
+    }
+
+    /// Returns the address server is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Set [EvictionPolicy] of keyed state on server. The policy is applied to state of every quota. Stale
+    /// keys are swept by a dedicated thread of server every sweep interval.
+    pub fn eviction(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Register a quota server accepts. When any quota is registered request with unregistered quota
+    /// is rejected. Otherwise quota is registered by the first request using it until there are
+    /// [Server::max_quotas] of them.
+    pub fn quota(mut self, quota: Quota) -> Self {
+        self.quotas.push(quota);
+        self
+    }
+
+    /// Set maximum number of quotas registered by requests. Default to 64.
+    pub fn max_quotas(mut self, max: usize) -> Self {
+        self.max_quotas = max;
+        self
+    }
+
+    /// Set maximum length of request line in bytes including line break. Default to 1024.
+    pub fn max_line_len(mut self, max: usize) -> Self {
+        self.max_line_len = max;
+        self
+    }
+
+    /// Set maximum number of concurrent connections. Default to 1024.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    /// Run server and block current thread until accepting connection failed.
+    pub fn run(self) -> io::Result<()> {
+        let limiters = self
+            .quotas
+            .iter()
+            .map(|quota| (quota_key(quota), limiter(*quota, &self.policy)))
+            .collect();
+
+        let shared = Arc::new(Shared {
+            limiters: Mutex::new(limiters),
+            registered: !self.quotas.is_empty(),
+            max_quotas: self.max_quotas,
+            max_line_len: self.max_line_len,
+            policy: self.policy,
+            conns: AtomicUsize::new(0),
+        });
+
+        let sweeper = Arc::downgrade(&shared);
+        thread::spawn(move || sweep(sweeper));
+
+        loop {
+            let (mut stream, _) = self.listener.accept()?;
+            if shared.conns.fetch_add(1, Ordering::Relaxed) >= self.max_connections {
+                shared.conns.fetch_sub(1, Ordering::Relaxed);
+                let _ = stream.write_all(b"ERR too many connections\n");
+                continue;
+            }
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let res = serve(stream, &shared);
+                shared.conns.fetch_sub(1, Ordering::Relaxed);
+                res
+            });
+        }
+    }
+}
+
+struct Shared {
+    limiters: Mutex<HashMap<(u64, NonZeroU32), RateLimit<String>>>,
+    // quotas are registered before server running and no more can be added by request.
+    registered: bool,
+    max_quotas: usize,
+    max_line_len: usize,
+    policy: EvictionPolicy,
+    conns: AtomicUsize,
+}
+
+impl Shared {
+    fn limiter(&self, quota: Quota) -> Result<RateLimit<String>, &'static str> {
+        let mut limiters = self.limiters.lock().unwrap();
+        let len = limiters.len();
+        match limiters.entry(quota_key(&quota)) {
+            Entry::Occupied(entry) => Ok(entry.get().clone()),
+            Entry::Vacant(_) if self.registered => Err("unknown quota"),
+            Entry::Vacant(_) if len >= self.max_quotas => Err("too many quotas"),
+            Entry::Vacant(entry) => Ok(entry.insert(limiter(quota, &self.policy)).clone()),
+        }
+    }
+}
+
+// stale keys are swept by a dedicated thread instead of rate limit checks.
+fn limiter(quota: Quota, policy: &EvictionPolicy) -> RateLimit<String> {
+    RateLimit::keyed(quota)
+        .with_eviction(policy.clone())
+        .with_external_sweep()
+}
+
+fn sweep(shared: Weak<Shared>) {
+    loop {
+        let Some(interval) = shared.upgrade().map(|shared| shared.policy.sweep_interval) else {
+            return;
+        };
+        thread::sleep(interval);
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        let limiters = shared.limiters.lock().unwrap().values().cloned().collect::<Vec<_>>();
+        for limiter in limiters {
+            while !limiter.sweep_batch() {}
+        }
+    }
+}
+
+fn quota_key(quota: &Quota) -> (u64, NonZeroU32) {
+    (Nanos::from(quota.replenish_1_per).as_u64(), quota.max_burst)
+}
+
+fn serve(stream: net::TcpStream, shared: &Shared) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        if (&mut reader)
+            .take(shared.max_line_len as u64)
+            .read_until(b'\n', &mut line)?
+            == 0
+        {
+            return Ok(());
+        }
+
+        // the rest of line is not read and connection can't be used anymore.
+        if !line.ends_with(b"\n") && line.len() >= shared.max_line_len {
+            writer.write_all(b"ERR request too long\n")?;
+            return writer.flush();
+        }
+
+        let req = core::str::from_utf8(&line)
+            .ok()
+            .and_then(|line| parse_request(line.trim_end_matches(['\r', '\n'])));
+
+        match req {
+            Some((quota, key)) => match shared.limiter(quota) {
+                Ok(limiter) => match limiter.check(&key.to_owned()) {
+                    Ok(snap) => writeln!(
+                        writer,
+                        "OK {} {} {} {}",
+                        snap.t.as_u64(),
+                        snap.tau.as_u64(),
+                        snap.time_of_measurement.as_u64(),
+                        snap.tat.as_u64()
+                    )?,
+                    Err(e) => writeln!(writer, "LIMITED {}", Nanos::from(e.wait()).as_u64())?,
+                },
+                Err(reason) => writeln!(writer, "ERR {reason}")?,
+            },
+            None => writer.write_all(b"ERR malformed request\n")?,
+        }
+
+        // flush when all pipelined requests are answered.
+        if reader.buffer().is_empty() {
+            writer.flush()?;
+        }
+    }
+}
+
+fn parse_request(req: &str) -> Option<(Quota, &str)> {
+    let req = req.strip_prefix("CHECK ")?;
+    let (replenish_1_per, req) = req.split_once(' ')?;
+    let (max_burst, key) = req.split_once(' ')?;
+    let replenish_1_per = Duration::from_nanos(replenish_1_per.parse().ok()?);
+    let max_burst = max_burst.parse::<NonZeroU32>().ok()?;
+    Quota::with_period(replenish_1_per).map(|quota| (quota.allow_burst(max_burst), key))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn server() -> SocketAddr {
+        server_with(|server| server)
+    }
+
+    fn server_with(f: impl FnOnce(Server) -> Server) -> SocketAddr {
+        let server = f(Server::bind("127.0.0.1:0").unwrap());
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    #[tokio::test]
+    async fn shared_quota() {
+        let addr = server();
+        let quota = Quota::per_hour(3);
+
+        // two replicas sharing the same limiter process.
+        let store1 = RemoteStore::new(addr, quota);
+        let store2 = RemoteStore::new(addr, quota);
+
+        // remote store produces the same snapshot as in process rate limiter.
+        let local = RateLimit::<&str>::keyed(quota);
+
+        for store in [&store1, &store2, &store1] {
+            let snap = store.check(&"foo").await.unwrap().unwrap();
+            let local = local.check(&"foo").unwrap();
+            assert_eq!(snap.remaining_burst_capacity(), local.remaining_burst_capacity());
+            assert_eq!(snap.quota(), quota);
+        }
+
+        let e = store2.check(&"foo").await.unwrap().unwrap_err();
+        assert!(e.wait() > Duration::from_secs(60 * 19));
+        assert!(e.wait() <= Duration::from_secs(60 * 20));
+
+        // different key has it's own quota.
+        assert!(store2.check(&"bar").await.unwrap().is_ok());
+
+        // different quota has it's own state.
+        let store3 = RemoteStore::new(addr, Quota::per_hour(1));
+        assert!(store3.check(&"foo").await.unwrap().is_ok());
+        assert!(store3.check(&"foo").await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn escape_key() {
+        let addr = server();
+        let store = RemoteStore::new(addr, Quota::per_hour(1));
+
+        assert!(store.check(&"foo\nbar").await.unwrap().is_ok());
+        assert!(store.check(&"foo\\nbar").await.unwrap().is_ok());
+        assert!(store.check(&"foo\nbar").await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn max_line_len() {
+        let addr = server_with(|server| server.max_line_len(64));
+        let store = RemoteStore::new(addr, Quota::per_hour(1));
+
+        assert!(store.check(&"foo").await.unwrap().is_ok());
+        let e = store.check(&"f".repeat(64)).await.unwrap_err();
+        assert_eq!(e.to_string(), "request too long");
+        // connection closed by server is not reused and check goes through a new one.
+        assert!(store.check(&"foo").await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn registered_quota() {
+        let addr = server_with(|server| server.quota(Quota::per_hour(1)));
+
+        let store = RemoteStore::new(addr, Quota::per_hour(1));
+        assert!(store.check(&"foo").await.unwrap().is_ok());
+
+        let store = RemoteStore::new(addr, Quota::per_hour(2));
+        let e = store.check(&"foo").await.unwrap_err();
+        assert_eq!(e.to_string(), "unknown quota");
+        // connection is usable after rejected request.
+        assert_eq!(store.check(&"foo").await.unwrap_err().to_string(), "unknown quota");
+    }
+
+    #[tokio::test]
+    async fn max_quotas() {
+        let addr = server_with(|server| server.max_quotas(1));
+
+        let store1 = RemoteStore::new(addr, Quota::per_hour(1));
+        assert!(store1.check(&"foo").await.unwrap().is_ok());
+
+        let store2 = RemoteStore::new(addr, Quota::per_hour(2));
+        let e = store2.check(&"foo").await.unwrap_err();
+        assert_eq!(e.to_string(), "too many quotas");
+
+        assert!(store1.check(&"foo").await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn max_connections() {
+        let addr = server_with(|server| server.max_connections(1));
+
+        // pooled connection of store1 stays open.
+        let store1 = RemoteStore::new(addr, Quota::per_hour(1));
+        assert!(store1.check(&"foo").await.unwrap().is_ok());
+
+        let store2 = RemoteStore::new(addr, Quota::per_hour(1));
+        let e = store2.check(&"bar").await.unwrap_err();
+        assert_eq!(e.to_string(), "too many connections");
+
+        // connection is released when client closed it.
+        drop(store1);
+        let mut res = store2.check(&"bar").await;
+        for _ in 0..100 {
+            if res.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            res = store2.check(&"bar").await;
+        }
+        assert!(res.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn sweep() {
+        let policy = EvictionPolicy::new().sweep_interval(Duration::from_millis(50));
+        let addr = server_with(|server| server.eviction(policy.clone()));
+        let store = RemoteStore::new(addr, Quota::per_second(1000));
+
+        assert!(store.check(&"foo").await.unwrap().is_ok());
+        assert_eq!(policy.evicted(), 0);
+
+        // stale key is swept by server without any rate limit check.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(policy.evicted(), 1);
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        // listener never answers.
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let store =
+            RemoteStore::new(listener.local_addr().unwrap(), Quota::per_hour(1)).timeout(Duration::from_millis(50));
+
+        let e = store.check(&"foo").await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(parse_request("CHECK 0 1 foo").is_none());
+        assert!(parse_request("CHECK 1 0 foo").is_none());
+        assert!(parse_request("CHECK 1 1").is_none());
+        assert_eq!(parse_request("CHECK 1 1 foo bar").unwrap().1, "foo bar");
+
+        assert_eq!(
+            parse_response("ERR malformed request").unwrap_err().to_string(),
+            "malformed request"
+        );
+        assert!(parse_response("OK 1 2").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use core::cmp;
+use core::{cmp, time::Duration};
 
 use http::{
     header::{HeaderName, HeaderValue},
@@ -11,28 +11,74 @@ use crate::{nanos::Nanos, quota::Quota};
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct RateSnapshot {
     // The "weight" of a single packet in units of time.
-    t: Nanos,
+    pub(crate) t: Nanos,
     // The "burst capacity" of the bucket.
-    tau: Nanos,
+    pub(crate) tau: Nanos,
     // The time at which the measurement was taken.
     pub(crate) time_of_measurement: Nanos,
     // The next time a cell is expected to arrive
     pub(crate) tat: Nanos,
 }
 
+/// Style of rate limit related response headers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeaderStyle {
+    /// legacy `x-ratelimit-*` headers and `retry-after` header.
+    #[default]
+    Legacy,
+    /// `ratelimit-*` headers of IETF draft
+    /// [RateLimit header fields for HTTP](https://datatracker.ietf.org/doc/draft-ietf-httpapi-ratelimit-headers/)
+    /// and `retry-after` header.
+    Ietf,
+}
+
 const X_RT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
 const X_RT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
 
+const RT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+const RT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
 impl RateSnapshot {
     /// extend response headers with headers
     /// Header: `x-ratelimit-limit: <num>`
     /// Header: `x-ratelimit-remaining: <num>`
     pub fn extend_response<Ext>(&self, res: &mut Response<Ext>) {
+        self.extend_response_with(res, HeaderStyle::Legacy)
+    }
+
+    /// extend response headers with headers of given style.
+    ///
+    /// [HeaderStyle::Legacy] is the same as [RateSnapshot::extend_response].
+    ///
+    /// [HeaderStyle::Ietf]:
+    /// Header: `ratelimit-limit: <num>`
+    /// Header: `ratelimit-remaining: <num>`
+    /// Header: `ratelimit-reset: <num in second until quota is fully replenished>`
+    /// Header: `ratelimit-policy: <num>;w=<num in second to replenish full quota>`
+    pub fn extend_response_with<Ext>(&self, res: &mut Response<Ext>, style: HeaderStyle) {
         let burst_size = self.quota().burst_size().get();
         let remaining_burst_capacity = self.remaining_burst_capacity();
         let headers = res.headers_mut();
-        headers.insert(X_RT_LIMIT, HeaderValue::from(burst_size));
-        headers.insert(X_RT_REMAINING, HeaderValue::from(remaining_burst_capacity));
+        match style {
+            HeaderStyle::Legacy => {
+                headers.insert(X_RT_LIMIT, HeaderValue::from(burst_size));
+                headers.insert(X_RT_REMAINING, HeaderValue::from(remaining_burst_capacity));
+            }
+            HeaderStyle::Ietf => {
+                // quota is fully replenished when time reaches tat - t.
+                let reset = ceil_secs(self.tat.saturating_sub(self.time_of_measurement + self.t).into());
+                let window = cmp::max(ceil_secs(self.tau.into()), 1);
+                headers.insert(RT_LIMIT, HeaderValue::from(burst_size));
+                headers.insert(RT_REMAINING, HeaderValue::from(remaining_burst_capacity));
+                headers.insert(RT_RESET, HeaderValue::from(reset));
+                headers.insert(
+                    RT_POLICY,
+                    HeaderValue::from_str(&format!("{burst_size};w={window}")).unwrap(),
+                );
+            }
+        }
     }
 
     pub(crate) const fn new(t: Nanos, tau: Nanos, time_of_measurement: Nanos, tat: Nanos) -> Self {
@@ -49,12 +95,17 @@ impl RateSnapshot {
         Quota::from_gcra_parameters(self.t, self.tau)
     }
 
-    fn remaining_burst_capacity(&self) -> u32 {
+    pub(crate) fn remaining_burst_capacity(&self) -> u32 {
         let t0 = self.time_of_measurement + self.t;
         (cmp::min((t0 + self.tau).saturating_sub(self.tat).as_u64(), self.tau.as_u64()) / self.t.as_u64()) as u32
     }
 }
 
+// round duration up to whole seconds.
+pub(crate) fn ceil_secs(dur: Duration) -> u64 {
+    dur.as_secs() + u64::from(dur.subsec_nanos() > 0)
+}
+
 #[cfg(test)]
 mod test {
     use core::time::Duration;
@@ -72,6 +123,41 @@ mod test {
         assert!(lim.check().is_err());
     }
 
+    #[test]
+    fn ietf_headers() {
+        use http::Response;
+
+        use super::HeaderStyle;
+
+        let clock = FakeRelativeClock::default();
+        let lim = RateLimiter::direct_with_clock(Quota::per_minute(2), &clock);
+
+        let header = |res: &Response<()>, name| res.headers().get(name).unwrap().to_str().unwrap().to_owned();
+
+        let mut res = Response::new(());
+        lim.check().unwrap().extend_response_with(&mut res, HeaderStyle::Ietf);
+        assert_eq!(header(&res, "ratelimit-limit"), "2");
+        assert_eq!(header(&res, "ratelimit-remaining"), "1");
+        assert_eq!(header(&res, "ratelimit-reset"), "30");
+        assert_eq!(header(&res, "ratelimit-policy"), "2;w=60");
+        assert!(res.headers().get("x-ratelimit-limit").is_none());
+
+        clock.advance(Duration::from_millis(500));
+        let mut res = Response::new(());
+        lim.check().unwrap().extend_response_with(&mut res, HeaderStyle::Ietf);
+        assert_eq!(header(&res, "ratelimit-remaining"), "0");
+        assert_eq!(header(&res, "ratelimit-reset"), "60");
+
+        let mut res = Response::new(());
+        lim.check().unwrap_err();
+        lim.check().unwrap_err();
+        clock.advance(Duration::from_secs(60));
+        lim.check().unwrap().extend_response_with(&mut res, HeaderStyle::Legacy);
+        assert_eq!(header(&res, "x-ratelimit-limit"), "2");
+        assert_eq!(header(&res, "x-ratelimit-remaining"), "1");
+        assert!(res.headers().get("ratelimit-limit").is_none());
+    }
+
     #[test]
     fn state_snapshot_tracks_quota_accurately() {
         let period = Duration::from_millis(90);
@@ -1,12 +1,15 @@
 use core::hash::Hash;
 
-use crate::{gcra::NotUntil, quota::Quota, snapshot::RateSnapshot, state::RateLimiter, state::StateStore, timer};
+use crate::{
+    gcra::NotUntil, quota::Quota, snapshot::RateSnapshot, state::RateLimiter, state::StateStore, timer,
+    timer::Reference,
+};
 
 #[cfg(test)]
 use core::num::NonZeroU32;
 
 #[cfg(test)]
-use crate::{error::InsufficientCapacity, timer::Reference};
+use crate::error::InsufficientCapacity;
 
 // A trait for state stores with one rate limiting state per key.
 //
@@ -37,8 +40,15 @@ where
 
     /// Constructs a new keyed rate limiter explicitly backed by a
     /// [`HashMap`][std::collections::HashMap].
+    #[cfg(test)]
     pub(crate) fn hashmap(quota: Quota) -> Self {
-        let state = HashMapStateStore::default();
+        Self::hashmap_with_policy(quota, crate::eviction::EvictionPolicy::default())
+    }
+
+    /// Constructs a new keyed rate limiter backed by a [`HashMap`][std::collections::HashMap] with
+    /// given eviction policy.
+    pub(crate) fn hashmap_with_policy(quota: Quota, policy: crate::eviction::EvictionPolicy) -> Self {
+        let state = HashMapStateStore::new(policy);
         let timer = timer::DefaultTimer;
         RateLimiter::new(quota, state, &timer)
     }
@@ -85,7 +95,6 @@ where
     }
 }
 
-#[cfg(test)]
 /// Keyed rate limiters that can be "cleaned up".
 ///
 /// Any keyed state store implementing this trait allows users to evict elements that are
@@ -99,6 +108,20 @@ pub(crate) trait ShrinkableKeyedStateStore<K: Hash>: KeyedStateStore<K> {
     /// Remove those keys with state older than `drop_below`.
     fn retain_recent(&self, drop_below: crate::nanos::Nanos);
 
+    /// Start a sweep round covering keys currently stored. Keys are examined by following
+    /// [`retain_recent_batch`](#method.retain_recent_batch) calls.
+    fn start_sweep(&self) {}
+
+    /// Examine at most `batch` keys of current sweep round and remove those with state older than
+    /// `drop_below`. Returns `true` when current sweep round still has keys to examine.
+    ///
+    /// State store without incremental sweeping removes all stale keys at once.
+    fn retain_recent_batch(&self, drop_below: crate::nanos::Nanos, batch: usize) -> bool {
+        let _ = batch;
+        self.retain_recent(drop_below);
+        false
+    }
+
     /// Shrinks the capacity of the state store, if possible.
     ///
     /// If the state store does not support shrinking, this method is a no-op.
@@ -118,7 +141,6 @@ pub(crate) trait ShrinkableKeyedStateStore<K: Hash>: KeyedStateStore<K> {
     fn is_empty(&self) -> bool;
 }
 
-#[cfg(test)]
 /// # Keyed rate limiters - Housekeeping
 ///
 /// As the inputs to a keyed rate-limiter can be arbitrary keys, the set of retained keys retained
@@ -145,6 +167,23 @@ where
         self.state.retain_recent(drop_below);
     }
 
+    // Start a sweep round of keys currently in the rate limiter.
+    pub(crate) fn start_sweep(&self) {
+        self.state.start_sweep();
+    }
+
+    // Examine a bounded batch of keys in current sweep round and remove stale ones. Returns `true`
+    // when the sweep round is not finished.
+    pub(crate) fn retain_recent_batch(&self, batch: usize) -> bool {
+        let drop_below = self.clock.now().duration_since(self.start);
+        self.state.retain_recent_batch(drop_below, batch)
+    }
+
+    // Time elapsed since the rate limiter was created.
+    pub(crate) fn elapsed(&self) -> crate::nanos::Nanos {
+        self.clock.now().duration_since(self.start)
+    }
+
     // Shrinks the capacity of the rate limiter's state store, if possible.
     pub(crate) fn shrink_to_fit(&self) {
         self.state.shrink_to_fit();
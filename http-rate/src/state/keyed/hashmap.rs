@@ -1,8 +1,12 @@
-use core::hash::Hash;
+use core::{cmp, hash::Hash};
 
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
 
 use crate::{
+    eviction::EvictionPolicy,
     nanos::Nanos,
     state::{InMemoryState, StateStore},
 };
@@ -15,7 +19,53 @@ use crate::{quota::Quota, state::RateLimiter, timer::Timer};
 ///
 /// The `HashMapStateStore` is the default state store in `std` when no other thread-safe
 /// features are enabled.
-pub(crate) type HashMapStateStore<K> = Mutex<HashMap<K, InMemoryState>>;
+pub(crate) struct HashMapStateStore<K> {
+    state: Mutex<State<K>>,
+    policy: EvictionPolicy,
+}
+
+pub(crate) struct State<K> {
+    pub(crate) map: HashMap<K, Entry>,
+    // logical clock advanced by every access of state. it orders keys by how recently they are accessed.
+    tick: u64,
+    // keys in order of insertion. periodic sweep walks the queue in bounded batches so that a single
+    // rate limit check never scans the whole map while holding the lock.
+    queue: VecDeque<K>,
+    // number of keys left to examine in current sweep round.
+    remaining: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct Entry {
+    state: InMemoryState,
+    // tick of last access.
+    last: u64,
+}
+
+impl<K> Default for HashMapStateStore<K> {
+    fn default() -> Self {
+        Self::new(EvictionPolicy::default())
+    }
+}
+
+impl<K> HashMapStateStore<K> {
+    pub(crate) fn new(policy: EvictionPolicy) -> Self {
+        Self {
+            state: Mutex::new(State {
+                map: HashMap::new(),
+                tick: 0,
+                queue: VecDeque::new(),
+                remaining: 0,
+            }),
+            policy,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn lock(&self) -> std::sync::LockResult<std::sync::MutexGuard<'_, State<K>>> {
+        self.state.lock()
+    }
+}
 
 impl<K> StateStore for HashMapStateStore<K>
 where
@@ -27,39 +77,130 @@ where
     where
         F: Fn(Option<Nanos>) -> Result<(T, Nanos), E>,
     {
-        let mut map = self.lock().unwrap();
-        if let Some(v) = (*map).get(key) {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some(v) = state.map.get_mut(key) {
             // fast path: a rate limiter is already present for the key.
-            return v.measure_and_replace_one(f);
+            v.last = tick;
+            return v.state.measure_and_replace_one(f);
+        }
+        // not-so-fast path: make room when state is full then make a new entry and measure it.
+        if let Some(max) = self.policy.max_keys {
+            if state.map.len() >= max.get() {
+                let evicted = evict_least_recent(&mut state.map, max.get());
+                state.retain_queue();
+                self.policy.record_evicted(evicted);
+            }
         }
-        // not-so-fast path: make a new entry and measure it.
-        let entry = (*map).entry(key.clone()).or_default();
-        entry.measure_and_replace_one(f)
+        state.queue.push_back(key.clone());
+        let entry = state.map.entry(key.clone()).or_default();
+        entry.last = tick;
+        entry.state.measure_and_replace_one(f)
+    }
+}
+
+// evict a batch of least recently accessed keys. evicting in batch amortizes the cost of scanning the map.
+fn evict_least_recent<K>(map: &mut HashMap<K, Entry>, max: usize) -> usize {
+    let len = map.len();
+    let mut n = cmp::max(cmp::max(max / 8, 1), (len + 1).saturating_sub(max));
+    if n >= len {
+        map.clear();
+        return len;
+    }
+
+    let mut ticks = map.values().map(|v| v.last).collect::<Vec<_>>();
+    let (_, threshold, _) = ticks.select_nth_unstable(n - 1);
+    let threshold = *threshold;
+
+    map.retain(|_, v| {
+        if n > 0 && v.last <= threshold {
+            n -= 1;
+            false
+        } else {
+            true
+        }
+    });
+
+    len - map.len()
+}
+
+impl<K> State<K>
+where
+    K: Hash + Eq,
+{
+    // drop keys no longer present in map from sweep queue.
+    fn retain_queue(&mut self) {
+        let Self {
+            map, queue, remaining, ..
+        } = self;
+        queue.retain(|k| map.contains_key(k));
+        *remaining = cmp::min(*remaining, queue.len());
     }
 }
 
-#[cfg(test)]
 impl<K> crate::state::keyed::ShrinkableKeyedStateStore<K> for HashMapStateStore<K>
 where
     K: Hash + Eq + Clone,
 {
     fn retain_recent(&self, drop_below: Nanos) {
-        let mut map = self.lock().unwrap();
-        map.retain(|_, v| !v.is_older_than(drop_below));
+        let mut state = self.state.lock().unwrap();
+        let len = state.map.len();
+        state.map.retain(|_, v| !v.state.is_older_than(drop_below));
+        state.retain_queue();
+        self.policy.record_evicted(len - state.map.len());
+    }
+
+    fn start_sweep(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.remaining = state.queue.len();
+    }
+
+    fn retain_recent_batch(&self, drop_below: Nanos, batch: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let State {
+            map, queue, remaining, ..
+        } = &mut *state;
+
+        let n = cmp::min(batch, *remaining);
+        *remaining -= n;
+
+        let mut evicted = 0;
+        for _ in 0..n {
+            let Some(key) = queue.pop_front() else { break };
+            if map.get(&key).is_some_and(|v| !v.state.is_older_than(drop_below)) {
+                queue.push_back(key);
+            } else if map.remove(&key).is_some() {
+                evicted += 1;
+            }
+        }
+        self.policy.record_evicted(evicted);
+
+        if *remaining > 0 {
+            return true;
+        }
+
+        // release memory when most of the capacity is unused. shrinking only happens after at least
+        // three quarters of capacity have been removed which amortizes it's cost over removed keys.
+        if map.capacity() > 64 && map.len() < map.capacity() / 4 {
+            map.shrink_to_fit();
+            queue.shrink_to_fit();
+        }
+
+        false
     }
 
     fn shrink_to_fit(&self) {
-        let mut map = self.lock().unwrap();
-        map.shrink_to_fit();
+        let mut state = self.state.lock().unwrap();
+        state.map.shrink_to_fit();
+        state.queue.shrink_to_fit();
     }
 
     fn len(&self) -> usize {
-        let map = self.lock().unwrap();
-        (*map).len()
+        self.state.lock().unwrap().map.len()
     }
     fn is_empty(&self) -> bool {
-        let map = self.lock().unwrap();
-        (*map).is_empty()
+        self.state.lock().unwrap().map.is_empty()
     }
 }
 
@@ -71,7 +212,7 @@ where
 {
     /// Constructs a new rate limiter with a custom clock, backed by a [`HashMap`].
     pub(crate) fn hashmap_with_clock(quota: Quota, clock: &C) -> Self {
-        let state = Mutex::new(HashMap::new());
+        let state = HashMapStateStore::default();
         RateLimiter::new(quota, state, clock)
     }
 }
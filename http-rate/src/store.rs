@@ -0,0 +1,32 @@
+use core::{convert::Infallible, future::Future, hash::Hash};
+
+use crate::{error::TooManyRequests, snapshot::RateSnapshot, RateLimit};
+
+/// A state store of keyed rate limiter which makes it's decision asynchronously.
+///
+/// [RateLimit] keeps it's state in memory of current process. When multiple processes(e.g. replicas
+/// of a service behind load balancer) should share the same quota the state has to be kept in a place
+/// they can all reach. Such a store is expected to make rate limiting decision with it's own clock so
+/// the result is not affected by clock difference between processes.
+///
+/// See `remote` module(guarded by `remote` crate feature) for a store backed by a shared limiter process.
+pub trait AsyncStateStore<K> {
+    /// Error of store itself. e.g. io error when communicating with a remote store.
+    type Error;
+
+    /// Rate limit with given key. Outer result is the outcome of talking to the store and inner one
+    /// is the rate limiting decision.
+    fn check(&self, key: &K) -> impl Future<Output = Result<Result<RateSnapshot, TooManyRequests>, Self::Error>>;
+}
+
+impl<K> AsyncStateStore<K> for RateLimit<K>
+where
+    K: Clone + Hash + Eq,
+{
+    type Error = Infallible;
+
+    #[inline]
+    async fn check(&self, key: &K) -> Result<Result<RateSnapshot, TooManyRequests>, Self::Error> {
+        Ok(RateLimit::check(self, key))
+    }
+}
@@ -28,6 +28,7 @@ use crate::{
         StatusCode,
         response::{Parts, Response},
     },
+    shutdown::ShutdownToken,
     util::{
         buffered::{BufferedIo, ListWriteBuf, ReadBuf, WriteBuf},
         timer::{KeepAlive, Timeout},
@@ -63,6 +64,7 @@ pub(crate) async fn run<
     config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
     service: &'a S,
     date: &'a D,
+    shutdown: &'a ShutdownToken,
 ) -> Result<(), Error<S::Error, BE>>
 where
     S: Service<ExtRequest<ReqB>, Response = Response<ResB>>,
@@ -77,7 +79,7 @@ where
         EitherBuf::Right(WriteBuf::<WRITE_BUF_LIMIT>::default())
     };
 
-    Dispatcher::new(io, addr, timer, config, service, date, write_buf)
+    Dispatcher::new(io, addr, timer, config, service, date, shutdown, write_buf)
         .run()
         .await
 }
@@ -88,6 +90,7 @@ struct Dispatcher<'a, St, S, ReqB, W, D, const HEADER_LIMIT: usize, const READ_B
     timer: Timer<'a>,
     ctx: Context<'a, D, HEADER_LIMIT>,
     service: &'a S,
+    shutdown: &'a ShutdownToken,
     _phantom: PhantomData<ReqB>,
 }
 
@@ -166,6 +169,7 @@ where
     W: H1BufWrite,
     D: DateTime,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new<const WRITE_BUF_LIMIT: usize>(
         io: &'a mut St,
         addr: SocketAddr,
@@ -173,6 +177,7 @@ where
         config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
         service: &'a S,
         date: &'a D,
+        shutdown: &'a ShutdownToken,
         write_buf: W,
     ) -> Self {
         Self {
@@ -180,6 +185,7 @@ where
             timer: Timer::new(timer, config.keep_alive_timeout, config.request_head_timeout),
             ctx: Context::with_addr(addr, date),
             service,
+            shutdown,
             _phantom: PhantomData,
         }
     }
@@ -201,12 +207,22 @@ where
 
     async fn _run(&mut self) -> Result<(), Error<S::Error, BE>> {
         self.timer.update(self.ctx.date().now());
-        let read = self
-            .io
-            .read()
-            .timeout(self.timer.get())
-            .await
-            .map_err(|_| self.timer.map_to_err())??;
+
+        // idle connection is closed on graceful shutdown. partial request in read buffer is still served.
+        let idle = self.io.read_buf.is_empty();
+        let read = self.io.read().timeout(self.timer.get());
+        let read = if idle {
+            match read.select(self.shutdown.wait()).await {
+                SelectOutput::A(read) => read,
+                SelectOutput::B(_) => {
+                    self.ctx.set_close();
+                    return Ok(());
+                }
+            }
+        } else {
+            read.await
+        };
+        let read = read.map_err(|_| self.timer.map_to_err())??;
 
         if read == 0 {
             self.ctx.set_close();
@@ -262,7 +278,7 @@ where
                 }
             }
 
-            if !body_reader.decoder.is_eof() {
+            if !body_reader.decoder.is_eof() || self.shutdown.is_shutdown() {
                 self.ctx.set_close();
                 break;
             }
@@ -272,6 +288,10 @@ where
     }
 
     fn encode_head(&mut self, parts: Parts, body: &impl Stream) -> Result<TransferCoding, ProtoError> {
+        // disable keep-alive on graceful shutdown so client would not reuse the connection.
+        if self.shutdown.is_shutdown() {
+            self.ctx.set_close();
+        }
         self.ctx.encode_head(parts, body, &mut self.io.write_buf)
     }
 
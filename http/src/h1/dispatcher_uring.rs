@@ -14,7 +14,7 @@ use futures_core::stream::Stream;
 use pin_project_lite::pin_project;
 use xitca_io::io_uring::{AsyncBufRead, AsyncBufWrite, BoundedBuf, write_all};
 use xitca_service::Service;
-use xitca_unsafe_collection::futures::SelectOutput;
+use xitca_unsafe_collection::futures::{Select as _, SelectOutput};
 
 use crate::{
     bytes::{Bytes, BytesMut},
@@ -22,6 +22,7 @@ use crate::{
     date::DateTime,
     h1::{body::RequestBody, error::Error},
     http::response::Response,
+    shutdown::ShutdownToken,
     util::timer::{KeepAlive, Timeout},
 };
 
@@ -43,6 +44,7 @@ pub(super) struct Dispatcher<'a, Io, S, ReqB, D, const H_LIMIT: usize, const R_L
     timer: Timer<'a>,
     ctx: Context<'a, D, H_LIMIT>,
     service: &'a S,
+    shutdown: &'a ShutdownToken,
     _phantom: PhantomData<ReqB>,
 }
 
@@ -85,6 +87,7 @@ where
         config: HttpServiceConfig<H_LIMIT, R_LIMIT, W_LIMIT>,
         service: &'a S,
         date: &'a D,
+        shutdown: &'a ShutdownToken,
     ) -> Result<(), Error<S::Error, BE>> {
         let mut dispatcher = Dispatcher::<_, _, _, _, H_LIMIT, R_LIMIT, W_LIMIT> {
             io,
@@ -92,6 +95,7 @@ where
             timer: Timer::new(timer, config.keep_alive_timeout, config.request_head_timeout),
             ctx: Context::with_addr(addr, date),
             service,
+            shutdown,
             _phantom: PhantomData,
         };
 
@@ -124,7 +128,23 @@ where
     ) -> (Result<(), Error<S::Error, BE>>, BytesMut, BytesMut) {
         self.timer.update(self.ctx.date().now());
 
-        match read_buf.read(&self.io).timeout(self.timer.get()).await {
+        // idle connection is closed on graceful shutdown. partial request in read buffer is still served.
+        let idle = read_buf.is_empty();
+        let read = read_buf.read(&self.io).timeout(self.timer.get());
+        let read = if idle {
+            match read.select(self.shutdown.wait()).await {
+                SelectOutput::A(read) => read,
+                // read_buf is lost during cancel. it's empty so make a new one instead.
+                SelectOutput::B(_) => {
+                    self.ctx.set_close();
+                    return (Ok(()), BytesMut::new(), write_buf);
+                }
+            }
+        } else {
+            read.await
+        };
+
+        match read {
             Ok((res, r_buf)) => {
                 read_buf = r_buf;
                 match res {
@@ -172,6 +192,11 @@ where
                 Err(e) => return (Err(Error::Service(e)), read_buf, write_buf),
             };
 
+            // disable keep-alive on graceful shutdown so client would not reuse the connection.
+            if self.shutdown.is_shutdown() {
+                self.ctx.set_close();
+            }
+
             let mut encoder = match self.ctx.encode_head(parts, &body, &mut write_buf) {
                 Ok(encoder) => encoder,
                 Err(e) => return (Err(e.into()), read_buf, write_buf),
@@ -224,6 +249,11 @@ where
                     }
                 }
             }
+
+            if self.shutdown.is_shutdown() {
+                self.ctx.set_close();
+                break;
+            }
         }
 
         (Ok(()), read_buf, write_buf)
@@ -41,9 +41,17 @@ where
             .await
             .map_err(|_| HttpServiceError::Timeout(TimeoutError::TlsAccept))??;
 
-        super::dispatcher::run(&mut io, addr, timer, self.config, &self.service, self.date.get())
-            .await
-            .map_err(Into::into)
+        super::dispatcher::run(
+            &mut io,
+            addr,
+            timer,
+            self.config,
+            &self.service,
+            self.date.get(),
+            &self.shutdown,
+        )
+        .await
+        .map_err(Into::into)
     }
 }
 
@@ -60,6 +68,7 @@ use {
 use crate::{
     config::HttpServiceConfig,
     date::{DateTime, DateTimeService},
+    shutdown::ShutdownToken,
     util::timer::KeepAlive,
 };
 
@@ -67,6 +76,7 @@ use crate::{
 pub struct H1UringService<S, A, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_BUF_LIMIT: usize> {
     pub(crate) config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
     pub(crate) date: DateTimeService,
+    pub(crate) shutdown: ShutdownToken,
     pub(crate) service: S,
     pub(crate) tls_acceptor: A,
 }
@@ -83,6 +93,7 @@ impl<S, A, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_B
         Self {
             config,
             date: DateTimeService::new(),
+            shutdown: ShutdownToken::current(),
             service,
             tls_acceptor,
         }
@@ -113,9 +124,17 @@ where
             .await
             .map_err(|_| HttpServiceError::Timeout(TimeoutError::TlsAccept))??;
 
-        super::dispatcher_uring::Dispatcher::run(io, addr, timer, self.config, &self.service, self.date.get())
-            .await
-            .map_err(Into::into)
+        super::dispatcher_uring::Dispatcher::run(
+            io,
+            addr,
+            timer,
+            self.config,
+            &self.service,
+            self.date.get(),
+            &self.shutdown,
+        )
+        .await
+        .map_err(Into::into)
     }
 }
 
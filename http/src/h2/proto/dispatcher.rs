@@ -1,6 +1,6 @@
 use core::{
     cmp, fmt,
-    future::{Future, poll_fn},
+    future::{Future, pending, poll_fn},
     marker::PhantomData,
     net::SocketAddr,
     pin::{Pin, pin},
@@ -28,6 +28,7 @@ use crate::{
         Extension, Request, RequestExt, Response, Version,
         header::{CONNECTION, CONTENT_LENGTH, DATE, HeaderMap, HeaderName, HeaderValue, TRAILER},
     },
+    shutdown::ShutdownToken,
     util::{futures::Queue, timer::KeepAlive},
 };
 
@@ -39,6 +40,7 @@ pub(crate) struct Dispatcher<'a, TlsSt, S, ReqB> {
     ka_dur: Duration,
     service: &'a S,
     date: &'a DateTimeHandle,
+    shutdown: &'a ShutdownToken,
     _req_body: PhantomData<ReqB>,
 }
 
@@ -58,6 +60,7 @@ where
         ka_dur: Duration,
         service: &'a S,
         date: &'a DateTimeHandle,
+        shutdown: &'a ShutdownToken,
     ) -> Self {
         Self {
             io,
@@ -66,6 +69,7 @@ where
             ka_dur,
             service,
             date,
+            shutdown,
             _req_body: PhantomData,
         }
     }
@@ -78,6 +82,7 @@ where
             ka_dur,
             service,
             date,
+            shutdown,
             ..
         } = self;
 
@@ -98,8 +103,35 @@ where
 
         let mut queue = Queue::new();
 
+        // graceful shutdown only needs to be observed once.
+        let mut is_shutdown = false;
+
         loop {
-            match io.accept().select(try_poll_queue(&mut queue, &mut ping_pong)).await {
+            let wait_shutdown = async move {
+                if is_shutdown {
+                    pending().await
+                } else {
+                    shutdown.wait().await
+                }
+            };
+
+            let res = match io
+                .accept()
+                .select(try_poll_queue(&mut queue, &mut ping_pong))
+                .select(wait_shutdown)
+                .await
+            {
+                SelectOutput::A(res) => res,
+                SelectOutput::B(_) => {
+                    trace!("Server shutting down. Sending GOAWAY");
+                    is_shutdown = true;
+                    // stop accepting new streams. in-flight streams are drained.
+                    io.graceful_shutdown();
+                    continue;
+                }
+            };
+
+            match res {
                 SelectOutput::A(Some(Ok((req, tx)))) => {
                     // Convert http::Request body type to crate::h2::Body
                     // and reconstruct as HttpRequest.
@@ -61,6 +61,7 @@ where
             self.config.keep_alive_timeout,
             &self.service,
             self.date.get(),
+            &self.shutdown,
         );
 
         dispatcher.run().await?;
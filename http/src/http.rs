@@ -115,7 +115,7 @@ where
 }
 
 #[cfg(feature = "router")]
-use super::util::service::router::Params;
+use super::util::service::router::{MatchedPath, Params};
 
 pin_project! {
     /// extension types for [Request]
@@ -153,6 +153,8 @@ impl Extension {
             addr,
             #[cfg(feature = "router")]
             params: Default::default(),
+            #[cfg(feature = "router")]
+            matched_path: Default::default(),
         }))
     }
 }
@@ -162,6 +164,8 @@ struct _Extension {
     addr: SocketAddr,
     #[cfg(feature = "router")]
     params: Params,
+    #[cfg(feature = "router")]
+    matched_path: MatchedPath,
 }
 
 impl<B> RequestExt<B> {
@@ -255,6 +259,18 @@ mod router {
         pub fn params_mut(&mut self) -> &mut Params {
             &mut self.ext.0.params
         }
+
+        /// retrieve shared reference of [MatchedPath].
+        #[inline]
+        pub fn matched_path(&self) -> &MatchedPath {
+            &self.ext.0.matched_path
+        }
+
+        /// retrieve exclusive reference of [MatchedPath].
+        #[inline]
+        pub fn matched_path_mut(&mut self) -> &mut MatchedPath {
+            &mut self.ext.0.matched_path
+        }
     }
 
     impl<B> Borrow<Params> for RequestExt<B> {
@@ -270,6 +286,20 @@ mod router {
             self.params_mut()
         }
     }
+
+    impl<B> Borrow<MatchedPath> for RequestExt<B> {
+        #[inline]
+        fn borrow(&self) -> &MatchedPath {
+            self.matched_path()
+        }
+    }
+
+    impl<B> BorrowMut<MatchedPath> for RequestExt<B> {
+        #[inline]
+        fn borrow_mut(&mut self) -> &mut MatchedPath {
+            self.matched_path_mut()
+        }
+    }
 }
 
 /// trait for Borrow &T from &Self.
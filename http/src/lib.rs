@@ -59,6 +59,8 @@ pub mod h1;
 pub mod h2;
 #[cfg(feature = "http3")]
 pub mod h3;
+#[cfg(feature = "runtime")]
+pub mod shutdown;
 
 /// re-export bytes crate as module.
 pub use xitca_io::bytes;
@@ -14,6 +14,7 @@ use super::{
     date::{DateTime, DateTimeService},
     error::{HttpServiceError, TimeoutError},
     http::{Request, RequestExt, Response},
+    shutdown::ShutdownToken,
     util::timer::{KeepAlive, Timeout},
     version::AsVersion,
 };
@@ -29,6 +30,7 @@ pub struct HttpService<
 > {
     pub(crate) config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
     pub(crate) date: DateTimeService,
+    pub(crate) shutdown: ShutdownToken,
     pub(crate) service: S,
     pub(crate) tls_acceptor: A,
     _body: PhantomData<(St, ReqB)>,
@@ -45,6 +47,7 @@ impl<St, S, ReqB, A, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, con
         Self {
             config,
             date: DateTimeService::new(),
+            shutdown: ShutdownToken::current(),
             service,
             tls_acceptor,
             _body: PhantomData,
@@ -120,6 +123,7 @@ where
                         self.config,
                         &self.service,
                         self.date.get(),
+                        &self.shutdown,
                     )
                     .await
                     .map_err(From::from),
@@ -142,6 +146,7 @@ where
                             self.config.keep_alive_timeout,
                             &self.service,
                             self.date.get(),
+                            &self.shutdown,
                         )
                         .run()
                         .await
@@ -168,6 +173,7 @@ where
                         self.config,
                         &self.service,
                         self.date.get(),
+                        &self.shutdown,
                     )
                     .await
                     .map_err(From::from)
@@ -0,0 +1,109 @@
+//! graceful shutdown signal shared by connections of the same worker thread.
+//!
+//! every thread owns a [ShutdownToken] obtained with [ShutdownToken::current]. once it's triggered:
+//! - http/1 connection disables keep-alive. idle connection is closed and in-flight response is sent with
+//!   `Connection: close` header.
+//! - http/2 connection sends GOAWAY frame and stop accepting new streams. in-flight streams are drained.
+//!
+//! `xitca-web` triggers the token when server is stopping. when using `xitca-http` with `xitca-server`
+//! directly the token has to be triggered manually from server's worker stop callback.
+//!
+//! # Examples
+//! ```rust
+//! # use xitca_http::shutdown::ShutdownToken;
+//! # async fn example() {
+//! // token of current thread. all clones observe the same shutdown state.
+//! let token = ShutdownToken::current();
+//! assert!(!token.is_shutdown());
+//!
+//! // trigger shutdown. usually called when server is stopping.
+//! token.shutdown();
+//!
+//! // wait for shutdown. resolve immediately as token is already triggered.
+//! ShutdownToken::current().wait().await;
+//! # }
+//! ```
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// signal for graceful shutdown. cloned token observes the same shutdown state.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownToken(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    triggered: AtomicBool,
+    notify: Notify,
+}
+
+thread_local! {
+    static CURRENT: ShutdownToken = ShutdownToken::default();
+}
+
+impl ShutdownToken {
+    /// construct a new token independent from any thread.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// token of current thread.
+    pub fn current() -> Self {
+        CURRENT.with(Clone::clone)
+    }
+
+    /// check if shutdown is triggered.
+    #[inline]
+    pub fn is_shutdown(&self) -> bool {
+        self.0.triggered.load(Ordering::Acquire)
+    }
+
+    /// trigger shutdown and wake up all waiters. triggering an already triggered token is a no-op.
+    pub fn shutdown(&self) {
+        if !self.0.triggered.swap(true, Ordering::AcqRel) {
+            self.0.notify.notify_waiters();
+        }
+    }
+
+    /// wait for shutdown to be triggered.
+    pub async fn wait(&self) {
+        let notified = self.0.notify.notified();
+        if self.is_shutdown() {
+            return;
+        }
+        notified.await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{
+        pin::pin,
+        task::{Context, Waker},
+    };
+
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use super::*;
+
+    #[test]
+    fn shutdown() {
+        let token = ShutdownToken::new();
+        let token2 = token.clone();
+
+        let mut wait = pin!(token2.wait());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(wait.as_mut().poll(&mut cx).is_pending());
+
+        token.shutdown();
+        assert!(token2.is_shutdown());
+        wait.now_or_panic();
+        token2.wait().now_or_panic();
+
+        // thread local token is independent.
+        assert!(!ShutdownToken::current().is_shutdown());
+    }
+}
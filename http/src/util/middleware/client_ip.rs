@@ -0,0 +1,413 @@
+//! client ip address resolution with trusted proxy configuration.
+
+use core::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use std::error;
+
+use xitca_service::Service;
+
+use crate::http::{
+    Extensions,
+    header::{FORWARDED, HeaderMap, HeaderName},
+};
+
+/// client ip address resolved by [`ClientIpResolver`].
+///
+/// [`ClientIpResolver`] middleware attaches it to [`Request`]'s [`Extensions`].
+///
+/// [`Request`]: crate::http::Request
+/// [`Extensions`]: crate::http::Extensions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// resolved ip address.
+    #[inline]
+    pub fn ip(&self) -> IpAddr {
+        self.0
+    }
+
+    /// get client ip from request's [`Extensions`] attached by [`ClientIpResolver`] middleware. peer address
+    /// of connection is used when it's absent.
+    ///
+    /// [`Extensions`]: crate::http::Extensions
+    pub fn from_extensions(extensions: &Extensions, addr: &SocketAddr) -> Self {
+        extensions
+            .get::<Self>()
+            .copied()
+            .unwrap_or_else(|| Self(addr.ip().to_canonical()))
+    }
+}
+
+impl From<IpAddr> for ClientIp {
+    fn from(ip: IpAddr) -> Self {
+        Self(ip)
+    }
+}
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// a range of ip addresses in CIDR notation. e.g. `10.0.0.0/8`, `2001:db8::/32`.
+///
+/// a single address without prefix length is a range of itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// construct a range from network address and prefix length.
+    ///
+    /// # Panics
+    /// when prefix length exceeds bit length of address.
+    pub fn new(addr: IpAddr, prefix: u8) -> Self {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        assert!(prefix <= max, "prefix length {prefix} exceeds {max}");
+        Self { addr, prefix }
+    }
+
+    /// check if given address is in range.
+    ///
+    /// ipv4-mapped ipv6 address is treated as ipv4 address.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = InvalidCidr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr = addr.parse::<IpAddr>().map_err(|_| InvalidCidr)?.to_canonical();
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().ok().filter(|p| *p <= max).ok_or(InvalidCidr)?,
+            None => max,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+/// error type when parsing [`Cidr`] from string failed.
+#[derive(Debug)]
+pub struct InvalidCidr;
+
+impl fmt::Display for InvalidCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid CIDR notation")
+    }
+}
+
+impl error::Error for InvalidCidr {}
+
+/// request header honored by [`ClientIpResolver`] when peer address is a trusted proxy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    /// `x-forwarded-for` header.
+    #[default]
+    XForwardedFor,
+    /// `forwarded` header defined by RFC 7239. `for` parameter is used.
+    Forwarded,
+    /// `x-real-ip` header. it contains a single address set by the closest proxy.
+    XRealIp,
+}
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+const X_REAL_IP: HeaderName = HeaderName::from_static("x-real-ip");
+
+/// resolver and middleware builder for client ip address.
+///
+/// without trusted proxy the peer address of connection is always used and forwarding headers are ignored.
+/// when peer address is in range of trusted proxies the configured [`ForwardedHeader`] is walked from right
+/// to left. every address in range of trusted proxies is skipped and the first untrusted one is resolved as
+/// client ip. when every address is trusted the left most one is used. an unparsable entry stops the walk and
+/// the last trusted address is used.
+///
+/// as middleware it attaches resolved [`ClientIp`] to request's extensions.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_http::{http::{header::HeaderValue, HeaderMap}, util::middleware::client_ip::ClientIpResolver};
+/// let resolver = ClientIpResolver::new()
+///     // trust reverse proxies in private network.
+///     .trust("10.0.0.0/8".parse().unwrap());
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.2"));
+///
+/// // request forwarded by trusted proxy.
+/// let ip = resolver.resolve(&headers, &"10.0.0.1:80".parse().unwrap());
+/// assert_eq!(ip.to_string(), "203.0.113.7");
+///
+/// // request from untrusted peer can't spoof it's address.
+/// let ip = resolver.resolve(&headers, &"198.51.100.1:80".parse().unwrap());
+/// assert_eq!(ip.to_string(), "198.51.100.1");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ClientIpResolver {
+    trusted: Vec<Cidr>,
+    header: ForwardedHeader,
+}
+
+impl ClientIpResolver {
+    /// construct a resolver without trusted proxy. [`ForwardedHeader::XForwardedFor`] is honored once
+    /// proxies are trusted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// trust proxies in given range.
+    pub fn trust(mut self, cidr: Cidr) -> Self {
+        self.trusted.push(cidr);
+        self
+    }
+
+    /// set the header honored when peer address is a trusted proxy.
+    pub fn header(mut self, header: ForwardedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// resolve client ip address from request headers and peer address of connection.
+    pub fn resolve(&self, headers: &HeaderMap, addr: &SocketAddr) -> ClientIp {
+        let peer = addr.ip().to_canonical();
+        if !self.is_trusted(&peer) {
+            return ClientIp(peer);
+        }
+
+        let ip = match self.header {
+            ForwardedHeader::XRealIp => headers
+                .get(X_REAL_IP)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| parse_node(v.trim()))
+                .unwrap_or(peer),
+            ForwardedHeader::XForwardedFor => self.walk(peer, headers, &X_FORWARDED_FOR, |v| parse_node(v.trim())),
+            ForwardedHeader::Forwarded => self.walk(peer, headers, &FORWARDED, forwarded_for),
+        };
+
+        ClientIp(ip)
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.trusted.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    // walk comma separated elements of all header values from right to left.
+    fn walk<F>(&self, peer: IpAddr, headers: &HeaderMap, name: &HeaderName, parse: F) -> IpAddr
+    where
+        F: Fn(&str) -> Option<IpAddr>,
+    {
+        let values = headers.get_all(name).iter().collect::<Vec<_>>();
+        let mut last = peer;
+        for value in values.into_iter().rev() {
+            let Ok(value) = value.to_str() else {
+                return last;
+            };
+            for elem in value.rsplit(',') {
+                match parse(elem) {
+                    Some(ip) if self.is_trusted(&ip) => last = ip,
+                    Some(ip) => return ip,
+                    None => return last,
+                }
+            }
+        }
+        last
+    }
+}
+
+// parse node of forwarding header. it can be an ip address optionally with port and brackets.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim_matches('"');
+    let ip = node
+        .parse::<IpAddr>()
+        .or_else(|_| node.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .or_else(|_| node.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>())
+        .ok()?;
+    Some(ip.to_canonical())
+}
+
+// parse `for` parameter of a forwarded element.
+fn forwarded_for(elem: &str) -> Option<IpAddr> {
+    elem.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("for")
+            .then(|| parse_node(value.trim()))?
+    })
+}
+
+impl<S, E> Service<Result<S, E>> for ClientIpResolver {
+    type Response = service::ClientIpService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::ClientIpService {
+            service,
+            resolver: self.clone(),
+        })
+    }
+}
+
+mod service {
+    use xitca_service::ready::ReadyService;
+
+    use crate::http::{BorrowReq, BorrowReqMut};
+
+    use super::*;
+
+    pub struct ClientIpService<S> {
+        pub(super) service: S,
+        pub(super) resolver: ClientIpResolver,
+    }
+
+    impl<S, Req> Service<Req> for ClientIpService<S>
+    where
+        S: Service<Req>,
+        Req: BorrowReq<HeaderMap> + BorrowReq<SocketAddr> + BorrowReqMut<Extensions>,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+
+        #[inline]
+        async fn call(&self, mut req: Req) -> Result<Self::Response, Self::Error> {
+            let ip = self.resolver.resolve(req.borrow(), req.borrow());
+            BorrowReqMut::<Extensions>::borrow_mut(&mut req).insert(ip);
+            self.service.call(req).await
+        }
+    }
+
+    impl<S> ReadyService for ClientIpService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::header::HeaderValue;
+
+    use super::*;
+
+    fn resolver() -> ClientIpResolver {
+        ClientIpResolver::new()
+            .trust("10.0.0.0/8".parse().unwrap())
+            .trust("2001:db8::/32".parse().unwrap())
+    }
+
+    fn resolve(resolver: &ClientIpResolver, name: HeaderName, values: &[&'static str], peer: &str) -> String {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(name.clone(), HeaderValue::from_static(value));
+        }
+        resolver.resolve(&headers, &peer.parse().unwrap()).to_string()
+    }
+
+    #[test]
+    fn cidr() {
+        let cidr = "192.168.0.0/16".parse::<Cidr>().unwrap();
+        assert!(cidr.contains(&"192.168.3.4".parse().unwrap()));
+        assert!(cidr.contains(&"::ffff:192.168.3.4".parse().unwrap()));
+        assert!(!cidr.contains(&"192.169.0.1".parse().unwrap()));
+
+        let cidr = "0.0.0.0/0".parse::<Cidr>().unwrap();
+        assert!(cidr.contains(&"1.2.3.4".parse().unwrap()));
+
+        let cidr = "2001:db8::1".parse::<Cidr>().unwrap();
+        assert!(cidr.contains(&"2001:db8::1".parse().unwrap()));
+        assert!(!cidr.contains(&"2001:db8::2".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("foo/8".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn untrusted_peer() {
+        let ip = resolve(&resolver(), X_FORWARDED_FOR, &["1.1.1.1"], "203.0.113.1:80");
+        assert_eq!(ip, "203.0.113.1");
+
+        let ip = resolve(&ClientIpResolver::new(), X_FORWARDED_FOR, &["1.1.1.1"], "10.0.0.1:80");
+        assert_eq!(ip, "10.0.0.1");
+    }
+
+    #[test]
+    fn x_forwarded_for() {
+        let r = resolver();
+
+        // right most untrusted address is used.
+        let ip = resolve(&r, X_FORWARDED_FOR, &["6.6.6.6, 1.1.1.1, 10.0.0.3"], "10.0.0.1:80");
+        assert_eq!(ip, "1.1.1.1");
+
+        // multiple header values are walked as one list.
+        let ip = resolve(&r, X_FORWARDED_FOR, &["1.1.1.1", "10.0.0.3, 10.0.0.2"], "10.0.0.1:80");
+        assert_eq!(ip, "1.1.1.1");
+
+        // left most address is used when all are trusted.
+        let ip = resolve(&r, X_FORWARDED_FOR, &["10.0.0.3, 10.0.0.2"], "10.0.0.1:80");
+        assert_eq!(ip, "10.0.0.3");
+
+        // walk stops at unparsable entry.
+        let ip = resolve(&r, X_FORWARDED_FOR, &["1.1.1.1, garbage, 10.0.0.2"], "10.0.0.1:80");
+        assert_eq!(ip, "10.0.0.2");
+
+        let ip = resolve(&r, X_FORWARDED_FOR, &[], "[2001:db8::1]:80");
+        assert_eq!(ip, "2001:db8::1");
+    }
+
+    #[test]
+    fn forwarded() {
+        let r = resolver().header(ForwardedHeader::Forwarded);
+
+        let ip = resolve(
+            &r,
+            FORWARDED,
+            &[r#"for=1.1.1.1;proto=http, for="[2001:db8::2]:4711";by=10.0.0.1"#],
+            "10.0.0.1:80",
+        );
+        assert_eq!(ip, "1.1.1.1");
+
+        let ip = resolve(&r, FORWARDED, &["for=unknown, for=10.0.0.2"], "10.0.0.1:80");
+        assert_eq!(ip, "10.0.0.2");
+    }
+
+    #[test]
+    fn x_real_ip() {
+        let r = resolver().header(ForwardedHeader::XRealIp);
+
+        let ip = resolve(&r, X_REAL_IP, &["1.1.1.1"], "10.0.0.1:80");
+        assert_eq!(ip, "1.1.1.1");
+
+        // x-forwarded-for is ignored.
+        let ip = resolve(&r, X_FORWARDED_FOR, &["1.1.1.1"], "10.0.0.1:80");
+        assert_eq!(ip, "10.0.0.1");
+    }
+}
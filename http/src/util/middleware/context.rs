@@ -161,7 +161,10 @@ mod service {
 mod router_impl {
     use xitca_service::object::ServiceObject;
 
-    use crate::util::service::router::{IntoObject, PathGen, RouteGen, RouteObject};
+    use crate::{
+        http::Method,
+        util::service::router::{IntoObject, PathGen, RouteGen, RouteObject},
+    };
 
     use super::*;
 
@@ -187,6 +190,10 @@ mod router_impl {
                 fn path_gen(&mut self, prefix: &str) -> String {
                     self.0.path_gen(prefix)
                 }
+
+                fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+                    self.0.path_visit(path, visitor)
+                }
             }
 
             impl<I, Req, C> RouteGen for Builder<I, Req, C>
@@ -2,8 +2,10 @@ use tracing::Level;
 use xitca_service::Service;
 
 /// a builder for logger service.
+///
+/// client ip is not recorded by default. see [`Logger::with_client_ip`] for detail.
 #[derive(Clone)]
-pub struct Logger {
+pub struct Logger<const CLIENT_IP: bool = false> {
     level: Level,
 }
 
@@ -23,10 +25,22 @@ impl Logger {
     pub fn with_level(level: Level) -> Self {
         Self { level }
     }
+
+    /// record client ip in span of request. client ip is taken from [`ClientIp`] attached by [`ClientIpResolver`]
+    /// middleware enclosed outside of Logger. it falls back to peer address when absent.
+    ///
+    /// logger recording client ip requires request type to implement `BorrowReq<SocketAddr>` and
+    /// `BorrowReq<Extensions>`.
+    ///
+    /// [`ClientIp`]: crate::util::middleware::client_ip::ClientIp
+    /// [`ClientIpResolver`]: crate::util::middleware::client_ip::ClientIpResolver
+    pub fn with_client_ip(self) -> Logger<true> {
+        Logger { level: self.level }
+    }
 }
 
-impl<S, E> Service<Result<S, E>> for Logger {
-    type Response = service::LoggerService<S>;
+impl<S, E, const CLIENT_IP: bool> Service<Result<S, E>> for Logger<CLIENT_IP> {
+    type Response = service::LoggerService<S, CLIENT_IP>;
     type Error = E;
 
     async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
@@ -38,21 +52,26 @@ impl<S, E> Service<Result<S, E>> for Logger {
 }
 
 mod service {
+    use core::net::SocketAddr;
+
     use std::error;
 
-    use tracing::{Instrument, event, span};
+    use tracing::{Instrument, event, field, field::Empty, span};
     use xitca_service::ready::ReadyService;
 
-    use crate::http::{BorrowReq, Method, Uri, header::HeaderMap};
+    use crate::{
+        http::{BorrowReq, Extensions, Method, Uri, header::HeaderMap},
+        util::middleware::client_ip::ClientIp,
+    };
 
     use super::*;
 
-    pub struct LoggerService<S> {
+    pub struct LoggerService<S, const CLIENT_IP: bool> {
         pub(super) service: S,
         pub(super) level: Level,
     }
 
-    impl<S, Req> Service<Req> for LoggerService<S>
+    impl<S, Req> Service<Req> for LoggerService<S, false>
     where
         S: Service<Req>,
         Req: BorrowReq<Method> + BorrowReq<Uri> + BorrowReq<HeaderMap>,
@@ -63,6 +82,33 @@ mod service {
 
         #[inline]
         async fn call(&self, req: Req) -> Result<Self::Response, Self::Error> {
+            self.log(req, None).await
+        }
+    }
+
+    impl<S, Req> Service<Req> for LoggerService<S, true>
+    where
+        S: Service<Req>,
+        Req: BorrowReq<Method> + BorrowReq<Uri> + BorrowReq<HeaderMap> + BorrowReq<SocketAddr> + BorrowReq<Extensions>,
+        S::Error: error::Error,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+
+        #[inline]
+        async fn call(&self, req: Req) -> Result<Self::Response, Self::Error> {
+            let client_ip = ClientIp::from_extensions(req.borrow(), req.borrow());
+            self.log(req, Some(client_ip)).await
+        }
+    }
+
+    impl<S, const CLIENT_IP: bool> LoggerService<S, CLIENT_IP> {
+        async fn log<Req>(&self, req: Req, client_ip: Option<ClientIp>) -> Result<S::Response, S::Error>
+        where
+            S: Service<Req>,
+            Req: BorrowReq<Method> + BorrowReq<Uri>,
+            S::Error: error::Error,
+        {
             let method: &Method = req.borrow();
             let uri: &Uri = req.borrow();
 
@@ -82,9 +128,14 @@ mod service {
                 self.level,
                 "request",
                 method = %method,
-                uri = %uri
+                uri = %uri,
+                client_ip = Empty
             );
 
+            if let Some(client_ip) = client_ip {
+                span.record("client_ip", field::display(client_ip));
+            }
+
             async {
                 event!(target: "on_request", Level::INFO, "serving request");
                 match self.service.call(req).await {
@@ -103,7 +154,7 @@ mod service {
         }
     }
 
-    impl<S> ReadyService for LoggerService<S>
+    impl<S, const CLIENT_IP: bool> ReadyService for LoggerService<S, CLIENT_IP>
     where
         S: ReadyService,
     {
@@ -115,3 +166,33 @@ mod service {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use xitca_service::{ServiceExt, fn_service};
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::http::{Request, RequestExt};
+
+    use super::*;
+
+    #[test]
+    fn client_ip_opt_in() {
+        // default logger works with request type without peer address and extensions.
+        let service = fn_service(|_: Request<()>| async { Ok::<_, Infallible>("ok") })
+            .enclosed(Logger::new())
+            .call(())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(service.call(Request::new(())).now_or_panic().unwrap(), "ok");
+
+        let service = fn_service(|_: Request<RequestExt<()>>| async { Ok::<_, Infallible>("ok") })
+            .enclosed(Logger::new().with_client_ip())
+            .call(())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(service.call(Request::default()).now_or_panic().unwrap(), "ok");
+    }
+}
@@ -6,7 +6,9 @@ mod logger;
 mod socket_config;
 
 pub mod catch_unwind;
+pub mod client_ip;
 pub mod context;
+pub mod request_trace;
 
 pub use self::{extension::Extension, logger::Logger};
 
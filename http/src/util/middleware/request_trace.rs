@@ -0,0 +1,341 @@
+//! request id and tracing span middleware.
+
+use core::{
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use std::{collections::hash_map::RandomState, sync::OnceLock};
+
+use tracing::Level;
+use xitca_service::Service;
+
+use crate::http::header::{HeaderName, HeaderValue};
+
+/// request id attached to [`Request`]'s [`Extensions`] by [`RequestTrace`] middleware.
+///
+/// [`Request`]: crate::http::Request
+/// [`Extensions`]: crate::http::Extensions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(HeaderValue);
+
+impl RequestId {
+    /// generate a new request id unique within current process.
+    pub fn generate() -> Self {
+        static SEED: OnceLock<u64> = OnceLock::new();
+        static COUNT: AtomicU64 = AtomicU64::new(0);
+
+        let seed = *SEED.get_or_init(|| RandomState::new().build_hasher().finish());
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+
+        let id = format!("{seed:016x}{count:016x}");
+        Self(HeaderValue::try_from(id).expect("hex string must be valid HeaderValue"))
+    }
+
+    /// string slice of request id.
+    pub fn as_str(&self) -> &str {
+        // request id is either generated as hex string or validated when taken from request header.
+        self.0.to_str().unwrap_or_default()
+    }
+
+    /// header value of request id.
+    pub fn as_header_value(&self) -> &HeaderValue {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// builder for request id and tracing span middleware.
+///
+/// for every request the middleware:
+/// - reads request id from request header(`x-request-id` by default) or generates a new one when absent.
+/// - attaches [`RequestId`] to request's extensions and echo it in response header.
+/// - opens a `request` span recording method, path, request id, peer address and client ip. the span is entered
+///   when driving the service and response body stream and closes when response body is finished or dropped.
+///   status code and latency in milliseconds are recorded to span when they are available.
+///
+/// client ip is taken from [`ClientIp`] attached by [`ClientIpResolver`] middleware enclosed outside of
+/// RequestTrace. it falls back to peer address when absent.
+///
+/// [`ClientIp`]: crate::util::middleware::client_ip::ClientIp
+/// [`ClientIpResolver`]: crate::util::middleware::client_ip::ClientIpResolver
+#[derive(Clone)]
+pub struct RequestTrace {
+    level: Level,
+    header: HeaderName,
+    trust_incoming: bool,
+}
+
+impl Default for RequestTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestTrace {
+    /// construct a new middleware builder with [`Level::INFO`] span.
+    pub fn new() -> Self {
+        Self {
+            level: Level::INFO,
+            header: HeaderName::from_static("x-request-id"),
+            trust_incoming: true,
+        }
+    }
+
+    /// set [`Level`] verbosity of request span.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// set header name where request id is read from and written to.
+    pub fn header_name(mut self, name: HeaderName) -> Self {
+        self.header = name;
+        self
+    }
+
+    /// when set to false request id from request header is ignored and a new one is always generated.
+    /// default to true.
+    pub fn trust_incoming(mut self, trust: bool) -> Self {
+        self.trust_incoming = trust;
+        self
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for RequestTrace {
+    type Response = service::RequestTraceService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::RequestTraceService {
+            service,
+            config: self.clone(),
+        })
+    }
+}
+
+mod service {
+    use core::{
+        net::SocketAddr,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use std::{error, time::Instant};
+
+    use futures_core::stream::Stream;
+    use pin_project_lite::pin_project;
+    use tracing::{Instrument, Span, event, field::Empty, span};
+    use xitca_service::ready::ReadyService;
+
+    use crate::{
+        http::{BorrowReq, BorrowReqMut, Extensions, Method, Response, Uri, header::HeaderMap},
+        util::middleware::client_ip::ClientIp,
+    };
+
+    use super::*;
+
+    // max length of request id accepted from request header.
+    const MAX_ID_LEN: usize = 256;
+
+    pub struct RequestTraceService<S> {
+        pub(super) service: S,
+        pub(super) config: RequestTrace,
+    }
+
+    impl<S, Req, ResB> Service<Req> for RequestTraceService<S>
+    where
+        S: Service<Req, Response = Response<ResB>>,
+        Req: BorrowReq<Method>
+            + BorrowReq<Uri>
+            + BorrowReq<HeaderMap>
+            + BorrowReq<SocketAddr>
+            + BorrowReqMut<Extensions>,
+        S::Error: error::Error,
+    {
+        type Response = Response<TraceBody<ResB>>;
+        type Error = S::Error;
+
+        async fn call(&self, mut req: Req) -> Result<Self::Response, Self::Error> {
+            let addr = *BorrowReq::<SocketAddr>::borrow(&req);
+            let client_ip = ClientIp::from_extensions(BorrowReqMut::<Extensions>::borrow_mut(&mut req), &addr);
+
+            let headers: &HeaderMap = req.borrow();
+            let id = headers
+                .get(&self.config.header)
+                .filter(|v| self.config.trust_incoming && !v.is_empty() && v.len() <= MAX_ID_LEN && v.to_str().is_ok())
+                .map(|v| RequestId(v.clone()))
+                .unwrap_or_else(RequestId::generate);
+
+            let method: &Method = req.borrow();
+            let uri: &Uri = req.borrow();
+
+            macro_rules! span2 {
+                ($lvl:expr, $name:expr, $($fields:tt)*) => {
+                    match $lvl {
+                        Level::TRACE => span!(Level::TRACE, $name, $($fields)*),
+                        Level::DEBUG => span!(Level::DEBUG, $name, $($fields)*),
+                        Level::INFO => span!(Level::INFO, $name, $($fields)*),
+                        Level::WARN => span!(Level::WARN, $name, $($fields)*),
+                        Level::ERROR => span!(Level::ERROR, $name, $($fields)*),
+                    }
+                }
+            }
+
+            let span = span2!(
+                self.config.level,
+                "request",
+                request_id = %id,
+                method = %method,
+                path = %uri.path(),
+                peer_addr = %addr,
+                client_ip = %client_ip,
+                status = Empty,
+                latency_ms = Empty
+            );
+
+            let header = id.as_header_value().clone();
+            BorrowReqMut::<Extensions>::borrow_mut(&mut req).insert(id);
+
+            let guard = Guard {
+                span,
+                start: Instant::now(),
+                done: false,
+            };
+
+            let res = self.service.call(req).instrument(guard.span.clone()).await;
+
+            match res {
+                Ok(res) => {
+                    guard.span.record("status", res.status().as_u16());
+                    let (mut parts, body) = res.into_parts();
+                    parts.headers.insert(self.config.header.clone(), header);
+                    Ok(Response::from_parts(parts, TraceBody { body, guard }))
+                }
+                Err(e) => {
+                    let _enter = guard.span.enter();
+                    event!(target: "on_error", Level::WARN, "{}", e);
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    impl<S> ReadyService for RequestTraceService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    // record latency to span when request is finished.
+    struct Guard {
+        span: Span,
+        start: Instant,
+        done: bool,
+    }
+
+    impl Guard {
+        fn finish(&mut self) {
+            if !self.done {
+                self.done = true;
+                self.span
+                    .record("latency_ms", self.start.elapsed().as_secs_f64() * 1000.0);
+            }
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.finish();
+        }
+    }
+
+    pin_project! {
+        /// response body type produced by [`RequestTrace`] middleware. stream of it is polled within
+        /// the request span.
+        pub struct TraceBody<B> {
+            #[pin]
+            body: B,
+            guard: Guard,
+        }
+    }
+
+    impl<B> Stream for TraceBody<B>
+    where
+        B: Stream,
+    {
+        type Item = B::Item;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+            let res = {
+                let _enter = this.guard.span.enter();
+                this.body.poll_next(cx)
+            };
+            if let Poll::Ready(None) = res {
+                this.guard.finish();
+            }
+            res
+        }
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.body.size_hint()
+        }
+    }
+}
+
+pub use service::{RequestTraceService, TraceBody};
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use xitca_service::{ServiceExt, fn_service};
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        body::ResponseBody,
+        http::{Request, RequestExt, Response},
+    };
+
+    use super::*;
+
+    #[test]
+    fn request_id() {
+        let service = fn_service(|req: Request<RequestExt<()>>| async move {
+            let id = req.extensions().get::<RequestId>().unwrap().clone();
+            let body: ResponseBody = ResponseBody::bytes(id.as_str().to_owned());
+            Ok::<_, Infallible>(Response::new(body))
+        })
+        .enclosed(RequestTrace::new())
+        .call(())
+        .now_or_panic()
+        .unwrap();
+
+        let res = service.call(Request::default()).now_or_panic().unwrap();
+        let id = res.headers().get("x-request-id").unwrap().clone();
+        assert_eq!(id.len(), 32);
+
+        let res2 = service.call(Request::default()).now_or_panic().unwrap();
+        assert_ne!(res2.headers().get("x-request-id").unwrap(), id);
+
+        let mut req = Request::default();
+        req.headers_mut()
+            .insert("x-request-id", HeaderValue::from_static("996"));
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.headers().get("x-request-id").unwrap(), "996");
+    }
+}
@@ -109,6 +109,21 @@ impl<R, N, const M: usize> Route<R, N, M> {
     route_method!(connect, CONNECT);
     route_method!(patch, PATCH);
     route_method!(trace, TRACE);
+
+    /// the methods matched by this Route. chained Routes are not included.
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// the service type of this Route.
+    pub fn service(&self) -> &R {
+        &self.route
+    }
+
+    /// the next Route chained after this one.
+    pub fn next_route(&self) -> &N {
+        &self.next
+    }
 }
 
 impl<Arg, R, N, const M: usize> Service<Arg> for Route<R, N, M>
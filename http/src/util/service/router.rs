@@ -2,26 +2,53 @@ pub use xitca_router::{MatchError, params::Params};
 
 use core::{fmt, marker::PhantomData};
 
-use std::{collections::HashMap, error};
+use std::{collections::HashMap, error, sync::Arc};
 
 use xitca_service::{BoxFuture, FnService, Service, object::BoxedServiceObject, pipeline::PipelineT};
 
-use crate::http::Request;
+use crate::http::{Method, Request};
 
 use super::{
     handler::HandlerService,
-    route::{MethodNotAllowed, Route},
+    route::{MethodNotAllowed, MethodNotAllowedBuilder, Route},
 };
 
 pub use self::object::RouteObject;
 
+/// route pattern matched by [Router] for current request. for nested router the pattern includes
+/// the path prefix of it's parent routers.
+///
+/// matched path is stored in [RequestExt](crate::http::RequestExt) and can be obtained with
+/// [RequestExt::matched_path](crate::http::RequestExt::matched_path).
+///
+/// # Examples
+/// ```rust
+/// # use xitca_http::{http::RequestExt, util::service::router::MatchedPath};
+/// let path = MatchedPath::default();
+/// // request not routed by Router does not have matched path.
+/// assert!(path.get().is_none());
+///
+/// let ext = RequestExt::<()>::default();
+/// assert_eq!(ext.matched_path(), &path);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MatchedPath(Option<Arc<str>>);
+
+impl MatchedPath {
+    /// get matched route pattern. e.g. `/users/{id}`.
+    #[inline]
+    pub fn get(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
 /// Simple router for matching path and call according service.
 ///
 /// An [ServiceObject](xitca_service::object::ServiceObject) must be specified as a type parameter
 /// in order to determine how the router type-erases node services.
 pub struct Router<Obj> {
     // record for last time PathGen is called with certain route string prefix.
-    prefix: usize,
+    prefix: String,
     routes: HashMap<String, Obj>,
 }
 
@@ -34,7 +61,7 @@ impl<Obj> Default for Router<Obj> {
 impl<Obj> Router<Obj> {
     pub fn new() -> Self {
         Router {
-            prefix: 0,
+            prefix: String::new(),
             routes: HashMap::new(),
         }
     }
@@ -89,11 +116,12 @@ where
 
         for (path, service) in self.routes.iter() {
             let service = service.call(arg.clone()).await?;
-            router.insert(path.to_string(), service).unwrap();
+            let pattern = Arc::from(format!("{}{path}", self.prefix));
+            router.insert(path.to_string(), (pattern, service)).unwrap();
         }
 
         Ok(service::RouterService {
-            prefix: self.prefix,
+            prefix: self.prefix.len(),
             router,
         })
     }
@@ -150,6 +178,14 @@ pub trait PathGen {
     fn path_gen(&mut self, prefix: &str) -> String {
         String::from(prefix)
     }
+
+    /// visit routed paths and the methods they are matched against. path is the full path of
+    /// current route including prefix of nested router.
+    ///
+    /// default to visit nothing.
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+        let _ = (path, visitor);
+    }
 }
 
 /// trait for specialized route generation when utilizing [Router::insert].
@@ -184,7 +220,7 @@ where
             path.pop();
         }
 
-        self.prefix += path.len();
+        self.prefix.insert_str(0, &path);
 
         self.routes.iter_mut().for_each(|(_, v)| {
             v.path_gen(path.as_str());
@@ -194,6 +230,13 @@ where
 
         path
     }
+
+    fn path_visit(&self, _: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+        // prefix already contains paths of parent routers.
+        self.routes.iter().for_each(|(path, v)| {
+            v.path_visit(&format!("{}{path}", self.prefix), visitor);
+        });
+    }
 }
 
 impl<Obj> RouteGen for Router<Obj>
@@ -207,9 +250,20 @@ where
     }
 }
 
-impl<R, N, const M: usize> PathGen for Route<R, N, M> {}
+impl<R, N, const M: usize> PathGen for Route<R, N, M>
+where
+    N: PathGen,
+{
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+        visitor(path, self.methods());
+        self.next_route().path_visit(path, visitor);
+    }
+}
 
-impl<R, N, const M: usize> RouteGen for Route<R, N, M> {
+impl<R, N, const M: usize> RouteGen for Route<R, N, M>
+where
+    N: PathGen,
+{
     type Route<R1> = R1;
 
     fn route_gen<R1>(route: R1) -> Self::Route<R1> {
@@ -217,6 +271,8 @@ impl<R, N, const M: usize> RouteGen for Route<R, N, M> {
     }
 }
 
+impl<R> PathGen for MethodNotAllowedBuilder<R> {}
+
 impl<F, T, M> PathGen for HandlerService<F, T, M> {}
 
 impl<F, T, M> RouteGen for HandlerService<F, T, M> {
@@ -244,6 +300,10 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.first.path_gen(prefix)
     }
+
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+        self.first.path_visit(path, visitor)
+    }
 }
 
 impl<F, S, M> RouteGen for PipelineT<F, S, M>
@@ -267,6 +327,10 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.0.path_gen(prefix)
     }
+
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+        self.0.path_visit(path, visitor)
+    }
 }
 
 impl<S> RouteGen for RouterMapErr<S>
@@ -343,6 +407,10 @@ mod object {
         fn path_gen(&mut self, prefix: &str) -> String {
             self.0.path_gen(prefix)
         }
+
+        fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+            self.0.path_visit(path, visitor)
+        }
     }
 
     impl<Arg, S, E> RouteGen for RouteObject<Arg, S, E> {
@@ -381,6 +449,10 @@ where
             fn path_gen(&mut self, prefix: &str) -> String {
                 self.0.path_gen(prefix)
             }
+
+            fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+                self.0.path_visit(path, visitor)
+            }
         }
 
         impl<T, Req> RouteGen for Builder<T, Req>
@@ -431,19 +503,19 @@ mod service {
 
     use crate::http::{BorrowReq, BorrowReqMut, Uri};
 
-    use super::{Params, RouterError, Service};
+    use super::{Arc, MatchedPath, Params, RouterError, Service};
 
     pub struct RouterService<S> {
         // a length record of prefix of current router.
         // when it's Some the request path has to be sliced to exclude the string path prefix.
         pub(super) prefix: usize,
-        pub(super) router: xitca_router::Router<S>,
+        pub(super) router: xitca_router::Router<(Arc<str>, S)>,
     }
 
     impl<S, Req, E> Service<Req> for RouterService<S>
     where
         S: Service<Req, Error = RouterError<E>>,
-        Req: BorrowReq<Uri> + BorrowReqMut<Params>,
+        Req: BorrowReq<Uri> + BorrowReqMut<Params> + BorrowReqMut<MatchedPath>,
     {
         type Response = S::Response;
         type Error = S::Error;
@@ -455,10 +527,13 @@ mod service {
         fn call(&self, mut req: Req) -> impl Future<Output = Result<Self::Response, Self::Error>> {
             async {
                 let path = req.borrow().path();
-                let xitca_router::Match { value, params } =
-                    self.router.at(&path[self.prefix..]).map_err(RouterError::Match)?;
-                *req.borrow_mut() = params;
-                Service::call(value, req).await
+                let xitca_router::Match {
+                    value: (pattern, service),
+                    params,
+                } = self.router.at(&path[self.prefix..]).map_err(RouterError::Match)?;
+                *BorrowReqMut::<Params>::borrow_mut(&mut req) = params;
+                *BorrowReqMut::<MatchedPath>::borrow_mut(&mut req) = MatchedPath(Some(pattern.clone()));
+                Service::call(service, req).await
             }
         }
     }
@@ -634,6 +709,32 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn router_matched_path() {
+        async fn matched(req: Request<RequestExt<()>>) -> Result<Response<()>, Infallible> {
+            assert_eq!(req.body().matched_path().get(), Some(req.uri().query().unwrap()));
+            Ok(Response::new(()))
+        }
+
+        let service = Router::new()
+            .insert("/users/{id}", fn_service(matched))
+            .insert(
+                "/scope/",
+                Router::new().insert("/nest", Router::new().insert("/{*rest}", fn_service(matched))),
+            )
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let req = |uri| Request::builder().uri(uri).body(Default::default()).unwrap();
+
+        service.call(req("/users/1?/users/{id}")).now_or_panic().unwrap();
+        service
+            .call(req("/scope/nest/foo/bar?/scope/nest/{*rest}"))
+            .now_or_panic()
+            .unwrap();
+    }
+
     #[test]
     fn router_service_call_size() {
         let service = Router::new()
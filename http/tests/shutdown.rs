@@ -0,0 +1,160 @@
+//! graceful shutdown behavior of http dispatchers.
+
+use core::{convert::Infallible, fmt, future::Future, net::SocketAddr, time::Duration};
+
+use std::rc::Rc;
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    task::LocalSet,
+    time::timeout,
+};
+use xitca_http::{
+    HttpServiceBuilder,
+    body::Once,
+    bytes::Bytes,
+    http::{Request, RequestExt, Response},
+    shutdown::ShutdownToken,
+};
+use xitca_io::net::TcpStream;
+use xitca_service::{Service, ServiceExt, fn_service};
+
+// request to "/shutdown" triggers graceful shutdown of current thread and keeps the response in-flight
+// for a short while.
+async fn handler<B>(req: Request<RequestExt<B>>) -> Result<Response<Once<Bytes>>, Infallible> {
+    if req.uri().path() == "/shutdown" {
+        ShutdownToken::current().shutdown();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    Ok(Response::new(Once::new(Bytes::from_static(b"done"))))
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(LocalSet::new().run_until(fut))
+}
+
+async fn serve<S>(service: S) -> SocketAddr
+where
+    S: Service<(TcpStream, SocketAddr)> + 'static,
+    S::Error: fmt::Debug,
+{
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let service = Rc::new(service);
+
+    tokio::task::spawn_local(async move {
+        loop {
+            let (stream, addr) = listener.accept().await.unwrap();
+            let stream = TcpStream::from_std(stream.into_std().unwrap()).unwrap();
+            let service = service.clone();
+            tokio::task::spawn_local(async move {
+                let _ = service.call((stream, addr)).await;
+            });
+        }
+    });
+
+    addr
+}
+
+async fn h1_request(stream: &mut tokio::net::TcpStream, path: &str) -> String {
+    let req = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    stream.write_all(req.as_bytes()).await.unwrap();
+
+    let mut res = Vec::new();
+    while !res.ends_with(b"done") {
+        let mut buf = [0; 1024];
+        let n = timeout(Duration::from_secs(5), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(n, 0, "connection closed before response is received");
+        res.extend_from_slice(&buf[..n]);
+    }
+
+    String::from_utf8(res).unwrap().to_lowercase()
+}
+
+async fn h1_closed(stream: &mut tokio::net::TcpStream) -> bool {
+    let mut buf = [0; 1024];
+    let n = timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .unwrap()
+        .unwrap();
+    n == 0
+}
+
+#[test]
+fn h1_shutdown() {
+    block_on(async {
+        let service = fn_service(handler)
+            .enclosed(HttpServiceBuilder::h1())
+            .call(())
+            .await
+            .unwrap();
+        let addr = serve(service).await;
+
+        let mut in_flight = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut idle = tokio::net::TcpStream::connect(addr).await.unwrap();
+
+        let res = h1_request(&mut in_flight, "/").await;
+        assert!(res.starts_with("http/1.1 200 ok"));
+        assert!(!res.contains("connection: close"));
+
+        let res = h1_request(&mut idle, "/").await;
+        assert!(!res.contains("connection: close"));
+
+        // in-flight response is sent with connection close header and connection is closed afterwards.
+        let res = h1_request(&mut in_flight, "/shutdown").await;
+        assert!(res.starts_with("http/1.1 200 ok"));
+        assert!(res.contains("connection: close"));
+        assert!(h1_closed(&mut in_flight).await);
+
+        // idle keep-alive connection is closed without waiting for keep-alive timeout.
+        assert!(h1_closed(&mut idle).await);
+    })
+}
+
+#[cfg(feature = "http2")]
+#[test]
+fn h2_shutdown() {
+    block_on(async {
+        let service = fn_service(handler)
+            .enclosed(HttpServiceBuilder::h2())
+            .call(())
+            .await
+            .unwrap();
+        let addr = serve(service).await;
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (client, conn) = h2::client::handshake(stream).await.unwrap();
+        let conn = tokio::task::spawn_local(conn);
+        let mut client = client.ready().await.unwrap();
+
+        let req = Request::get("http://localhost/shutdown").body(()).unwrap();
+        let (res, _) = client.send_request(req, true).unwrap();
+
+        // in-flight stream is drained after GOAWAY frame.
+        let res = timeout(Duration::from_secs(5), res).await.unwrap().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+        let body = res.into_body().data().await.unwrap().unwrap();
+        assert_eq!(body, "done");
+
+        // new stream is not accepted after GOAWAY frame.
+        let req = Request::get("http://localhost/").body(()).unwrap();
+        let err = match client.ready().await {
+            Ok(mut client) => match client.send_request(req, true) {
+                Ok((res, _)) => res.await.unwrap_err(),
+                Err(e) => e,
+            },
+            Err(e) => e,
+        };
+        assert!(err.is_go_away() || err.is_remote());
+
+        // connection is closed gracefully by server.
+        timeout(Duration::from_secs(5), conn).await.unwrap().unwrap().unwrap();
+    })
+}
@@ -14,6 +14,8 @@ use crate::{
 
 type ListenerFn = Box<dyn FnOnce() -> io::Result<ListenerDyn> + Send>;
 
+pub(crate) type HookFn = Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
 pub struct Builder {
     pub(crate) server_threads: usize,
     pub(crate) worker_threads: usize,
@@ -22,7 +24,9 @@ pub struct Builder {
     pub(crate) factories: HashMap<String, ServiceObj>,
     pub(crate) enable_signal: bool,
     pub(crate) shutdown_timeout: Duration,
-    pub(crate) on_worker_start: Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>,
+    pub(crate) on_worker_start: HookFn,
+    pub(crate) on_worker_stop: HookFn,
+    pub(crate) on_shutdown: HookFn,
     backlog: u32,
 }
 
@@ -44,6 +48,8 @@ impl Builder {
             enable_signal: true,
             shutdown_timeout: Duration::from_secs(30),
             on_worker_start: Box::new(|| Box::pin(async {})),
+            on_worker_stop: Box::new(|| Box::pin(async {})),
+            on_shutdown: Box::new(|| Box::pin(async {})),
             backlog: 2048,
         }
     }
@@ -125,13 +131,50 @@ impl Builder {
         F: Fn() -> Fut + Send + Sync + 'static,
         Fut: Future + Send + 'static,
     {
-        self.on_worker_start = Box::new(move || {
-            let fut = on_start();
-            Box::pin(async {
-                fut.await;
-            })
-        });
+        self.on_worker_start = hook(on_start);
+        self
+    }
+
+    /// Async callback called on every worker thread when server is stopping.
+    ///
+    /// The callback is called after worker stopped accepting new connections and before waiting for
+    /// in-flight connections to finish. It's a good place for notifying long-lived connections about the
+    /// shutdown so they can finish before [Builder::shutdown_timeout] elapsed.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use xitca_server::Builder;
+    /// let builder = Builder::new().on_worker_stop(|| async {
+    ///     println!("worker {:?} is stopping", std::thread::current().name());
+    /// });
+    /// ```
+    pub fn on_worker_stop<F, Fut>(mut self, on_stop: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+    {
+        self.on_worker_stop = hook(on_stop);
+        self
+    }
 
+    /// Async callback called once when all workers are stopped and before server is fully shutdown.
+    ///
+    /// The callback runs on a dedicated thread with a single threaded tokio runtime. It's a good place for
+    /// cleaning up global resources like flushing logs and closing database pools.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use xitca_server::Builder;
+    /// let builder = Builder::new().on_shutdown(|| async {
+    ///     println!("server is shutdown");
+    /// });
+    /// ```
+    pub fn on_shutdown<F, Fut>(mut self, on_shutdown: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+    {
+        self.on_shutdown = hook(on_shutdown);
         self
     }
 
@@ -274,3 +317,16 @@ impl Builder {
         Ok(self.listen(name, listener, service))
     }
 }
+
+fn hook<F, Fut>(func: F) -> HookFn
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+{
+    Box::new(move || {
+        let fut = func();
+        Box::pin(async {
+            fut.await;
+        })
+    })
+}
@@ -7,6 +7,7 @@ mod server;
 mod signals;
 mod worker;
 
+pub mod metrics;
 pub mod net;
 
 pub use builder::Builder;
@@ -27,4 +28,72 @@ mod test {
             .listen("test", listener, fn_service(|_: TcpStream| async { Ok::<_, ()>(()) }))
             .build();
     }
+
+    #[test]
+    fn test_stop_hooks() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        let worker_stop = Arc::new(AtomicUsize::new(0));
+        let shutdown = Arc::new(AtomicUsize::new(0));
+
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let mut server = crate::builder::Builder::new()
+            .worker_threads(2)
+            .disable_signal()
+            .on_worker_stop({
+                let worker_stop = worker_stop.clone();
+                move || {
+                    let worker_stop = worker_stop.clone();
+                    async move {
+                        worker_stop.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            })
+            .on_shutdown({
+                let shutdown = shutdown.clone();
+                move || {
+                    // all workers must be stopped before shutdown.
+                    assert_eq!(worker_stop.load(Ordering::SeqCst), 2);
+                    let shutdown = shutdown.clone();
+                    async move {
+                        shutdown.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            })
+            .listen("test", listener, fn_service(|_: TcpStream| async { Ok::<_, ()>(()) }))
+            .build();
+
+        server.handle().unwrap().stop(true);
+        server.wait().unwrap();
+
+        assert_eq!(shutdown.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut server = crate::builder::Builder::new()
+            .worker_threads(1)
+            .disable_signal()
+            .listen("test", listener, fn_service(|_: TcpStream| async { Ok::<_, ()>(()) }))
+            .build();
+
+        let handle = server.handle().unwrap();
+        let server = std::thread::spawn(move || server.wait());
+
+        let _stream = std::net::TcpStream::connect(addr).unwrap();
+
+        let start = std::time::Instant::now();
+        while !crate::metrics::workers().iter().any(|w| w.accepted() > 0) {
+            assert!(start.elapsed().as_secs() < 10, "connection must be accepted");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        handle.stop(true);
+        server.join().unwrap().unwrap();
+    }
 }
@@ -0,0 +1,124 @@
+//! connection metrics of server workers.
+//!
+//! every worker thread owns it's own set of atomic counters and updates them without contention.
+//! [workers] collects a snapshot of all running workers for exposition.
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+};
+
+/// snapshot of connection metrics of a single worker thread.
+#[derive(Clone, Debug)]
+pub struct WorkerMetrics {
+    name: String,
+    accepted: u64,
+    active: u64,
+    accept_errors: u64,
+}
+
+impl WorkerMetrics {
+    /// name of worker thread.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// total number of accepted connections.
+    pub fn accepted(&self) -> u64 {
+        self.accepted
+    }
+
+    /// number of connections currently being served.
+    pub fn active(&self) -> u64 {
+        self.active
+    }
+
+    /// total number of errors occurred when accepting connections.
+    pub fn accept_errors(&self) -> u64 {
+        self.accept_errors
+    }
+}
+
+/// collect snapshot of connection metrics of all running workers in current process.
+///
+/// # Examples
+/// ```rust
+/// for worker in xitca_server::metrics::workers() {
+///     println!("{}: {} active connections", worker.name(), worker.active());
+/// }
+/// ```
+pub fn workers() -> Vec<WorkerMetrics> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|counters| WorkerMetrics {
+            name: counters.name.clone(),
+            accepted: counters.accepted.load(Ordering::Relaxed),
+            active: counters.active.load(Ordering::Relaxed),
+            accept_errors: counters.accept_errors.load(Ordering::Relaxed),
+        })
+        .collect()
+}
+
+static REGISTRY: Mutex<Vec<Arc<Counters>>> = Mutex::new(Vec::new());
+
+pub(crate) struct Counters {
+    name: String,
+    accepted: AtomicU64,
+    active: AtomicU64,
+    accept_errors: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn accept(self: &Arc<Self>) -> ActiveGuard {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        self.active.fetch_add(1, Ordering::Relaxed);
+        ActiveGuard(self.clone())
+    }
+
+    pub(crate) fn accept_error(&self) {
+        self.accept_errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// guard decrease active connection count when connection is finished or dropped.
+pub(crate) struct ActiveGuard(Arc<Counters>);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// counters are registered on first use of worker thread and unregistered when thread exits.
+struct Local(Arc<Counters>);
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        if let Ok(mut registry) = REGISTRY.lock() {
+            registry.retain(|counters| !Arc::ptr_eq(counters, &self.0));
+        }
+    }
+}
+
+thread_local! {
+    static LOCAL: Local = {
+        let counters = Arc::new(Counters {
+            name: thread::current().name().unwrap_or("xitca-server-worker").to_owned(),
+            accepted: AtomicU64::new(0),
+            active: AtomicU64::new(0),
+            accept_errors: AtomicU64::new(0),
+        });
+        REGISTRY.lock().unwrap().push(counters.clone());
+        Local(counters)
+    };
+}
+
+/// counters of current worker thread.
+pub(crate) fn local() -> Arc<Counters> {
+    LOCAL.with(|local| local.0.clone())
+}
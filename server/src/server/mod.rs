@@ -38,6 +38,8 @@ impl Server {
             factories,
             shutdown_timeout,
             on_worker_start,
+            on_worker_stop,
+            on_shutdown,
             ..
         } = builder;
 
@@ -71,7 +73,16 @@ impl Server {
                 services.push(s);
             }
 
-            worker::wait_for_stop(handles, services, shutdown_timeout, &is_graceful_shutdown).await;
+            worker::wait_for_stop(
+                handles,
+                services,
+                shutdown_timeout,
+                &is_graceful_shutdown,
+                &on_worker_stop,
+            )
+            .await;
+
+            on_shutdown().await;
 
             Ok::<_, io::Error>(())
         };
@@ -91,6 +102,8 @@ impl Server {
             factories,
             shutdown_timeout,
             on_worker_start,
+            on_worker_stop,
+            on_shutdown,
             ..
         } = builder;
 
@@ -122,7 +135,7 @@ impl Server {
 
                 // TODO: wait for startup error(including panic) and return as io::Error on call site.
                 // currently the error only show when shared scope thread is joined with handle.
-                thread::scope(|scope| {
+                let res = thread::scope(|scope| {
                     for idx in 0..worker_threads {
                         let thread = thread::Builder::new().name(format!("xitca-server-worker-{idx}"));
 
@@ -142,7 +155,14 @@ impl Server {
                                 }
                             }
 
-                            worker::wait_for_stop(handles, services, shutdown_timeout, &is_graceful_shutdown).await;
+                            worker::wait_for_stop(
+                                handles,
+                                services,
+                                shutdown_timeout,
+                                &is_graceful_shutdown,
+                                &on_worker_stop,
+                            )
+                            .await;
                         };
 
                         #[cfg(not(feature = "io-uring"))]
@@ -167,7 +187,15 @@ impl Server {
                     }
 
                     Ok(())
-                })
+                });
+
+                // all workers are exited at this point.
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(on_shutdown());
+
+                res
             })?;
 
         let (tx_cmd, rx_cmd) = tokio::sync::mpsc::unbounded_channel();
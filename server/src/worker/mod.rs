@@ -9,7 +9,7 @@ use tracing::{error, info};
 use xitca_io::net::Stream;
 use xitca_service::{Service, ready::ReadyService};
 
-use crate::net::ListenerDyn;
+use crate::{builder::HookFn, metrics, net::ListenerDyn};
 
 use self::shutdown::ShutdownHandle;
 
@@ -24,6 +24,7 @@ where
 {
     let listener = listener.clone();
     let service = service.clone();
+    let metrics = metrics::local();
 
     tokio::task::spawn_local(async move {
         loop {
@@ -33,16 +34,22 @@ where
                 Ok(stream) => {
                     if let Ok(req) = TryFrom::try_from(stream) {
                         let service = service.clone();
+                        let active = metrics.accept();
                         tokio::task::spawn_local(async move {
                             let _ = service.call(req).await;
                             drop(ready);
+                            drop(active);
                         });
                     }
                 }
-                Err(ref e) if connection_error(e) => continue,
+                Err(ref e) if connection_error(e) => {
+                    metrics.accept_error();
+                    continue;
+                }
                 Err(ref e) if fatal_error(e) => return,
                 // TODO: handling os level io error differently according to the error code?
                 Err(ref e) if os_error(e) => {
+                    metrics.accept_error();
                     error!("Error accepting connection: {e}");
                     sleep(Duration::from_secs(1)).await;
                 }
@@ -57,6 +64,7 @@ pub(crate) async fn wait_for_stop(
     services: Vec<ServiceAny>,
     shutdown_timeout: Duration,
     is_graceful_shutdown: &AtomicBool,
+    on_worker_stop: &HookFn,
 ) {
     with_worker_name_str(|name| info!("Started {name}"));
 
@@ -68,6 +76,9 @@ pub(crate) async fn wait_for_stop(
             .unwrap_or_else(|e| with_worker_name_str(|name| error!("{name} exit on error: {e}")));
     }
 
+    // listeners are closed at this point. notify in-flight connections before waiting for them.
+    on_worker_stop().await;
+
     shutdown_handle.shutdown().await;
 }
 
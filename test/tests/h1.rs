@@ -120,6 +120,51 @@ async fn h1_post() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn h1_post_codec() -> Result<(), Error> {
+    #[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
+    struct Gacha {
+        credit_card: String,
+        pulls: u32,
+    }
+
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let server_url = format!("http://{}/", handle.ip_port_string());
+
+    let c = Client::new();
+
+    let gacha = Gacha {
+        credit_card: "declined".into(),
+        pulls: 10,
+    };
+
+    // server echoes request body and content type.
+    let res = c
+        .post(&server_url)
+        .version(Version::HTTP_11)
+        .msgpack(&gacha)
+        .send()
+        .await?;
+    assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "application/msgpack");
+    assert_eq!(res.msgpack::<Gacha>().await?, gacha);
+
+    let res = c
+        .post(&server_url)
+        .version(Version::HTTP_11)
+        .cbor(&gacha)
+        .send()
+        .await?;
+    assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "application/cbor");
+    assert_eq!(res.cbor::<Gacha>().await?, gacha);
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn h1_drop_body_read() -> Result<(), Error> {
     let mut handle = test_h1_server(fn_service(handle))?;
@@ -159,3 +159,147 @@ async fn web_handler() {
         .at_typed(test3)
         .finish();
 }
+
+/// a pet.
+#[allow(dead_code)]
+#[derive(xitca_web::codegen::Schema)]
+#[serde(rename_all = "camelCase")]
+struct Pet {
+    /// name of pet.
+    pet_name: String,
+    #[serde(default)]
+    age: u8,
+    #[serde(rename = "type")]
+    kind: Option<Kind>,
+    #[serde(skip)]
+    _owner: String,
+}
+
+#[allow(dead_code)]
+#[derive(xitca_web::codegen::Schema)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    BigDog,
+    Cat,
+}
+
+#[test]
+fn schema_derive() {
+    use xitca_web::openapi::{Components, Schema};
+
+    let mut components = Components::default();
+    let schema = Pet::schema(&mut components);
+    assert_eq!(schema["$ref"], "#/components/schemas/Pet");
+
+    let pet = components.get("Pet").unwrap();
+    assert_eq!(pet["description"], "a pet.");
+    assert_eq!(pet["required"][0], "petName");
+    assert_eq!(pet["required"].as_array().unwrap().len(), 1);
+    assert_eq!(pet["properties"]["petName"]["description"], "name of pet.");
+    assert_eq!(pet["properties"]["age"]["type"], "integer");
+    assert_eq!(
+        pet["properties"]["type"]["anyOf"][0]["$ref"],
+        "#/components/schemas/Kind"
+    );
+    assert!(pet["properties"].get("_owner").is_none());
+
+    let kind = components.get("Kind").unwrap();
+    assert_eq!(kind["enum"][0], "big_dog");
+    assert_eq!(kind["enum"][1], "cat");
+}
+
+#[test]
+fn web_handler_openapi() {
+    use xitca_web::{handler::params::Params, openapi::Info};
+
+    #[xitca_web::codegen::route("/pet/{id}", method = get, openapi)]
+    async fn pet(_: Params<u32>) -> &'static str {
+        ""
+    }
+
+    let app = xitca_web::App::new().at_typed_api(pet);
+    let doc = app.openapi(Info::new("test", "0.1.0")).to_json();
+
+    let op = &doc["paths"]["/pet/{id}"]["get"];
+    assert_eq!(op["parameters"][0]["name"], "id");
+    assert_eq!(op["parameters"][0]["schema"]["format"], "int32");
+    assert_eq!(
+        op["responses"]["200"]["content"]["text/plain"]["schema"]["type"],
+        "string"
+    );
+
+    let _ = app.finish();
+}
+
+#[derive(Debug)]
+struct TooYoung(u8);
+
+impl fmt::Display for TooYoung {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "age {} is too young", self.0)
+    }
+}
+
+impl std::error::Error for TooYoung {}
+
+#[xitca_codegen::error_impl]
+impl TooYoung {
+    async fn call(&self, ctx: WebContext<'_, Request<'_>>) -> WebResponse {
+        let mut res = ctx.into_response(self.to_string());
+        *res.status_mut() = StatusCode::FORBIDDEN;
+        res
+    }
+}
+
+struct Age(u8);
+
+impl<'a, 'r, C, B> xitca_web::handler::FromRequest<'a, WebContext<'r, C, B>> for Age {
+    type Type<'b> = Age;
+    type Error = xitca_web::error::Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let age = ctx.req().uri().query().and_then(|q| q.parse().ok()).unwrap_or_default();
+        Ok(Age(age))
+    }
+}
+
+impl xitca_web::handler::validate::Validate for Age {
+    type Error = TooYoung;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.0 < 18 {
+            Err(TooYoung(self.0))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn validate_error_impl() {
+    use xitca_web::{
+        handler::{handler_service, validate::Valid},
+        http::WebRequest,
+    };
+
+    async fn handler(Valid(Age(age)): Valid<Age>) -> String {
+        age.to_string()
+    }
+
+    let service = xitca_web::App::new()
+        .at("/", handler_service(handler))
+        .finish()
+        .call(())
+        .await
+        .unwrap();
+
+    let mut req = WebRequest::default();
+    *req.uri_mut() = "/?16".parse().unwrap();
+    let res = service.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let mut req = WebRequest::default();
+    *req.uri_mut() = "/?18".parse().unwrap();
+    let res = service.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
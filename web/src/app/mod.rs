@@ -331,6 +331,58 @@ impl<Obj, CF> App<AppRouter<Obj>, CF> {
         self.router = self.router.insert_typed(typed);
         self
     }
+
+    /// insert routed service with given path to application and describe it's operations in openapi document.
+    ///
+    /// works the same as [App::at] with additional requirement of [DescribeRoute] trait. nested application
+    /// inserted with this method has it's own described routes merged with given path as prefix.
+    ///
+    /// [DescribeRoute]: crate::openapi::DescribeRoute
+    #[cfg(feature = "openapi")]
+    pub fn at_api<F, C, B>(mut self, path: &'static str, builder: F) -> Self
+    where
+        F: RouteGen + Service + Send + Sync + crate::openapi::DescribeRoute,
+        F::Response: for<'r> Service<WebContext<'r, C, B>>,
+        for<'r> WebContext<'r, C, B>: IntoObject<F::Route<F>, (), Object = Obj>,
+    {
+        builder.describe_route(path, self.router.api_mut());
+        self.at(path, builder)
+    }
+
+    /// insert typed route service generated by `#[route]` macro with `openapi` attribute to application and
+    /// describe it's operations in openapi document.
+    #[cfg(feature = "openapi")]
+    pub fn at_typed_api<T, C>(mut self, typed: T) -> Self
+    where
+        T: TypedRoute<C, Route = Obj> + crate::openapi::DescribeTypedRoute,
+    {
+        T::describe_typed_route(self.router.api_mut());
+        self.at_typed(typed)
+    }
+
+    /// generate openapi document with given info from all routes of application.
+    ///
+    /// operations are described by routes inserted with [App::at_api] and [App::at_typed_api]. routes
+    /// with method guard inserted in other ways produce operations without description.
+    ///
+    /// the document can be inserted to application as route service serving itself as json.
+    #[cfg(feature = "openapi")]
+    pub fn openapi(&self, info: crate::openapi::Info) -> crate::openapi::OpenApi
+    where
+        Obj: PathGen,
+    {
+        let mut api = self.router.api().with_info(info);
+        self.router
+            .path_visit("", &mut |path, methods| api.undescribed(path, methods));
+        api
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<Obj, CF> crate::openapi::DescribeRoute for App<AppRouter<Obj>, CF> {
+    fn describe_route(&self, path: &str, api: &mut crate::openapi::OpenApi) {
+        api.merge(path, self.router.api());
+    }
 }
 
 impl<R, CF> App<R, CF> {
@@ -549,6 +601,10 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.router.path_gen(prefix)
     }
+
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[crate::http::Method])) {
+        self.router.path_visit(path, visitor)
+    }
 }
 
 impl<R, F> RouteGen for App<R, F>
@@ -3,7 +3,7 @@ use core::marker::PhantomData;
 use xitca_http::util::service::router::{IntoObject, PathGen, RouteGen, RouteObject};
 use xitca_service::{Service, object::ServiceObject};
 
-use crate::context::WebContext;
+use crate::{context::WebContext, http::Method};
 
 pub type WebObject<C, B, Res, Err> = Box<dyn for<'r> ServiceObject<WebContext<'r, C, B>, Response = Res, Error = Err>>;
 
@@ -26,6 +26,10 @@ where
             fn path_gen(&mut self, prefix: &str) -> String {
                 self.0.path_gen(prefix)
             }
+
+            fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+                self.0.path_visit(path, visitor)
+            }
         }
 
         impl<I, C, B> RouteGen for Builder<I, C, B>
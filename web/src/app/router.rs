@@ -7,11 +7,29 @@ use crate::{
 };
 
 /// application wrap around [Router] and transform it's error type into [Error]
-pub struct AppRouter<Obj>(Router<Obj>);
+pub struct AppRouter<Obj> {
+    router: Router<Obj>,
+    #[cfg(feature = "openapi")]
+    api: crate::openapi::OpenApi,
+}
 
 impl<Obj> AppRouter<Obj> {
     pub(super) fn new() -> Self {
-        Self(Router::new())
+        Self {
+            router: Router::new(),
+            #[cfg(feature = "openapi")]
+            api: Default::default(),
+        }
+    }
+
+    #[cfg(feature = "openapi")]
+    pub(super) fn api(&self) -> &crate::openapi::OpenApi {
+        &self.api
+    }
+
+    #[cfg(feature = "openapi")]
+    pub(super) fn api_mut(&mut self) -> &mut crate::openapi::OpenApi {
+        &mut self.api
     }
 
     pub(super) fn insert<F, Arg, Req>(mut self, path: &'static str, builder: F) -> Self
@@ -20,7 +38,7 @@ impl<Obj> AppRouter<Obj> {
         F::Response: Service<Req>,
         Req: IntoObject<F::Route<F>, Arg, Object = Obj>,
     {
-        self.0 = self.0.insert(path, builder);
+        self.router = self.router.insert(path, builder);
         self
     }
 
@@ -28,7 +46,7 @@ impl<Obj> AppRouter<Obj> {
     where
         T: TypedRoute<M, Route = Obj>,
     {
-        self.0 = self.0.insert_typed(t);
+        self.router = self.router.insert_typed(t);
         self
     }
 }
@@ -38,7 +56,11 @@ where
     Router<Obj>: PathGen,
 {
     fn path_gen(&mut self, prefix: &str) -> String {
-        self.0.path_gen(prefix)
+        self.router.path_gen(prefix)
+    }
+
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[crate::http::Method])) {
+        self.router.path_visit(path, visitor)
     }
 }
 
@@ -61,7 +83,7 @@ where
     type Error = <Router<Obj> as Service<Arg>>::Error;
 
     async fn call(&self, arg: Arg) -> Result<Self::Response, Self::Error> {
-        self.0.call(arg).await.map(RouterService)
+        self.router.call(arg).await.map(RouterService)
     }
 }
 
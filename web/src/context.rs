@@ -130,8 +130,8 @@ impl<'a, C, B> WebContext<'a, C, B> {
     ///
     /// The heap allocation of request would be re-used.
     #[inline]
-    pub fn into_response<ResB: Into<ResponseBody>>(self, body: ResB) -> WebResponse {
-        self.req.as_response(body.into())
+    pub fn into_response<ResB: Into<ResponseBody>>(mut self, body: ResB) -> WebResponse {
+        self.take_head().into_response(body.into())
     }
 
     /// Transform &mut self to a WebResponse with given body type.
@@ -139,7 +139,15 @@ impl<'a, C, B> WebContext<'a, C, B> {
     /// The heap allocation of request would be re-used.
     #[inline]
     pub fn as_response<ResB: Into<ResponseBody>>(&mut self, body: ResB) -> WebResponse {
-        self.req.as_response(body.into())
+        self.take_head().into_response(body.into())
+    }
+
+    // take request head for constructing response. the head's http::Extensions moves into the response
+    // while RequestExt(socket address, route params and matched path) stays in context so routing state
+    // remains observable to enclosing middlewares.
+    pub(crate) fn take_head(&mut self) -> WebRequest<()> {
+        let ext = mem::take(self.req.body_mut());
+        mem::replace(self.req, Request::new(ext))
     }
 
     pub(crate) fn take_body_ref(&self) -> B
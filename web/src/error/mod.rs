@@ -390,6 +390,12 @@ impl From<StdErr> for Error {
             return Self::from(e.clone());
         }
 
+        // same hack for middleware::Timeout where request body stream produce TimeoutError.
+        #[cfg(feature = "timeout")]
+        if let Some(e) = e.downcast_ref::<crate::middleware::timeout::TimeoutError>() {
+            return Self::from(*e);
+        }
+
         Self(Box::new(StdError(e)))
     }
 }
@@ -0,0 +1,279 @@
+//! type extractor for http authentication credential.
+
+use core::fmt;
+
+use std::error;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::{Error, error_from_service},
+    handler::FromRequest,
+    http::{
+        StatusCode, WebResponse,
+        header::{AUTHORIZATION, HeaderValue, WWW_AUTHENTICATE},
+    },
+    service::Service,
+};
+
+/// credential of `Basic` http authentication scheme.
+///
+/// on failure [AuthError] would be returned which would generate a "401 Unauthorized" http response
+/// with `WWW-Authenticate` challenge header.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{auth::BasicAuth, handler_service}, App, WebContext};
+/// async fn handler(auth: BasicAuth) -> String {
+///     format!("hello,{}!", auth.user())
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BasicAuth {
+    user: String,
+    password: String,
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("user", &self.user)
+            .field("password", &"******")
+            .finish()
+    }
+}
+
+impl BasicAuth {
+    /// user id of credential.
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// password of credential.
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    fn parse(value: &HeaderValue) -> Result<Self, AuthError> {
+        let encoded = credential(value, "Basic").ok_or(AuthError::Missing(Scheme::Basic))?;
+        let decoded = STANDARD
+            .decode(encoded)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(AuthError::Malformed(Scheme::Basic))?;
+        let (user, password) = decoded.split_once(':').ok_or(AuthError::Malformed(Scheme::Basic))?;
+        Ok(Self {
+            user: user.to_owned(),
+            password: password.to_owned(),
+        })
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for BasicAuth {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let value = ctx
+            .req()
+            .headers()
+            .get(AUTHORIZATION)
+            .ok_or(AuthError::Missing(Scheme::Basic))?;
+        Self::parse(value).map_err(Error::from)
+    }
+}
+
+/// credential of `Bearer` http authentication scheme.
+///
+/// on failure [AuthError] would be returned which would generate a "401 Unauthorized" http response
+/// with `WWW-Authenticate` challenge header.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{auth::BearerToken, handler_service}, App, WebContext};
+/// async fn handler(token: BearerToken) -> String {
+///     format!("token: {}", token.as_str())
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BearerToken(String);
+
+impl fmt::Debug for BearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BearerToken(******)")
+    }
+}
+
+impl BearerToken {
+    /// get token as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn parse(value: &HeaderValue) -> Result<Self, AuthError> {
+        let token = credential(value, "Bearer").ok_or(AuthError::Missing(Scheme::Bearer))?;
+        // token68 syntax from RFC 6750.
+        let valid = token
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'+' | b'/' | b'='));
+        if !valid {
+            return Err(AuthError::Malformed(Scheme::Bearer));
+        }
+        Ok(Self(token.to_owned()))
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for BearerToken {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let value = ctx
+            .req()
+            .headers()
+            .get(AUTHORIZATION)
+            .ok_or(AuthError::Missing(Scheme::Bearer))?;
+        Self::parse(value).map_err(Error::from)
+    }
+}
+
+// split Authorization header value into scheme and credential. scheme name is case insensitive.
+fn credential<'a>(value: &'a HeaderValue, scheme: &str) -> Option<&'a str> {
+    let (name, credential) = value.to_str().ok()?.trim().split_once(' ')?;
+    name.eq_ignore_ascii_case(scheme)
+        .then(|| credential.trim())
+        .filter(|c| !c.is_empty())
+}
+
+/// http authentication scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Basic,
+    Bearer,
+}
+
+/// error type for http authentication failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// `Authorization` header is absent or it's not using expected scheme.
+    Missing(Scheme),
+    /// credential can not be parsed.
+    Malformed(Scheme),
+    /// credential is parsed but rejected.
+    Rejected(Scheme),
+}
+
+impl AuthError {
+    fn challenge(&self) -> HeaderValue {
+        match *self {
+            Self::Missing(Scheme::Basic) | Self::Malformed(Scheme::Basic) | Self::Rejected(Scheme::Basic) => {
+                HeaderValue::from_static("Basic realm=\"restricted\", charset=\"UTF-8\"")
+            }
+            Self::Missing(Scheme::Bearer) => HeaderValue::from_static("Bearer realm=\"restricted\""),
+            Self::Malformed(Scheme::Bearer) => {
+                HeaderValue::from_static("Bearer realm=\"restricted\", error=\"invalid_request\"")
+            }
+            Self::Rejected(Scheme::Bearer) => {
+                HeaderValue::from_static("Bearer realm=\"restricted\", error=\"invalid_token\"")
+            }
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(scheme) => write!(f, "{scheme:?} authentication credential is missing"),
+            Self::Malformed(scheme) => write!(f, "{scheme:?} authentication credential is malformed"),
+            Self::Rejected(scheme) => write!(f, "{scheme:?} authentication credential is rejected"),
+        }
+    }
+}
+
+impl error::Error for AuthError {}
+
+error_from_service!(AuthError);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for AuthError {
+    type Response = WebResponse;
+    type Error = core::convert::Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(ResponseBody::empty());
+        *res.status_mut() = StatusCode::UNAUTHORIZED;
+        res.headers_mut().insert(WWW_AUTHENTICATE, self.challenge());
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use super::*;
+
+    #[test]
+    fn basic() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        let err = BasicAuth::from_request(&ctx).now_or_panic().unwrap_err();
+        let res = err.call(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Basic realm=\"restricted\", charset=\"UTF-8\""
+        );
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("basic Zm9vOmJhcjpiYXo="));
+        let auth = BasicAuth::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(auth.user(), "foo");
+        assert_eq!(auth.password(), "bar:baz");
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Basic !!!"));
+        assert!(BasicAuth::from_request(&ctx).now_or_panic().is_err());
+    }
+
+    #[test]
+    fn bearer() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Bearer abc.DEF-123"));
+        let token = BearerToken::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(token.as_str(), "abc.DEF-123");
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Bearer a,b"));
+        let err = BearerToken::from_request(&ctx).now_or_panic().unwrap_err();
+        let res = err.call(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"restricted\", error=\"invalid_request\""
+        );
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Basic Zm9vOmJhcg=="));
+        assert!(BearerToken::from_request(&ctx).now_or_panic().is_err());
+    }
+}
@@ -138,8 +138,8 @@ impl<'r, C, B, ResB> Responder<WebContext<'r, C, B>> for ResponseBody<ResB> {
     type Error = Error;
 
     #[inline]
-    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-        Ok(ctx.req.as_response(self))
+    async fn respond(self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        Ok(ctx.take_head().into_response(self))
     }
 
     #[inline]
@@ -0,0 +1,64 @@
+//! type extractor for client ip address.
+
+pub use xitca_http::util::middleware::client_ip::{Cidr, ClientIp, ClientIpResolver, ForwardedHeader, InvalidCidr};
+
+use crate::{context::WebContext, error::Error, handler::FromRequest};
+
+/// extract [ClientIp] resolved by [ClientIpResolver] middleware. fall back to peer address of connection
+/// when the middleware is absent.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{handler_service, client_ip::{ClientIp, ClientIpResolver}}, App, WebContext};
+/// App::new()
+///     .at("/", handler_service(|ClientIp(ip): ClientIp| async move { ip.to_string() }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // trust forwarded headers from proxies in private network.
+///     .enclosed(ClientIpResolver::new().trust("10.0.0.0/8".parse().unwrap()));
+/// ```
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for ClientIp {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let req = ctx.req();
+        Ok(ClientIp::from_extensions(req.extensions(), req.body().socket_addr()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{App, handler::handler_service, http::WebRequest, service::Service, test::collect_string_body};
+
+    use super::*;
+
+    #[test]
+    fn extract() {
+        let service = App::new()
+            .at(
+                "/",
+                handler_service(|ClientIp(ip): ClientIp| async move { ip.to_string() }),
+            )
+            .enclosed(ClientIpResolver::new().trust("10.0.0.0/8".parse().unwrap()))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |peer: [u8; 4], forwarded: &'static str| {
+            let mut req = WebRequest::default();
+            *req.body_mut().socket_addr_mut() = SocketAddr::new(IpAddr::V4(Ipv4Addr::from(peer)), 80);
+            req.headers_mut().insert("x-forwarded-for", forwarded.parse().unwrap());
+            let res = service.call(req).now_or_panic().unwrap();
+            collect_string_body(res.into_body()).now_or_panic().unwrap()
+        };
+
+        assert_eq!(call([10, 0, 0, 1], "1.2.3.4"), "1.2.3.4");
+        assert_eq!(call([5, 6, 7, 8], "1.2.3.4"), "5.6.7.8");
+    }
+}
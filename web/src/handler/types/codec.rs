@@ -0,0 +1,50 @@
+//! shared extract and respond logic of serde based body types.
+
+use crate::{
+    body::BodyStream,
+    context::WebContext,
+    error::Error,
+    handler::FromRequest,
+    http::{
+        WebResponse,
+        header::{CONTENT_TYPE, HeaderValue},
+    },
+};
+
+use super::{
+    body::Limit,
+    header::{self, HeaderRef},
+};
+
+/// default max size of body in bytes.
+pub const DEFAULT_LIMIT: usize = 1024 * 1024;
+
+// check content type header and collect request body with given limit then decode it with given codec.
+pub(super) async fn extract<'a, 'r, C, B, T, E, const LIMIT: usize>(
+    ctx: &'a WebContext<'r, C, B>,
+    decode: impl FnOnce(&[u8]) -> Result<T, E>,
+) -> Result<T, Error>
+where
+    B: BodyStream + Default,
+    Error: From<E>,
+{
+    HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
+    let (buf, _) = <(Vec<u8>, Limit<LIMIT>)>::from_request(ctx).await?;
+    decode(&buf).map_err(Into::into)
+}
+
+// encode value with given codec and generate response with given content type.
+pub(super) fn respond<E, F>(
+    encode: impl FnOnce() -> Result<Vec<u8>, E>,
+    content_type: HeaderValue,
+    func: F,
+) -> Result<WebResponse, Error>
+where
+    Error: From<E>,
+    F: FnOnce(Vec<u8>) -> WebResponse,
+{
+    let buf = encode()?;
+    let mut res = func(buf);
+    res.headers_mut().insert(CONTENT_TYPE, content_type);
+    Ok(res)
+}
@@ -24,7 +24,7 @@ macro_rules! const_header_name {
 
 macro_rules! map_to_header_name {
     ($($i:ident), +) => {
-        const fn map_to_header_name<const HEADER_NAME: usize>() -> header::HeaderName {
+        pub(crate) const fn map_to_header_name<const HEADER_NAME: usize>() -> header::HeaderName {
             match HEADER_NAME  {
             $(
                 $i => header::$i,
@@ -15,16 +15,17 @@ use crate::{
     context::WebContext,
     error::{Error, error_from_service, forward_blank_bad_request},
     handler::{FromRequest, Responder},
-    http::{WebResponse, const_header_value::JSON, header::CONTENT_TYPE},
+    http::{WebResponse, const_header_value::JSON},
     service::Service,
 };
 
 use super::{
     body::Limit,
+    codec,
     header::{self, HeaderRef},
 };
 
-pub const DEFAULT_LIMIT: usize = 1024 * 1024;
+pub use super::codec::DEFAULT_LIMIT;
 
 /// Extract type for Json object. const generic param LIMIT is for max size of the object in bytes.
 /// Object larger than limit would be treated as error.
@@ -68,9 +69,9 @@ where
     type Error = Error;
 
     async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
-        HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
-        let (buf, _) = <(Vec<u8>, Limit<LIMIT>)>::from_request(ctx).await?;
-        serde_json::from_slice(&buf).map(Json).map_err(Into::into)
+        codec::extract::<_, _, _, _, LIMIT>(ctx, |buf| serde_json::from_slice(buf))
+            .await
+            .map(Json)
     }
 }
 
@@ -162,10 +163,7 @@ impl<T> Json<T> {
         T: Serialize,
         F: FnOnce(Vec<u8>) -> WebResponse,
     {
-        let buf = serde_json::to_vec(&self.0)?;
-        let mut res = func(buf);
-        res.headers_mut().insert(CONTENT_TYPE, JSON);
-        Ok(res)
+        codec::respond(|| serde_json::to_vec(&self.0), JSON, func)
     }
 }
 
@@ -229,7 +227,10 @@ mod test {
     use crate::{
         App,
         handler::handler_service,
-        http::{WebRequest, header::CONTENT_LENGTH},
+        http::{
+            WebRequest,
+            header::{CONTENT_LENGTH, CONTENT_TYPE},
+        },
         test::collect_string_body,
     };
 
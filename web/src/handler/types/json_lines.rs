@@ -0,0 +1,338 @@
+//! type extractor and responder for newline delimited json(NDJSON/JSON Lines) stream.
+
+use core::{
+    error, fmt,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
+use serde_core::{de::DeserializeOwned, ser::Serialize};
+
+use crate::{
+    body::{BodyStream, RequestBody, ResponseBody},
+    bytes::{BufMut, Bytes, BytesMut},
+    context::WebContext,
+    error::{BodyError, BodyOverFlow, Error},
+    handler::{FromRequest, Responder},
+    http::{
+        WebResponse,
+        header::{CONTENT_TYPE, HeaderValue},
+    },
+};
+
+use super::{
+    header::{self, HeaderRef},
+    json::DEFAULT_LIMIT,
+};
+
+const NDJSON: HeaderValue = HeaderValue::from_static("application/x-ndjson");
+
+/// extract request body as a [Stream] of json values separated by line break. const generic param
+/// LIMIT is for max size of a single line in bytes. line larger than limit would be treated as
+/// error and terminate the stream. when LIMIT == 0 line size is unlimited.
+///
+/// a line failed to deserialize yields an error and the stream continues with the next line.
+/// blank lines are skipped. stream is only available for body type that is [Unpin].
+///
+/// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// # Examples
+/// ```rust
+/// # use futures_util::StreamExt;
+/// # use serde::Deserialize;
+/// # use xitca_web::{error::Error, handler::{handler_service, json_lines::JsonLines}, App, WebContext};
+/// #[derive(Deserialize)]
+/// struct Record {
+///     name: String,
+/// }
+///
+/// // import records one by one without buffering the whole body.
+/// async fn handler(mut lines: JsonLines<Record>) -> Result<String, Error> {
+///     let mut count = 0;
+///     while let Some(record) = lines.next().await {
+///         let _name = record?.name;
+///         count += 1;
+///     }
+///     Ok(format!("imported {count} records"))
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+pub struct JsonLines<T, B = RequestBody, const LIMIT: usize = DEFAULT_LIMIT> {
+    body: B,
+    buf: BytesMut,
+    // length of buf prefix known to have no line break.
+    scanned: usize,
+    eof: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T, B, const LIMIT: usize> fmt::Debug for JsonLines<T, B, LIMIT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines")
+            .field("buffered", &self.buf.len())
+            .field("eof", &self.eof)
+            .finish()
+    }
+}
+
+impl<'a, 'r, C, B, T, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for JsonLines<T, B, LIMIT>
+where
+    B: BodyStream + Default,
+    T: DeserializeOwned,
+{
+    type Type<'b> = JsonLines<T, B, LIMIT>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
+        Ok(JsonLines {
+            body: ctx.take_body_ref(),
+            buf: BytesMut::new(),
+            scanned: 0,
+            eof: false,
+            _item: PhantomData,
+        })
+    }
+}
+
+impl<T, B, const LIMIT: usize> Stream for JsonLines<T, B, LIMIT>
+where
+    B: BodyStream + Unpin,
+    T: DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            let line = match this.buf[this.scanned..].iter().position(|b| *b == b'\n') {
+                Some(pos) => {
+                    let line = this.buf.split_to(this.scanned + pos + 1);
+                    this.scanned = 0;
+                    Some(line)
+                }
+                None if this.eof => {
+                    this.scanned = 0;
+                    (!this.buf.is_empty()).then(|| this.buf.split())
+                }
+                None => {
+                    this.scanned = this.buf.len();
+                    None
+                }
+            };
+
+            if let Some(line) = line {
+                let line = line.trim_ascii();
+                if line.is_empty() {
+                    continue;
+                }
+                if LIMIT > 0 && line.len() > LIMIT {
+                    return Poll::Ready(Some(Err(this.overflow())));
+                }
+                return Poll::Ready(Some(serde_json::from_slice(line).map_err(Into::into)));
+            }
+
+            if this.eof {
+                return Poll::Ready(None);
+            }
+
+            if LIMIT > 0 && this.buf.len() > LIMIT {
+                return Poll::Ready(Some(Err(this.overflow())));
+            }
+
+            match ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                Some(Ok(chunk)) => this.buf.extend_from_slice(chunk.as_ref()),
+                Some(Err(e)) => {
+                    this.eof = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(Error::from(e.into()))));
+                }
+                None => this.eof = true,
+            }
+        }
+    }
+}
+
+impl<T, B, const LIMIT: usize> JsonLines<T, B, LIMIT> {
+    // oversized line terminates the stream as the boundary of next line is unknown.
+    fn overflow(&mut self) -> Error {
+        self.eof = true;
+        self.buf.clear();
+        Error::from(BodyOverFlow { limit: LIMIT })
+    }
+}
+
+/// newline delimited json responder. wraps a [Stream] of `Result<T, E>` where T is serialized into a
+/// single line of json.
+///
+/// items are serialized one at a time when response body is polled so a slow client would apply
+/// backpressure to the stream. an error from the stream or from serialization terminates response
+/// body abruptly so client can tell an incomplete response from a complete one.
+///
+/// response is sent with `application/x-ndjson` content type.
+///
+/// # Examples
+/// ```rust
+/// # use std::convert::Infallible;
+/// # use futures_util::stream;
+/// # use xitca_web::{handler::{handler_service, json_lines::JsonLinesResponse}, App, WebContext};
+/// async fn handler() -> JsonLinesResponse<impl futures_core::Stream<Item = Result<u64, Infallible>>> {
+///     JsonLinesResponse(stream::iter((0..3).map(Ok)))
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+pub struct JsonLinesResponse<S>(pub S);
+
+impl<'r, C, B, S, T, E> Responder<WebContext<'r, C, B>> for JsonLinesResponse<S>
+where
+    S: Stream<Item = Result<T, E>> + 'static,
+    T: Serialize,
+    E: error::Error + Send + Sync + 'static,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let res = ctx.into_response(ResponseBody::empty());
+        Responder::<WebContext<'r, C, B>>::map(self, res)
+    }
+
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        let mut res = res.map(|_| ResponseBody::box_stream(JsonLinesBody { stream: self.0 }));
+        res.headers_mut().insert(CONTENT_TYPE, NDJSON);
+        Ok(res)
+    }
+}
+
+pin_project! {
+    struct JsonLinesBody<S> {
+        #[pin]
+        stream: S,
+    }
+}
+
+impl<S, T, E> Stream for JsonLinesBody<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Serialize,
+    E: Into<BodyError>,
+{
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = match ready!(self.project().stream.poll_next(cx)) {
+            Some(item) => item.map_err(Into::into)?,
+            None => return Poll::Ready(None),
+        };
+        let mut writer = BytesMut::new().writer();
+        serde_json::to_writer(&mut writer, &item)?;
+        let mut buf = writer.into_inner();
+        buf.put_u8(b'\n');
+        Poll::Ready(Some(Ok(buf.freeze())))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // body size is unknown.
+        (0, None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use futures_util::stream::{self, StreamExt};
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{http::header::CONTENT_TYPE, test::collect_string_body};
+
+    use super::*;
+
+    fn lines<const LIMIT: usize>(
+        chunks: &'static [&'static str],
+    ) -> JsonLines<u32, impl Stream<Item = Result<Bytes, Infallible>>, LIMIT> {
+        JsonLines {
+            body: stream::iter(chunks.iter().map(|c| Ok(Bytes::from_static(c.as_bytes())))),
+            buf: BytesMut::new(),
+            scanned: 0,
+            eof: false,
+            _item: PhantomData,
+        }
+    }
+
+    #[test]
+    fn extract() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut()
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+        *ctx.body_borrow_mut() = b"1\n2\n"[..].into();
+
+        let lines = JsonLines::<u32>::from_request(&ctx).now_or_panic().unwrap();
+        let res = lines.map(Result::unwrap).collect::<Vec<_>>().now_or_panic();
+        assert_eq!(res, [1, 2]);
+    }
+
+    #[test]
+    fn split_chunks() {
+        let res = lines::<DEFAULT_LIMIT>(&["1", "2\r\n\n 3", "\n", "4"])
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .now_or_panic();
+        assert_eq!(res, [12, 3, 4]);
+
+        let res = lines::<DEFAULT_LIMIT>(&["1\nfoo\n3\n"])
+            .collect::<Vec<_>>()
+            .now_or_panic();
+        assert_eq!(res.len(), 3);
+        assert!(res[1].as_ref().is_err());
+        assert_eq!(*res[2].as_ref().unwrap(), 3);
+    }
+
+    #[test]
+    fn limit() {
+        let res = lines::<3>(&["123\n12", "34\n1\n"]).collect::<Vec<_>>().now_or_panic();
+        assert_eq!(res.len(), 2);
+        assert_eq!(*res[0].as_ref().unwrap(), 123);
+        let err = res[1].as_ref().err().unwrap();
+        assert!(err.upcast().downcast_ref::<BodyOverFlow>().is_some());
+    }
+
+    #[test]
+    fn respond() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let items = stream::iter([Ok::<_, Infallible>(vec![1]), Ok(vec![2, 3])]);
+        let res = JsonLinesResponse(items).respond(ctx).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/x-ndjson");
+
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "[1]\n[2,3]\n");
+    }
+
+    #[test]
+    fn respond_error() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let items = stream::iter([Ok(1), Err(BodyOverFlow { limit: 0 }), Ok(2)]);
+        let res = JsonLinesResponse(items).respond(ctx).now_or_panic().unwrap();
+
+        let mut body = core::pin::pin!(res.into_body());
+        let chunk = body.next().now_or_panic().unwrap().unwrap();
+        assert_eq!(chunk.as_ref(), b"1\n");
+        assert!(body.next().now_or_panic().unwrap().is_err());
+    }
+}
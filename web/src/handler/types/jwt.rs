@@ -0,0 +1,446 @@
+//! type extractor for json web token.
+
+use core::{fmt, ops::Deref};
+
+use std::{error, io, path::Path};
+
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    errors::ErrorKind,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm},
+};
+use serde_core::de::DeserializeOwned;
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::{Error, error_from_service},
+    handler::{FromRequest, auth::BearerToken, state::BorrowState},
+    http::{
+        StatusCode, WebResponse,
+        header::{HeaderValue, WWW_AUTHENTICATE},
+    },
+    service::Service,
+};
+
+/// set of keys and validation rules for verifying json web token. it must be borrowable from application
+/// state for [Jwt] extractor to function.
+///
+/// supported signing algorithms are HS256, RS256, ES256 and EdDSA(Ed25519). `exp` claim is required and
+/// checked together with `nbf` claim. `aud` and `iss` claims are checked when [JwtKeys::audience] and
+/// [JwtKeys::issuer] are set.
+///
+/// # Examples
+/// ```rust
+/// # use serde::Deserialize;
+/// # use xitca_web::{handler::{handler_service, jwt::{Jwt, JwtKeys}}, App, WebContext};
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// async fn handler(Jwt(claims): Jwt<Claims>) -> String {
+///     format!("hello,{}!", claims.sub)
+/// }
+///
+/// let keys = JwtKeys::new()
+///     .hs256(b"secret")
+///     .audience(["my-service"])
+///     .issuer(["https://auth.example.com"]);
+///
+/// App::new()
+///     .with_state(keys)
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_, JwtKeys>| async{ "infer type" }));
+/// ```
+#[derive(Clone)]
+pub struct JwtKeys {
+    keys: Vec<Key>,
+    validation: Validation,
+}
+
+#[derive(Clone)]
+struct Key {
+    kid: Option<String>,
+    alg: Algorithm,
+    key: DecodingKey,
+}
+
+impl Default for JwtKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JwtKeys {
+    /// construct an empty key set.
+    pub fn new() -> Self {
+        let mut validation = Validation::default();
+        validation.validate_nbf = true;
+        validation.validate_aud = false;
+        Self {
+            keys: Vec::new(),
+            validation,
+        }
+    }
+
+    /// construct key set from json web key set. keys with unsupported algorithm are ignored.
+    pub fn from_jwks(jwks: &str) -> Result<Self, JwtError> {
+        let set = serde_json::from_str::<JwkSet>(jwks).map_err(|_| JwtError::InvalidKey)?;
+        set.keys.iter().try_fold(Self::new(), Self::jwk)
+    }
+
+    /// construct key set from json web key set file. see [JwtKeys::from_jwks] for detail.
+    pub fn from_jwks_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let jwks = std::fs::read_to_string(path)?;
+        Self::from_jwks(&jwks).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// add a json web key to key set. key with unsupported algorithm is ignored.
+    pub fn jwk(mut self, jwk: &Jwk) -> Result<Self, JwtError> {
+        let alg = match (jwk.common.key_algorithm, &jwk.algorithm) {
+            (Some(KeyAlgorithm::HS256), _) | (None, AlgorithmParameters::OctetKey(_)) => Algorithm::HS256,
+            (Some(KeyAlgorithm::RS256), _) | (None, AlgorithmParameters::RSA(_)) => Algorithm::RS256,
+            (Some(KeyAlgorithm::ES256), _) => Algorithm::ES256,
+            (None, AlgorithmParameters::EllipticCurve(params)) if params.curve == EllipticCurve::P256 => {
+                Algorithm::ES256
+            }
+            (Some(KeyAlgorithm::EdDSA), _) => Algorithm::EdDSA,
+            (None, AlgorithmParameters::OctetKeyPair(params)) if params.curve == EllipticCurve::Ed25519 => {
+                Algorithm::EdDSA
+            }
+            _ => return Ok(self),
+        };
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| JwtError::InvalidKey)?;
+        self.keys.push(Key {
+            kid: jwk.common.key_id.clone(),
+            alg,
+            key,
+        });
+        Ok(self)
+    }
+
+    /// add HS256 shared secret to key set.
+    pub fn hs256(self, secret: &[u8]) -> Self {
+        self.key(Algorithm::HS256, DecodingKey::from_secret(secret))
+    }
+
+    /// add RS256 public key in PEM format to key set.
+    pub fn rs256_pem(self, pem: &[u8]) -> Result<Self, JwtError> {
+        let key = DecodingKey::from_rsa_pem(pem).map_err(|_| JwtError::InvalidKey)?;
+        Ok(self.key(Algorithm::RS256, key))
+    }
+
+    /// add ES256 public key in PEM format to key set.
+    pub fn es256_pem(self, pem: &[u8]) -> Result<Self, JwtError> {
+        let key = DecodingKey::from_ec_pem(pem).map_err(|_| JwtError::InvalidKey)?;
+        Ok(self.key(Algorithm::ES256, key))
+    }
+
+    /// add EdDSA(Ed25519) public key in PEM format to key set.
+    pub fn eddsa_pem(self, pem: &[u8]) -> Result<Self, JwtError> {
+        let key = DecodingKey::from_ed_pem(pem).map_err(|_| JwtError::InvalidKey)?;
+        Ok(self.key(Algorithm::EdDSA, key))
+    }
+
+    /// set accepted values of `aud` claim. token must contain at least one of them.
+    pub fn audience<T: ToString>(mut self, aud: impl IntoIterator<Item = T>) -> Self {
+        let aud = aud.into_iter().collect::<Vec<_>>();
+        self.validation.set_audience(&aud);
+        self.validation.validate_aud = true;
+        self
+    }
+
+    /// set accepted values of `iss` claim.
+    pub fn issuer<T: ToString>(mut self, iss: impl IntoIterator<Item = T>) -> Self {
+        let iss = iss.into_iter().collect::<Vec<_>>();
+        self.validation.set_issuer(&iss);
+        self
+    }
+
+    /// set leeway in seconds for checking `exp` and `nbf` claims. default to 60 seconds.
+    pub fn leeway(mut self, secs: u64) -> Self {
+        self.validation.leeway = secs;
+        self
+    }
+
+    fn key(mut self, alg: Algorithm, key: DecodingKey) -> Self {
+        self.keys.push(Key { kid: None, alg, key });
+        self
+    }
+
+    /// verify token and deserialize it's claims.
+    pub fn verify<T>(&self, token: &str) -> Result<T, JwtError>
+    where
+        T: DeserializeOwned,
+    {
+        let header = decode_header(token).map_err(JwtError::from)?;
+
+        let mut keys = self
+            .keys
+            .iter()
+            .filter(|k| k.alg == header.alg)
+            .filter(|k| match (header.kid.as_deref(), k.kid.as_deref()) {
+                (Some(kid), Some(k)) => kid == k,
+                _ => true,
+            })
+            .peekable();
+
+        if keys.peek().is_none() {
+            return Err(JwtError::UnknownKey);
+        }
+
+        let mut validation = self.validation.clone();
+        validation.algorithms = vec![header.alg];
+
+        let mut err = JwtError::InvalidSignature;
+
+        for key in keys {
+            match decode::<T>(token, &key.key, &validation) {
+                Ok(data) => return Ok(data.claims),
+                // try next key when signature does not match.
+                Err(e) => match JwtError::from(e) {
+                    JwtError::InvalidSignature => {}
+                    e => err = e,
+                },
+            }
+        }
+
+        Err(err)
+    }
+}
+
+/// json web token extractor. token is extracted from `Authorization` header with `Bearer` scheme and
+/// verified against [JwtKeys] borrowed from application state. claims is deserialized to type `T`.
+///
+/// on failure of extracting bearer token [AuthError] would be returned. on failure of verifying token
+/// [JwtError] would be returned. both would generate a "401 Unauthorized" http response.
+///
+/// [AuthError]: crate::handler::auth::AuthError
+pub struct Jwt<T>(pub T);
+
+impl<T: fmt::Debug> fmt::Debug for Jwt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Jwt({:?})", self.0)
+    }
+}
+
+impl<T> Deref for Jwt<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for Jwt<T>
+where
+    C: BorrowState<JwtKeys>,
+    T: DeserializeOwned,
+{
+    type Type<'b> = Jwt<T>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let token = BearerToken::from_request(ctx).await?;
+        ctx.state()
+            .borrow()
+            .verify(token.as_str())
+            .map(Jwt)
+            .map_err(Error::from)
+    }
+}
+
+/// error type for json web token verification failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JwtError {
+    /// token can not be decoded.
+    Malformed,
+    /// no key in key set matches token's algorithm and key id.
+    UnknownKey,
+    /// key is not valid for it's algorithm.
+    InvalidKey,
+    /// token's signature does not match.
+    InvalidSignature,
+    /// token is expired according to `exp` claim.
+    Expired,
+    /// token is not valid yet according to `nbf` claim.
+    NotYetValid,
+    /// `aud` claim does not match.
+    InvalidAudience,
+    /// `iss` claim does not match.
+    InvalidIssuer,
+    /// required claim is missing or claims can not be deserialized.
+    InvalidClaims,
+}
+
+impl From<jsonwebtoken::errors::Error> for JwtError {
+    fn from(e: jsonwebtoken::errors::Error) -> Self {
+        match e.kind() {
+            ErrorKind::InvalidSignature => Self::InvalidSignature,
+            ErrorKind::ExpiredSignature => Self::Expired,
+            ErrorKind::ImmatureSignature => Self::NotYetValid,
+            ErrorKind::InvalidAudience => Self::InvalidAudience,
+            ErrorKind::InvalidIssuer => Self::InvalidIssuer,
+            ErrorKind::MissingRequiredClaim(_) | ErrorKind::InvalidSubject | ErrorKind::Json(_) => Self::InvalidClaims,
+            ErrorKind::InvalidEcdsaKey | ErrorKind::InvalidRsaKey(_) | ErrorKind::InvalidKeyFormat => Self::InvalidKey,
+            _ => Self::Malformed,
+        }
+    }
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Malformed => "json web token is malformed",
+            Self::UnknownKey => "json web token's key is unknown",
+            Self::InvalidKey => "json web token's key is invalid",
+            Self::InvalidSignature => "json web token's signature is invalid",
+            Self::Expired => "json web token is expired",
+            Self::NotYetValid => "json web token is not valid yet",
+            Self::InvalidAudience => "json web token's audience is invalid",
+            Self::InvalidIssuer => "json web token's issuer is invalid",
+            Self::InvalidClaims => "json web token's claims are invalid",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl error::Error for JwtError {}
+
+error_from_service!(JwtError);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for JwtError {
+    type Response = WebResponse;
+    type Error = core::convert::Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(ResponseBody::empty());
+        match self {
+            Self::InvalidKey => *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR,
+            _ => {
+                *res.status_mut() = StatusCode::UNAUTHORIZED;
+                res.headers_mut().insert(
+                    WWW_AUTHENTICATE,
+                    HeaderValue::from_static("Bearer realm=\"restricted\", error=\"invalid_token\""),
+                );
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    fn unix_now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Claims {
+        sub: String,
+        exp: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        nbf: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        aud: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        iss: Option<String>,
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            sub: "foo".into(),
+            exp: unix_now() + 3600,
+            nbf: None,
+            aud: Some("bar".into()),
+            iss: Some("xitca".into()),
+        }
+    }
+
+    fn token(claims: &Claims, secret: &[u8]) -> String {
+        encode(&Header::default(), claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    #[test]
+    fn verify() {
+        let keys = JwtKeys::new().hs256(b"secret").audience(["bar"]).issuer(["xitca"]);
+
+        let claims = claims();
+        assert_eq!(keys.verify::<Claims>(&token(&claims, b"secret")).unwrap(), claims);
+
+        let err = keys.verify::<Claims>(&token(&claims, b"secret2")).unwrap_err();
+        assert_eq!(err, JwtError::InvalidSignature);
+
+        let mut expired = super::test::claims();
+        expired.exp = unix_now() - 3600;
+        let err = keys.verify::<Claims>(&token(&expired, b"secret")).unwrap_err();
+        assert_eq!(err, JwtError::Expired);
+
+        let mut immature = super::test::claims();
+        immature.nbf = Some(unix_now() + 1800);
+        let err = keys.verify::<Claims>(&token(&immature, b"secret")).unwrap_err();
+        assert_eq!(err, JwtError::NotYetValid);
+
+        let mut aud = super::test::claims();
+        aud.aud = Some("baz".into());
+        let err = keys.verify::<Claims>(&token(&aud, b"secret")).unwrap_err();
+        assert_eq!(err, JwtError::InvalidAudience);
+
+        let mut iss = super::test::claims();
+        iss.iss = Some("baz".into());
+        let err = keys.verify::<Claims>(&token(&iss, b"secret")).unwrap_err();
+        assert_eq!(err, JwtError::InvalidIssuer);
+
+        let err = keys.verify::<Claims>("not.a.token").unwrap_err();
+        assert_eq!(err, JwtError::Malformed);
+    }
+
+    #[test]
+    fn jwks() {
+        // "secret" in base64url.
+        let keys = JwtKeys::from_jwks(r#"{"keys":[{"kty":"oct","kid":"1","k":"c2VjcmV0"}]}"#)
+            .unwrap()
+            .audience(["bar"]);
+
+        let claims = claims();
+
+        let mut header = Header {
+            kid: Some("1".into()),
+            ..Default::default()
+        };
+        let token = encode(&header, &claims, &EncodingKey::from_secret(b"secret")).unwrap();
+        assert_eq!(keys.verify::<Claims>(&token).unwrap(), claims);
+
+        header.kid = Some("2".into());
+        let token = encode(&header, &claims, &EncodingKey::from_secret(b"secret")).unwrap();
+        assert_eq!(keys.verify::<Claims>(&token).unwrap_err(), JwtError::UnknownKey);
+    }
+
+    #[test]
+    fn extract() {
+        use xitca_unsafe_collection::futures::NowOrPanic;
+
+        use crate::http::header::AUTHORIZATION;
+
+        let mut ctx = WebContext::new_test(JwtKeys::new().hs256(b"secret").audience(["bar"]));
+        let mut ctx = ctx.as_web_ctx();
+
+        let value = format!("Bearer {}", token(&claims(), b"secret"));
+        ctx.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::try_from(value).unwrap());
+
+        let Jwt(claims) = Jwt::<Claims>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(claims.sub, "foo");
+    }
+}
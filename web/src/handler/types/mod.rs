@@ -1,11 +1,15 @@
 pub mod body;
+pub mod client_ip;
 pub mod extension;
 pub mod header;
 pub mod html;
+pub mod negotiate;
 pub mod path;
 pub mod redirect;
+pub mod request_id;
 pub mod state;
 pub mod text;
+pub mod typed_header;
 pub mod uri;
 
 #[cfg(feature = "params")]
@@ -20,11 +24,41 @@ pub mod form;
 #[cfg(feature = "json")]
 pub mod json;
 
+#[cfg(feature = "json")]
+pub mod json_lines;
+
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+#[cfg(feature = "cbor")]
+pub mod cbor;
+
+#[cfg(any(feature = "json", feature = "msgpack", feature = "cbor"))]
+mod codec;
+
 #[cfg(feature = "cookie")]
 pub mod cookie;
 
+#[cfg(feature = "auth")]
+pub mod auth;
+
+#[cfg(feature = "jwt")]
+pub mod jwt;
+
+#[cfg(feature = "session")]
+pub mod session;
+
 #[cfg(feature = "multipart")]
 pub mod multipart;
 
 #[cfg(feature = "websocket")]
 pub mod websocket;
+
+#[cfg(feature = "sse")]
+pub mod sse;
+
+#[cfg(feature = "validate")]
+pub mod validate;
+
+#[cfg(feature = "__server")]
+pub mod shutdown;
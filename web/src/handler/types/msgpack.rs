@@ -0,0 +1,195 @@
+//! type extractor and response generator for MessagePack
+
+use core::{
+    convert::Infallible,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use serde_core::{de::DeserializeOwned, ser::Serialize};
+use xitca_http::util::service::router::{PathGen, RouteGen, RouterMapErr};
+
+use crate::{
+    body::BodyStream,
+    context::WebContext,
+    error::{Error, error_from_service, forward_blank_bad_request, forward_blank_internal},
+    handler::{FromRequest, Responder},
+    http::{WebResponse, header::HeaderValue},
+    service::Service,
+};
+
+use super::codec;
+
+pub use super::codec::DEFAULT_LIMIT;
+
+const MSGPACK: HeaderValue = HeaderValue::from_static("application/msgpack");
+
+/// Extract type for MessagePack object. const generic param LIMIT is for max size of the object in bytes.
+/// Object larger than limit would be treated as error.
+///
+/// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// As responder the object is serialized with struct fields as map keys and `Content-Type` header is set
+/// to `application/msgpack`.
+#[derive(Clone)]
+pub struct MsgPack<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> fmt::Debug for MsgPack<T, LIMIT>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MsgPack")
+            .field("value", &self.0)
+            .field("limit", &LIMIT)
+            .finish()
+    }
+}
+
+impl<T, const LIMIT: usize> Deref for MsgPack<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> DerefMut for MsgPack<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for MsgPack<T, LIMIT>
+where
+    B: BodyStream + Default,
+    T: DeserializeOwned,
+{
+    type Type<'b> = MsgPack<T, LIMIT>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        codec::extract::<_, _, _, _, LIMIT>(ctx, |buf| rmp_serde::from_slice(buf))
+            .await
+            .map(MsgPack)
+    }
+}
+
+impl<'r, C, B, T> Responder<WebContext<'r, C, B>> for MsgPack<T>
+where
+    T: Serialize,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        self._respond(|buf| ctx.into_response(buf))
+    }
+
+    #[inline]
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        self._respond(|buf| res.map(|_| buf.into()))
+    }
+}
+
+impl<T> MsgPack<T> {
+    fn _respond<F>(self, func: F) -> Result<WebResponse, Error>
+    where
+        T: Serialize,
+        F: FnOnce(Vec<u8>) -> WebResponse,
+    {
+        codec::respond(|| rmp_serde::to_vec_named(&self.0), MSGPACK, func)
+    }
+}
+
+error_from_service!(rmp_serde::decode::Error);
+forward_blank_bad_request!(rmp_serde::decode::Error);
+
+error_from_service!(rmp_serde::encode::Error);
+forward_blank_internal!(rmp_serde::encode::Error);
+
+impl<T> PathGen for MsgPack<T> {}
+
+impl<T> RouteGen for MsgPack<T> {
+    type Route<R> = RouterMapErr<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        RouterMapErr(route)
+    }
+}
+
+impl<T> Service for MsgPack<T>
+where
+    T: Clone,
+{
+    type Response = Self;
+    type Error = Infallible;
+
+    async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<'r, C, B, T> Service<WebContext<'r, C, B>> for MsgPack<T>
+where
+    T: Serialize + Clone,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        self.clone().respond(ctx).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        error::BodyOverFlow,
+        http::{
+            StatusCode,
+            header::{CONTENT_LENGTH, CONTENT_TYPE},
+        },
+        test::collect_body,
+    };
+
+    use super::*;
+
+    #[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+    struct Gacha {
+        credit_card: String,
+    }
+
+    #[test]
+    fn extract_respond() {
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+
+        let gacha = Gacha {
+            credit_card: "declined".into(),
+        };
+        let res = MsgPack(gacha.clone()).respond(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/msgpack");
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, MSGPACK);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        *ctx.body_borrow_mut() = body.clone().into();
+
+        let MsgPack(res) = MsgPack::<Gacha>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(res, gacha);
+
+        *ctx.body_borrow_mut() = body.into();
+        let err = MsgPack::<Gacha, 4>::from_request(&ctx).now_or_panic().unwrap_err();
+        assert!(err.upcast().downcast_ref::<BodyOverFlow>().is_some());
+
+        *ctx.body_borrow_mut() = b"\xc1"[..].into();
+        let err = MsgPack::<Gacha>::from_request(&ctx).now_or_panic().unwrap_err();
+        let res = err.call(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
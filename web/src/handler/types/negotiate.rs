@@ -0,0 +1,332 @@
+//! content negotiation responder driven by [ACCEPT] header.
+//!
+//! [ACCEPT]: crate::http::header::ACCEPT
+
+use core::{convert::Infallible, fmt};
+
+use std::error;
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::{Error, error_from_service},
+    handler::Responder,
+    http::{
+        StatusCode, WebResponse,
+        header::{ACCEPT, CONTENT_TYPE, HeaderValue, VARY},
+    },
+    service::Service,
+};
+
+use super::{
+    html::Html,
+    text::Text,
+    typed_header::{Accept, Header},
+};
+
+type Encoder<T> = Box<dyn FnOnce(T, WebResponse) -> Result<WebResponse, Error>>;
+
+/// responder picking one of registered representations of a value based on [Accept] header of request.
+///
+/// representations are tried with quality value of [Accept] header and registration order breaks the tie.
+/// when request has no or malformed [Accept] header the first registered representation is used.
+/// when no representation is acceptable [NotAcceptable] error is returned and would generate a
+/// "406 Not Acceptable" http response.
+///
+/// `Vary: Accept` header is appended to response in both cases.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, html::Html, negotiate::Negotiate, text::Text},
+/// #   App, WebContext
+/// # };
+/// async fn handler() -> Negotiate<String> {
+///     Negotiate::new(String::from("hello,world!"))
+///         .html(|msg| Html(format!("<p>{msg}</p>")))
+///         .text(Text)
+///         // custom representation with it's media type and encoder.
+///         .with("application/x-bytes", |msg| Ok::<_, std::convert::Infallible>(msg.into_bytes()))
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+pub struct Negotiate<T> {
+    value: T,
+    representations: Vec<(&'static str, Encoder<T>)>,
+}
+
+impl<T> fmt::Debug for Negotiate<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let media_types = self.representations.iter().map(|(mime, _)| mime).collect::<Vec<_>>();
+        f.debug_struct("Negotiate")
+            .field("value", &self.value)
+            .field("media_types", &media_types)
+            .finish()
+    }
+}
+
+impl<T> Negotiate<T>
+where
+    T: 'static,
+{
+    /// construct a new negotiate responder with no representation registered.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            representations: Vec::new(),
+        }
+    }
+
+    /// register `application/json` representation serialized with [Json](super::json::Json) type.
+    #[cfg(feature = "json")]
+    pub fn json(self) -> Self
+    where
+        T: serde_core::Serialize,
+    {
+        self.encoder("application/json", |value, res| {
+            Responder::<WebContext<'_>>::map(super::json::Json(value), res)
+        })
+    }
+
+    /// register `text/html` representation produced by given function.
+    pub fn html<F, B>(self, func: F) -> Self
+    where
+        F: FnOnce(T) -> Html<B> + 'static,
+        B: Into<ResponseBody>,
+    {
+        self.encoder("text/html", |value, res| {
+            Responder::<WebContext<'_>>::map(func(value), res)
+        })
+    }
+
+    /// register `text/plain` representation produced by given function.
+    pub fn text<F, B>(self, func: F) -> Self
+    where
+        F: FnOnce(T) -> Text<B> + 'static,
+        B: Into<ResponseBody>,
+    {
+        self.encoder("text/plain", |value, res| {
+            Responder::<WebContext<'_>>::map(func(value), res)
+        })
+    }
+
+    /// register custom representation with given media type and encoder function.
+    /// the media type is also used as value of [CONTENT_TYPE] header of response.
+    ///
+    /// # Panics
+    /// when media type is not a valid header value.
+    pub fn with<F, B, E>(self, media_type: &'static str, func: F) -> Self
+    where
+        F: FnOnce(T) -> Result<B, E> + 'static,
+        B: Into<ResponseBody>,
+        E: Into<Error>,
+    {
+        let value = HeaderValue::from_static(media_type);
+        self.encoder(media_type, move |t, res| {
+            let body = func(t).map_err(Into::into)?;
+            let mut res = res.map(|_| body.into());
+            res.headers_mut().insert(CONTENT_TYPE, value);
+            Ok(res)
+        })
+    }
+
+    fn encoder<F>(mut self, media_type: &'static str, func: F) -> Self
+    where
+        F: FnOnce(T, WebResponse) -> Result<WebResponse, Error> + 'static,
+    {
+        self.representations.push((media_type, Box::new(func)));
+        self
+    }
+
+    fn select(self, accept: Option<Accept>) -> Result<(T, Encoder<T>), NotAcceptable> {
+        let Self {
+            value,
+            mut representations,
+        } = self;
+
+        let idx = match accept {
+            Some(accept) => {
+                let media_types = representations.iter().map(|(mime, _)| *mime).collect::<Vec<_>>();
+                accept
+                    .preferred(&media_types)
+                    .and_then(|mime| media_types.iter().position(|m| *m == mime))
+            }
+            None => (!representations.is_empty()).then_some(0),
+        };
+
+        match idx {
+            Some(idx) => Ok((value, representations.swap_remove(idx).1)),
+            None => Err(NotAcceptable(
+                representations.into_iter().map(|(mime, _)| mime).collect(),
+            )),
+        }
+    }
+}
+
+impl<'r, C, B, T> Responder<WebContext<'r, C, B>> for Negotiate<T>
+where
+    T: 'static,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut values = ctx.req().headers().get_all(ACCEPT).iter().peekable();
+        let accept = if values.peek().is_some() {
+            Accept::decode(values)
+        } else {
+            None
+        };
+        let (value, encoder) = self.select(accept)?;
+        let res = encoder(value, ctx.into_response(ResponseBody::none()))?;
+        Ok(vary(res))
+    }
+
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        // without request context there is nothing to negotiate with.
+        let (value, encoder) = self.select(None)?;
+        encoder(value, res).map(vary)
+    }
+}
+
+fn vary(mut res: WebResponse) -> WebResponse {
+    res.headers_mut().append(VARY, HeaderValue::from_static("accept"));
+    res
+}
+
+/// error type when none of registered representations of [Negotiate] is acceptable.
+#[derive(Debug)]
+pub struct NotAcceptable(Vec<&'static str>);
+
+impl NotAcceptable {
+    /// media types offered by server.
+    pub fn available(&self) -> &[&'static str] {
+        &self.0
+    }
+}
+
+impl fmt::Display for NotAcceptable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "none of media types [{}] is acceptable", self.0.join(", "))
+    }
+}
+
+impl error::Error for NotAcceptable {}
+
+error_from_service!(NotAcceptable);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for NotAcceptable {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(ResponseBody::empty());
+        *res.status_mut() = StatusCode::NOT_ACCEPTABLE;
+        Ok(vary(res))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{body::ResponseBody, test::collect_string_body};
+
+    use super::*;
+
+    fn negotiate() -> Negotiate<&'static str> {
+        Negotiate::new("996")
+            .html(|v| Html(format!("<p>{v}</p>")))
+            .text(Text)
+            .with("application/x-num", |v| Ok::<_, Infallible>(v.as_bytes()))
+    }
+
+    fn respond(accept: Option<&'static str>) -> Result<WebResponse, Error> {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+        if let Some(accept) = accept {
+            ctx.req_mut()
+                .headers_mut()
+                .insert(ACCEPT, HeaderValue::from_static(accept));
+        }
+        negotiate().respond(ctx).now_or_panic()
+    }
+
+    #[test]
+    fn select() {
+        let res = respond(None).unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/html; charset=utf-8");
+        assert_eq!(res.headers().get(VARY).unwrap(), "accept");
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "<p>996</p>");
+
+        let res = respond(Some("text/html;q=0.5, text/*;q=0.8")).unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/plain; charset=utf-8");
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "996");
+
+        let res = respond(Some("application/*, text/*;q=0.1")).unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/x-num");
+
+        // tie of quality value is broken by registration order.
+        let res = respond(Some("*/*")).unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/html; charset=utf-8");
+
+        // malformed header is ignored.
+        let res = respond(Some("text/plain;q=abc")).unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/html; charset=utf-8");
+    }
+
+    #[test]
+    fn not_acceptable() {
+        let Err(err) = respond(Some("image/png, text/html;q=0")) else {
+            panic!("representation must not be acceptable")
+        };
+        let e = err.upcast().downcast_ref::<NotAcceptable>().unwrap();
+        assert_eq!(e.available(), ["text/html", "text/plain", "application/x-num"]);
+
+        let mut ctx = WebContext::new_test(());
+        let res = err.call(ctx.as_web_ctx()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(res.headers().get(VARY).unwrap(), "accept");
+
+        let Err(err) = Negotiate::new(())
+            .respond(WebContext::new_test(()).as_web_ctx())
+            .now_or_panic()
+        else {
+            panic!("representation must not be acceptable")
+        };
+        assert!(err.upcast().downcast_ref::<NotAcceptable>().is_some());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut()
+            .headers_mut()
+            .insert(ACCEPT, HeaderValue::from_static("application/json"));
+        let res = Negotiate::new(vec![1, 2])
+            .text(|v| Text(format!("{v:?}")))
+            .json()
+            .respond(ctx)
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "[1,2]");
+    }
+
+    #[test]
+    fn map() {
+        let res = Responder::<WebContext<'_>>::map(negotiate(), WebResponse::new(ResponseBody::none())).unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/html; charset=utf-8");
+    }
+}
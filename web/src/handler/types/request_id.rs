@@ -0,0 +1,26 @@
+//! type extractor for request id.
+
+pub use xitca_http::util::middleware::request_trace::RequestId;
+
+use crate::{
+    context::WebContext,
+    error::{Error, ExtensionNotFound},
+    handler::FromRequest,
+};
+
+/// extract [RequestId] attached by [RequestTrace] middleware.
+///
+/// [RequestTrace]: crate::middleware::RequestTrace
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for RequestId {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        ctx.req()
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| Error::from(ExtensionNotFound::from_type::<Self>()))
+    }
+}
@@ -0,0 +1,237 @@
+//! type extractor for server side session.
+
+use core::fmt;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_core::{de::DeserializeOwned, ser::Serialize};
+use serde_json::Value;
+
+use crate::{
+    context::WebContext,
+    error::{Error, ExtensionNotFound},
+    handler::FromRequest,
+};
+
+/// persisted state of a session. [SessionStore] implementations are tasked with storing and loading it.
+///
+/// [SessionStore]: crate::middleware::session::SessionStore
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionRecord {
+    /// unix timestamp in seconds when session is created.
+    pub created: u64,
+    /// unix timestamp in seconds when session is last accessed.
+    pub accessed: u64,
+    /// session data in key value pairs.
+    pub data: HashMap<String, Value>,
+}
+
+impl SessionRecord {
+    pub(crate) fn new() -> Self {
+        let now = unix_now();
+        Self {
+            created: now,
+            accessed: now,
+            data: HashMap::new(),
+        }
+    }
+
+    /// serialize record to json string.
+    pub fn to_json(&self) -> String {
+        let mut obj = serde_json::Map::with_capacity(3);
+        obj.insert("c".into(), self.created.into());
+        obj.insert("a".into(), self.accessed.into());
+        let data = self.data.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        obj.insert("d".into(), Value::Object(data));
+        Value::Object(obj).to_string()
+    }
+
+    /// deserialize record from json string produced by [SessionRecord::to_json].
+    /// return None when input is malformed.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let Value::Object(mut obj) = serde_json::from_str(json).ok()? else {
+            return None;
+        };
+        let created = obj.get("c")?.as_u64()?;
+        let accessed = obj.get("a")?.as_u64()?;
+        let Value::Object(data) = obj.remove("d")? else {
+            return None;
+        };
+        Some(Self {
+            created,
+            accessed,
+            data: data.into_iter().collect(),
+        })
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|dur| dur.as_secs())
+        .unwrap_or(0)
+}
+
+/// session extractor. available when [Session] middleware is applied to application or route.
+///
+/// all modification to session data is persisted by middleware after the handler produce a response.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   error::Error,
+/// #   handler::{handler_service, session::Session},
+/// #   middleware::session::{MemoryStore, Session as SessionMiddleware},
+/// #   handler::cookie::Key,
+/// #   App, WebContext
+/// # };
+/// async fn counter(session: Session) -> Result<String, Error> {
+///     let count = session.get::<u64>("count")?.unwrap_or(0) + 1;
+///     session.insert("count", count)?;
+///     Ok(count.to_string())
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(counter))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(SessionMiddleware::new(MemoryStore::new(), Key::generate()));
+/// ```
+///
+/// [Session]: crate::middleware::session::Session
+#[derive(Clone)]
+pub struct Session(Arc<Mutex<SessionInner>>);
+
+pub(crate) struct SessionInner {
+    pub(crate) record: SessionRecord,
+    pub(crate) changed: bool,
+    pub(crate) renew: bool,
+    pub(crate) purge: bool,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session").field("record", &self.lock().record).finish()
+    }
+}
+
+impl Session {
+    pub(crate) fn new(record: SessionRecord) -> Self {
+        Self(Arc::new(Mutex::new(SessionInner {
+            record,
+            changed: false,
+            renew: false,
+            purge: false,
+        })))
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, SessionInner> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// get value of given key and deserialize it to type T.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        match self.lock().record.data.get(key) {
+            Some(value) => T::deserialize(value).map(Some).map_err(Into::into),
+            None => Ok(None),
+        }
+    }
+
+    /// serialize value and insert it with given key. previous value of the same key is replaced.
+    pub fn insert<T>(&self, key: impl Into<String>, value: T) -> Result<(), Error>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        let mut inner = self.lock();
+        inner.record.data.insert(key.into(), value);
+        inner.changed = true;
+        Ok(())
+    }
+
+    /// remove value of given key. return true when the key exists.
+    pub fn remove(&self, key: &str) -> bool {
+        let mut inner = self.lock();
+        let removed = inner.record.data.remove(key).is_some();
+        inner.changed |= removed;
+        removed
+    }
+
+    /// remove all values from session.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.changed |= !inner.record.data.is_empty();
+        inner.record.data.clear();
+    }
+
+    /// rotate session id while keeping session data. useful for preventing session fixation
+    /// after privilege change like user login.
+    pub fn renew(&self) {
+        self.lock().renew = true;
+    }
+
+    /// destroy session. session data is removed from store and session cookie is removed from client.
+    pub fn purge(&self) {
+        let mut inner = self.lock();
+        inner.record.data.clear();
+        inner.purge = true;
+    }
+
+    /// check if session contains no value.
+    pub fn is_empty(&self) -> bool {
+        self.lock().record.data.is_empty()
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Session {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        ctx.req()
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| Error::from(ExtensionNotFound::from_type::<Self>()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_json() {
+        let mut record = SessionRecord::new();
+        record.data.insert("foo".into(), Value::from("bar"));
+        record.data.insert("num".into(), Value::from(996));
+
+        let json = record.to_json();
+        assert_eq!(SessionRecord::from_json(&json).unwrap(), record);
+
+        assert!(SessionRecord::from_json("{}").is_none());
+        assert!(SessionRecord::from_json("996").is_none());
+    }
+
+    #[test]
+    fn session_data() {
+        let session = Session::new(SessionRecord::new());
+        assert!(session.is_empty());
+
+        session.insert("foo", "bar").unwrap();
+        assert_eq!(session.get::<String>("foo").unwrap().as_deref(), Some("bar"));
+        assert!(session.get::<u64>("foo").is_err());
+        assert!(session.lock().changed);
+
+        assert!(session.remove("foo"));
+        assert!(!session.remove("foo"));
+        assert!(session.is_empty());
+    }
+}
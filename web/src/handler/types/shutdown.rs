@@ -0,0 +1,61 @@
+//! type extractor for graceful shutdown signal.
+
+pub use xitca_http::shutdown::ShutdownToken;
+
+use crate::{context::WebContext, error::Error, handler::FromRequest};
+
+/// extract [ShutdownToken] of current worker thread. the token is triggered when [HttpServer] is stopping
+/// and long-lived handler (websocket, server sent event and other streaming response) can observe it to
+/// finish in time before server's shutdown timeout elapsed.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{handler_service, shutdown::ShutdownToken}, App, WebContext};
+/// async fn handler(token: ShutdownToken) -> &'static str {
+///     // a long running task that finish early when server is stopping.
+///     tokio::select! {
+///         _ = token.wait() => "server is stopping",
+///         _ = long_running_task() => "task is done",
+///     }
+/// }
+/// # async fn long_running_task() {}
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+///
+/// [HttpServer]: crate::HttpServer
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for ShutdownToken {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(_: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        Ok(ShutdownToken::current())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use super::*;
+
+    #[test]
+    fn extract() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let token = ShutdownToken::from_request(&ctx).now_or_panic().unwrap();
+        assert!(!token.is_shutdown());
+
+        ShutdownToken::current().shutdown();
+        assert!(token.is_shutdown());
+        ShutdownToken::from_request(&ctx)
+            .now_or_panic()
+            .unwrap()
+            .wait()
+            .now_or_panic();
+    }
+}
@@ -0,0 +1,365 @@
+//! type extractor and responder for server-sent events.
+
+use core::{
+    error, fmt,
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
+
+use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
+use tokio::time::{Instant, Sleep, sleep};
+
+use crate::{
+    body::ResponseBody,
+    bytes::{BufMut, Bytes, BytesMut},
+    context::WebContext,
+    error::{BodyError, Error, HeaderNotFound},
+    handler::{FromRequest, Responder},
+    http::{
+        WebResponse,
+        header::{CACHE_CONTROL, CONTENT_TYPE, HeaderName, HeaderValue},
+    },
+};
+
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// a single event of server-sent events stream.
+///
+/// line breaks in id, event name and comment are removed. multiple lines of data are sent as multiple
+/// `data` fields and joined by client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// construct an empty event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// construct an event with given data.
+    pub fn data(data: impl Into<String>) -> Self {
+        Self {
+            data: Some(data.into()),
+            ..Self::default()
+        }
+    }
+
+    /// set id of event. client would send it back with `Last-Event-ID` header when reconnecting.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(single_line(id.into()));
+        self
+    }
+
+    /// set name of event.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(single_line(event.into()));
+        self
+    }
+
+    /// set reconnection time of client.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// set comment of event. comment is ignored by client.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(single_line(comment.into()));
+        self
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        if let Some(ref comment) = self.comment {
+            field(buf, "", comment);
+        }
+        if let Some(ref id) = self.id {
+            field(buf, "id", id);
+        }
+        if let Some(ref event) = self.event {
+            field(buf, "event", event);
+        }
+        if let Some(ref data) = self.data {
+            // "\r\n", "\r" and "\n" are all line breaks of event stream. every line is sent as it's own data field.
+            let mut data = data.as_str();
+            while let Some(idx) = data.find(['\r', '\n']) {
+                field(buf, "data", &data[..idx]);
+                let len = if data[idx..].starts_with("\r\n") { 2 } else { 1 };
+                data = &data[idx + len..];
+            }
+            field(buf, "data", data);
+        }
+        if let Some(retry) = self.retry {
+            field(buf, "retry", &retry.as_millis().to_string());
+        }
+        buf.put_u8(b'\n');
+    }
+}
+
+fn single_line(mut value: String) -> String {
+    value.retain(|c| c != '\n' && c != '\r');
+    value
+}
+
+fn field(buf: &mut BytesMut, name: &str, value: &str) {
+    buf.put_slice(name.as_bytes());
+    buf.put_u8(b':');
+    if !value.is_empty() {
+        buf.put_u8(b' ');
+        buf.put_slice(value.as_bytes());
+    }
+    buf.put_u8(b'\n');
+}
+
+/// server-sent events responder. wraps a [Stream] of [Event].
+///
+/// response is sent with `text/event-stream` content type and is not compressed by [Compress] middleware.
+/// a comment is sent as keep-alive when stream is idle for a period of time(15 seconds by default).
+///
+/// # Examples
+/// ```rust
+/// # use std::convert::Infallible;
+/// # use xitca_web::{handler::{handler_service, sse::{Event, LastEventId, Sse}}, App, WebContext};
+/// # use futures_util::stream;
+/// async fn handler(id: Option<LastEventId>) -> Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>> {
+///     // resume from last event id sent by client.
+///     let start = id.and_then(|id| id.parse::<u64>().ok()).map(|id| id + 1).unwrap_or(0);
+///     let events = (start..start + 3).map(|i| Ok(Event::data(format!("message {i}")).id(i.to_string())));
+///     Sse::new(stream::iter(events))
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+///
+/// [Compress]: crate::middleware::compress::Compress
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<Duration>,
+}
+
+impl<S> Sse<S> {
+    /// construct a new responder with given stream of events.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: Some(Duration::from_secs(15)),
+        }
+    }
+
+    /// set the idle period after which a keep-alive comment is sent.
+    pub fn keep_alive(mut self, dur: Duration) -> Self {
+        self.keep_alive = Some(dur);
+        self
+    }
+
+    /// disable keep-alive comment.
+    pub fn disable_keep_alive(mut self) -> Self {
+        self.keep_alive = None;
+        self
+    }
+}
+
+impl<'r, C, B, S, E> Responder<WebContext<'r, C, B>> for Sse<S>
+where
+    S: Stream<Item = Result<Event, E>> + 'static,
+    E: error::Error + Send + Sync + 'static,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let res = ctx.into_response(ResponseBody::empty());
+        Responder::<WebContext<'r, C, B>>::map(self, res)
+    }
+
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        let body = SseBody {
+            stream: self.stream,
+            keep_alive: self.keep_alive,
+            sleep: None,
+        };
+        let mut res = res.map(|_| ResponseBody::box_stream(body));
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        res.headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        Ok(res)
+    }
+}
+
+pin_project! {
+    struct SseBody<S> {
+        #[pin]
+        stream: S,
+        keep_alive: Option<Duration>,
+        // timer is lazily constructed when stream is pending for the first time.
+        sleep: Option<Pin<Box<Sleep>>>,
+    }
+}
+
+impl<S, E> Stream for SseBody<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+    E: Into<BodyError>,
+{
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(res)) => {
+                let event = res.map_err(Into::into)?;
+                if let (Some(dur), Some(sleep)) = (this.keep_alive, this.sleep.as_mut()) {
+                    sleep.as_mut().reset(Instant::now() + *dur);
+                }
+                let mut buf = BytesMut::new();
+                event.encode(&mut buf);
+                Poll::Ready(Some(Ok(buf.freeze())))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                let Some(dur) = *this.keep_alive else {
+                    return Poll::Pending;
+                };
+                let sleep = this.sleep.get_or_insert_with(|| Box::pin(sleep(dur)));
+                ready!(sleep.as_mut().poll(cx));
+                sleep.as_mut().reset(Instant::now() + dur);
+                Poll::Ready(Some(Ok(Bytes::from_static(b":\n\n"))))
+            }
+        }
+    }
+}
+
+/// extract value of `Last-Event-ID` header sent by reconnecting client.
+///
+/// on failure [HeaderNotFound] error would be returned. use `Option<LastEventId>` when the header is optional.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LastEventId(pub String);
+
+impl fmt::Debug for LastEventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LastEventId({})", self.0)
+    }
+}
+
+impl core::ops::Deref for LastEventId {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for LastEventId {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        ctx.req()
+            .headers()
+            .get(LAST_EVENT_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| LastEventId(v.to_owned()))
+            .ok_or_else(|| Error::from_service(HeaderNotFound(LAST_EVENT_ID)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use futures_util::stream::{self, StreamExt};
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::test::collect_string_body;
+
+    use super::*;
+
+    #[test]
+    fn encode() {
+        let mut buf = BytesMut::new();
+        Event::data("hello\nworld")
+            .id("1\n")
+            .event("greet")
+            .retry(Duration::from_secs(3))
+            .comment("ping")
+            .encode(&mut buf);
+        assert_eq!(
+            buf.as_ref(),
+            b": ping\nid: 1\nevent: greet\ndata: hello\ndata: world\nretry: 3000\n\n"
+        );
+
+        // lone carriage return is a line break and can't be used to inject other fields.
+        let mut buf = BytesMut::new();
+        Event::data("x\rid: 1\revent: evil\r\ny\n").encode(&mut buf);
+        assert_eq!(
+            buf.as_ref(),
+            b"data: x\ndata: id: 1\ndata: event: evil\ndata: y\ndata:\n\n"
+        );
+    }
+
+    #[test]
+    fn respond() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let events = stream::iter([Ok::<_, Infallible>(Event::data("996")), Ok(Event::data("251").id("2"))]);
+
+        let res = Sse::new(events).respond(ctx).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/event-stream");
+
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "data: 996\n\nid: 2\ndata: 251\n\n");
+    }
+
+    #[tokio::test]
+    async fn keep_alive() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let events = stream::once(async { Ok::<_, Infallible>(Event::data("996")) }).chain(stream::pending());
+
+        let res = Sse::new(events)
+            .keep_alive(Duration::from_millis(10))
+            .respond(ctx)
+            .await
+            .unwrap();
+
+        let mut body = core::pin::pin!(res.into_body());
+        let chunk = core::future::poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.as_ref(), b"data: 996\n\n");
+        let chunk = core::future::poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.as_ref(), b":\n\n");
+    }
+
+    #[test]
+    fn last_event_id() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        assert!(LastEventId::from_request(&ctx).now_or_panic().is_err());
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(LAST_EVENT_ID, HeaderValue::from_static("996"));
+        let id = LastEventId::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(&*id, "996");
+    }
+}
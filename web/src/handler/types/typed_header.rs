@@ -0,0 +1,1042 @@
+//! type extractor and responder for typed header.
+
+use core::{fmt, ops::Deref};
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::{Error, HeaderNotFound, InvalidHeaderValue},
+    handler::{FromRequest, Responder},
+    http::{
+        WebResponse,
+        header::{self, HeaderName, HeaderValue},
+    },
+};
+
+/// trait for header type that can be decoded from and encoded to [HeaderValue].
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, typed_header::{Header, TypedHeader}},
+/// #   http::header::{HeaderName, HeaderValue},
+/// #   App, WebContext
+/// # };
+/// // a custom header carrying an api version number.
+/// struct ApiVersion(u32);
+///
+/// impl Header for ApiVersion {
+///     const NAME: HeaderName = HeaderName::from_static("x-api-version");
+///
+///     fn decode<'a, I>(mut values: I) -> Option<Self>
+///     where
+///         I: Iterator<Item = &'a HeaderValue>,
+///     {
+///         values.next()?.to_str().ok()?.parse().ok().map(ApiVersion)
+///     }
+///
+///     fn encode(&self) -> HeaderValue {
+///         HeaderValue::from(self.0)
+///     }
+/// }
+///
+/// // on missing or malformed header a 400 bad request response would be returned.
+/// async fn handler(TypedHeader(version): TypedHeader<ApiVersion>) -> (TypedHeader<ApiVersion>, &'static str) {
+///     (TypedHeader(version), "versioned")
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+pub trait Header: Sized {
+    /// name of header.
+    const NAME: HeaderName;
+
+    /// decode header from all values associated with [Header::NAME]. iterator always yields at least one value.
+    ///
+    /// return None when values are malformed.
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>;
+
+    /// encode header to value.
+    fn encode(&self) -> HeaderValue;
+}
+
+/// typed header extractor and responder.
+///
+/// on extracting failure [HeaderNotFound] or [InvalidHeaderValue] error would be returned which would
+/// generate a "400 BadRequest" http response. use `Option<TypedHeader<T>>` when the header is optional.
+///
+/// as responder the header is inserted to response and replaces existing values of the same name.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, typed_header::{CacheControl, ContentType, TypedHeader}},
+/// #   App, WebContext
+/// # };
+/// async fn handler(TypedHeader(ty): TypedHeader<ContentType>) -> (TypedHeader<CacheControl>, String) {
+///     let cache = CacheControl::new().no_store();
+///     (TypedHeader(cache), ty.mime_type().to_owned())
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct TypedHeader<T>(pub T);
+
+impl<T> fmt::Debug for TypedHeader<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TypedHeader({:?})", self.0)
+    }
+}
+
+impl<T> Deref for TypedHeader<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for TypedHeader<T>
+where
+    T: Header,
+{
+    type Type<'b> = TypedHeader<T>;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let mut values = ctx.req().headers().get_all(T::NAME).iter().peekable();
+        if values.peek().is_none() {
+            return Err(Error::from_service(HeaderNotFound(T::NAME)));
+        }
+        T::decode(values)
+            .map(TypedHeader)
+            .ok_or_else(|| Error::from_service(InvalidHeaderValue(T::NAME)))
+    }
+}
+
+impl<'r, C, B, T> Responder<WebContext<'r, C, B>> for TypedHeader<T>
+where
+    T: Header,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let res = ctx.into_response(ResponseBody::empty());
+        Responder::<WebContext<'r, C, B>>::map(self, res)
+    }
+
+    fn map(self, mut res: Self::Response) -> Result<Self::Response, Self::Error> {
+        res.headers_mut().insert(T::NAME, self.0.encode());
+        Ok(res)
+    }
+}
+
+// split comma separated list of all values. empty elements are ignored and comma inside quoted string
+// is not treated as separator.
+fn split_list<'a, I>(values: I) -> Option<Vec<&'a str>>
+where
+    I: Iterator<Item = &'a HeaderValue>,
+{
+    let mut list = Vec::new();
+    for value in values {
+        let value = value.to_str().ok()?;
+        let mut quoted = false;
+        let mut start = 0;
+        for (i, c) in value.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ',' if !quoted => {
+                    list.push(&value[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if quoted {
+            return None;
+        }
+        list.push(&value[start..]);
+    }
+    list.retain(|item| !item.trim().is_empty());
+    Some(list.into_iter().map(str::trim).collect())
+}
+
+fn single<'a, I>(mut values: I) -> Option<&'a str>
+where
+    I: Iterator<Item = &'a HeaderValue>,
+{
+    let value = values.next()?;
+    // single value header must not be repeated.
+    match values.next() {
+        Some(_) => None,
+        None => value.to_str().ok().map(str::trim),
+    }
+}
+
+// constructors of built in headers validate their input so encoding never fails.
+fn encode_str(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("typed header must be encoded to valid HeaderValue")
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+// visible characters, space and horizontal tab allowed in header value.
+fn is_field_value(s: &str) -> bool {
+    s.bytes().all(|b| b == b'\t' || (b' '..=b'~').contains(&b) || b >= 0x80)
+}
+
+// token68 form of credentials. e.g. bearer token.
+fn is_token68(s: &str) -> bool {
+    let s = s.trim_end_matches('=');
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"-._~+/".contains(&b))
+}
+
+/// `Content-Type` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentType(String);
+
+impl ContentType {
+    /// construct from given media type. e.g. `text/plain; charset=utf-8`
+    ///
+    /// return None when it's not in the form of `type/subtype` with optional `name=value` parameters.
+    pub fn new(media_type: impl Into<String>) -> Option<Self> {
+        let media_type = media_type.into();
+        Self::parse(&media_type).then_some(Self(media_type))
+    }
+
+    /// `application/json` content type.
+    pub fn json() -> Self {
+        Self(String::from("application/json"))
+    }
+
+    /// `text/plain; charset=utf-8` content type.
+    pub fn text() -> Self {
+        Self(String::from("text/plain; charset=utf-8"))
+    }
+
+    /// `text/html; charset=utf-8` content type.
+    pub fn html() -> Self {
+        Self(String::from("text/html; charset=utf-8"))
+    }
+
+    /// `application/x-www-form-urlencoded` content type.
+    pub fn form_url_encoded() -> Self {
+        Self(String::from("application/x-www-form-urlencoded"))
+    }
+
+    /// `application/octet-stream` content type.
+    pub fn octet_stream() -> Self {
+        Self(String::from("application/octet-stream"))
+    }
+
+    /// media type without parameters. e.g. `text/plain`
+    pub fn mime_type(&self) -> &str {
+        self.0.split(';').next().unwrap_or_default().trim()
+    }
+
+    /// value of given parameter. e.g. `charset`
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.0.split(';').skip(1).find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().trim_matches('"'))
+        })
+    }
+
+    /// full value of header.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn parse(value: &str) -> bool {
+        let mut parts = value.split(';');
+        let mime = parts.next().unwrap_or_default().trim();
+        matches!(mime.split_once('/'), Some((ty, sub)) if is_token(ty) && is_token(sub))
+            && is_field_value(value)
+            && parts.map(str::trim).filter(|p| !p.is_empty()).all(|param| {
+                matches!(param.split_once('='), Some((name, value)) if is_token(name.trim()) && {
+                    let value = value.trim();
+                    is_token(value) || (value.len() > 1 && value.starts_with('"') && value.ends_with('"'))
+                })
+            })
+    }
+}
+
+impl Header for ContentType {
+    const NAME: HeaderName = header::CONTENT_TYPE;
+
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let value = single(values)?;
+        let (mime, params) = value.split_once(';').unwrap_or((value, ""));
+        let mut value = mime.trim().to_ascii_lowercase();
+        if !params.is_empty() {
+            value.push(';');
+            value.push_str(params);
+        }
+        Self::new(value)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        encode_str(&self.0)
+    }
+}
+
+/// media range and it's quality value of [Accept] header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QualityItem {
+    value: String,
+    quality: u16,
+}
+
+impl QualityItem {
+    /// media range of item. e.g. `text/*`
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// quality value of item in range of 0 to 1000.
+    pub fn quality(&self) -> u16 {
+        self.quality
+    }
+
+    fn parse(item: &str) -> Option<Self> {
+        let mut params = item.split(';');
+        let value = params.next()?.trim().to_ascii_lowercase();
+        if !ContentType::parse(&value) {
+            return None;
+        }
+        let mut quality = 1000;
+        for param in params {
+            let Some((key, q)) = param.split_once('=') else {
+                continue;
+            };
+            if key.trim().eq_ignore_ascii_case("q") {
+                quality = parse_quality(q.trim())?;
+            }
+        }
+        Some(Self { value, quality })
+    }
+
+    fn matches(&self, mime: &str) -> bool {
+        let Some((ty, sub)) = mime.split_once('/') else {
+            return false;
+        };
+        match self.value.split_once('/') {
+            Some(("*", "*")) => true,
+            Some((t, "*")) => t.eq_ignore_ascii_case(ty),
+            Some((t, s)) => t.eq_ignore_ascii_case(ty) && s.eq_ignore_ascii_case(sub),
+            None => false,
+        }
+    }
+
+    // more specific media range takes priority when quality is equal.
+    fn specificity(&self) -> u8 {
+        match self.value.split_once('/') {
+            Some(("*", _)) => 0,
+            Some((_, "*")) => 1,
+            _ => 2,
+        }
+    }
+}
+
+// parse quality value with at most 3 digits after decimal point.
+fn parse_quality(q: &str) -> Option<u16> {
+    let (int, frac) = q.split_once('.').unwrap_or((q, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let frac = frac.bytes().chain(core::iter::repeat(b'0')).take(3);
+    let frac = frac.fold(0, |acc, b| acc * 10 + u16::from(b - b'0'));
+    match int {
+        "0" => Some(frac),
+        "1" if frac == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+/// `Accept` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accept(Vec<QualityItem>);
+
+impl Accept {
+    /// construct from given media ranges with default quality.
+    pub fn new<I, S>(ranges: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let items = ranges
+            .into_iter()
+            .map(|range| QualityItem::parse(range.as_ref()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::sorted(items))
+    }
+
+    /// media ranges ordered by quality value from high to low.
+    pub fn items(&self) -> &[QualityItem] {
+        &self.0
+    }
+
+    /// quality value of given media type. return 0 when it's not acceptable.
+    pub fn quality(&self, mime: &str) -> u16 {
+        self.0
+            .iter()
+            .filter(|item| item.matches(mime))
+            .max_by_key(|item| item.specificity())
+            .map(|item| item.quality)
+            .unwrap_or(0)
+    }
+
+    /// check if given media type is acceptable.
+    pub fn accepts(&self, mime: &str) -> bool {
+        self.quality(mime) > 0
+    }
+
+    /// pick the most preferred media type from given candidates. candidate order breaks tie of quality value.
+    pub fn preferred<'a>(&self, candidates: &[&'a str]) -> Option<&'a str> {
+        let mut res: Option<(&str, u16)> = None;
+        for mime in candidates {
+            let q = self.quality(mime);
+            if q > 0 && res.is_none_or(|(_, best)| q > best) {
+                res = Some((mime, q));
+            }
+        }
+        res.map(|(mime, _)| mime)
+    }
+
+    fn sorted(mut items: Vec<QualityItem>) -> Self {
+        // stable sort keeps the order of items with the same quality and specificity.
+        items.sort_by(|a, b| {
+            b.quality
+                .cmp(&a.quality)
+                .then_with(|| b.specificity().cmp(&a.specificity()))
+        });
+        Self(items)
+    }
+}
+
+impl Header for Accept {
+    const NAME: HeaderName = header::ACCEPT;
+
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let items = split_list(values)?
+            .into_iter()
+            .map(QualityItem::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::sorted(items))
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let value = self
+            .0
+            .iter()
+            .map(|item| match item.quality {
+                1000 => item.value.clone(),
+                q => format!("{};q={}", item.value, format!("0.{q:03}").trim_end_matches('0')),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        encode_str(&value)
+    }
+}
+
+/// entity tag of [IfMatch] header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityTag {
+    weak: bool,
+    tag: String,
+}
+
+impl EntityTag {
+    /// construct a strong entity tag. return None when tag contains invalid character.
+    pub fn strong(tag: impl Into<String>) -> Option<Self> {
+        Self::new(false, tag.into())
+    }
+
+    /// construct a weak entity tag. return None when tag contains invalid character.
+    pub fn weak(tag: impl Into<String>) -> Option<Self> {
+        Self::new(true, tag.into())
+    }
+
+    /// opaque tag without quotes.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// check if entity tag is weak.
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// strong comparison. both tags must be strong and equal.
+    pub fn strong_eq(&self, other: &Self) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// weak comparison. tags are equal regardless of their weakness.
+    pub fn weak_eq(&self, other: &Self) -> bool {
+        self.tag == other.tag
+    }
+
+    fn new(weak: bool, tag: String) -> Option<Self> {
+        tag.bytes()
+            .all(|b| b == 0x21 || (0x23..=0x7e).contains(&b) || b >= 0x80)
+            .then_some(Self { weak, tag })
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let (weak, value) = match value.strip_prefix("W/") {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let tag = value.strip_prefix('"')?.strip_suffix('"')?;
+        Self::new(weak, tag.to_owned())
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+/// `If-Match` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `*` matching any current representation.
+    Any,
+    /// list of entity tags.
+    Tags(Vec<EntityTag>),
+}
+
+impl IfMatch {
+    /// check if given entity tag of current representation matches. strong comparison is used.
+    pub fn matches(&self, tag: &EntityTag) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Tags(tags) => tags.iter().any(|t| t.strong_eq(tag)),
+        }
+    }
+}
+
+impl Header for IfMatch {
+    const NAME: HeaderName = header::IF_MATCH;
+
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let list = split_list(values)?;
+        if list == ["*"] {
+            return Some(Self::Any);
+        }
+        list.into_iter()
+            .map(EntityTag::parse)
+            .collect::<Option<Vec<_>>>()
+            .filter(|tags| !tags.is_empty())
+            .map(Self::Tags)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        match self {
+            Self::Any => HeaderValue::from_static("*"),
+            Self::Tags(tags) => {
+                let value = tags.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                encode_str(&value)
+            }
+        }
+    }
+}
+
+/// `Cache-Control` header.
+///
+/// unknown directives are ignored when decoding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    no_cache: bool,
+    no_store: bool,
+    no_transform: bool,
+    must_revalidate: bool,
+    public: bool,
+    private: bool,
+    immutable: bool,
+    only_if_cached: bool,
+    max_age: Option<u64>,
+    s_max_age: Option<u64>,
+    max_stale: Option<u64>,
+    min_fresh: Option<u64>,
+}
+
+macro_rules! cache_flag {
+    ($($field: ident, $is: ident, $directive: literal);*) => {
+        $(
+            #[doc = concat!("set `", $directive, "` directive.")]
+            pub fn $field(mut self) -> Self {
+                self.$field = true;
+                self
+            }
+
+            #[doc = concat!("check if `", $directive, "` directive is set.")]
+            pub fn $is(&self) -> bool {
+                self.$field
+            }
+        )*
+    };
+}
+
+macro_rules! cache_value {
+    ($($field: ident, $get: ident, $directive: literal);*) => {
+        $(
+            #[doc = concat!("set `", $directive, "` directive in seconds.")]
+            pub fn $field(mut self, secs: u64) -> Self {
+                self.$field = Some(secs);
+                self
+            }
+
+            #[doc = concat!("value of `", $directive, "` directive in seconds.")]
+            pub fn $get(&self) -> Option<u64> {
+                self.$field
+            }
+        )*
+    };
+}
+
+impl CacheControl {
+    /// construct an empty header.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    cache_flag!(
+        no_cache, is_no_cache, "no-cache";
+        no_store, is_no_store, "no-store";
+        no_transform, is_no_transform, "no-transform";
+        must_revalidate, is_must_revalidate, "must-revalidate";
+        public, is_public, "public";
+        private, is_private, "private";
+        immutable, is_immutable, "immutable";
+        only_if_cached, is_only_if_cached, "only-if-cached"
+    );
+
+    cache_value!(
+        max_age, get_max_age, "max-age";
+        s_max_age, get_s_max_age, "s-maxage";
+        max_stale, get_max_stale, "max-stale";
+        min_fresh, get_min_fresh, "min-fresh"
+    );
+}
+
+impl Header for CacheControl {
+    const NAME: HeaderName = header::CACHE_CONTROL;
+
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let mut res = Self::default();
+        for directive in split_list(values)? {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+            let secs = || value.and_then(|v| v.parse().ok());
+            match name.to_ascii_lowercase().as_str() {
+                "no-cache" => res.no_cache = true,
+                "no-store" => res.no_store = true,
+                "no-transform" => res.no_transform = true,
+                "must-revalidate" => res.must_revalidate = true,
+                "public" => res.public = true,
+                "private" => res.private = true,
+                "immutable" => res.immutable = true,
+                "only-if-cached" => res.only_if_cached = true,
+                "max-age" => res.max_age = Some(secs()?),
+                "s-maxage" => res.s_max_age = Some(secs()?),
+                // max-stale without value means any staleness is accepted and is decoded as u64::MAX.
+                "max-stale" => res.max_stale = Some(value.map_or(Some(u64::MAX), |v| v.parse().ok())?),
+                "min-fresh" => res.min_fresh = Some(secs()?),
+                _ => {}
+            }
+        }
+        Some(res)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let mut directives = Vec::new();
+        let flags = [
+            (self.no_cache, "no-cache"),
+            (self.no_store, "no-store"),
+            (self.no_transform, "no-transform"),
+            (self.must_revalidate, "must-revalidate"),
+            (self.public, "public"),
+            (self.private, "private"),
+            (self.immutable, "immutable"),
+            (self.only_if_cached, "only-if-cached"),
+        ];
+        directives.extend(flags.into_iter().filter(|(set, _)| *set).map(|(_, d)| d.to_owned()));
+        let values = [
+            (self.max_age, "max-age"),
+            (self.s_max_age, "s-maxage"),
+            (self.max_stale, "max-stale"),
+            (self.min_fresh, "min-fresh"),
+        ];
+        // max-stale of u64::MAX is encoded without value which means any staleness is accepted.
+        directives.extend(values.into_iter().filter_map(|(v, d)| match v? {
+            u64::MAX if d == "max-stale" => Some(d.to_owned()),
+            v => Some(format!("{d}={v}")),
+        }));
+        encode_str(&directives.join(", "))
+    }
+}
+
+/// `Authorization` header with scheme and credentials.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Authorization {
+    scheme: String,
+    credentials: String,
+}
+
+impl fmt::Debug for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authorization")
+            .field("scheme", &self.scheme)
+            .field("credentials", &"******")
+            .finish()
+    }
+}
+
+impl Authorization {
+    /// construct from given scheme and credentials. return None when scheme is not a valid token or
+    /// credentials contain character not allowed in header value.
+    pub fn new(scheme: impl Into<String>, credentials: impl Into<String>) -> Option<Self> {
+        let (scheme, credentials) = (scheme.into(), credentials.into());
+        (is_token(&scheme) && is_field_value(&credentials)).then_some(Self { scheme, credentials })
+    }
+
+    /// construct with `Bearer` scheme and given token. return None when token is not in token68 form.
+    pub fn bearer(token: impl Into<String>) -> Option<Self> {
+        let credentials = token.into();
+        is_token68(&credentials).then(|| Self {
+            scheme: String::from("Bearer"),
+            credentials,
+        })
+    }
+
+    /// authentication scheme. e.g. `Basic`
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// check if scheme equals given one. scheme is case insensitive.
+    pub fn is_scheme(&self, scheme: &str) -> bool {
+        self.scheme.eq_ignore_ascii_case(scheme)
+    }
+
+    /// credentials following scheme.
+    pub fn credentials(&self) -> &str {
+        &self.credentials
+    }
+}
+
+impl Header for Authorization {
+    const NAME: HeaderName = header::AUTHORIZATION;
+
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let value = single(values)?;
+        let (scheme, credentials) = value.split_once(' ').unwrap_or((value, ""));
+        Self::new(scheme, credentials.trim())
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let mut value = if self.credentials.is_empty() {
+            encode_str(&self.scheme)
+        } else {
+            encode_str(&format!("{} {}", self.scheme, self.credentials))
+        };
+        value.set_sensitive(true);
+        value
+    }
+}
+
+/// range of bytes in [Range] header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// inclusive range from first to last byte position. e.g. `0-499`
+    FromTo(u64, u64),
+    /// range from byte position to the end. e.g. `500-`
+    From(u64),
+    /// last n bytes. e.g. `-500`
+    Last(u64),
+}
+
+impl ByteRange {
+    /// resolve to inclusive start and end position with given length of representation.
+    ///
+    /// return None when range is not satisfiable.
+    pub fn to_satisfiable(&self, len: u64) -> Option<(u64, u64)> {
+        match *self {
+            Self::FromTo(start, end) if start < len => Some((start, end.min(len - 1))),
+            Self::From(start) if start < len => Some((start, len - 1)),
+            Self::Last(n) if n > 0 && len > 0 => Some((len.saturating_sub(n), len - 1)),
+            _ => None,
+        }
+    }
+
+    fn parse(range: &str) -> Option<Self> {
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+        match (start.is_empty(), end.is_empty()) {
+            (true, false) => end.parse().ok().map(Self::Last),
+            (false, true) => start.parse().ok().map(Self::From),
+            (false, false) => {
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                (start <= end).then_some(Self::FromTo(start, end))
+            }
+            (true, true) => None,
+        }
+    }
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::FromTo(start, end) => write!(f, "{start}-{end}"),
+            Self::From(start) => write!(f, "{start}-"),
+            Self::Last(n) => write!(f, "-{n}"),
+        }
+    }
+}
+
+/// `Range` header. only `bytes` unit is supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range(Vec<ByteRange>);
+
+impl Range {
+    /// construct with single inclusive byte range. return None when start is greater than end.
+    pub fn bytes(start: u64, end: u64) -> Option<Self> {
+        (start <= end).then(|| Self(vec![ByteRange::FromTo(start, end)]))
+    }
+
+    /// construct with given byte ranges. return None when ranges is empty or any [ByteRange::FromTo] has
+    /// start position greater than end position.
+    pub fn from_ranges(ranges: Vec<ByteRange>) -> Option<Self> {
+        let valid = ranges.iter().all(|r| match *r {
+            ByteRange::FromTo(start, end) => start <= end,
+            _ => true,
+        });
+        (valid && !ranges.is_empty()).then_some(Self(ranges))
+    }
+
+    /// byte ranges of header.
+    pub fn ranges(&self) -> &[ByteRange] {
+        &self.0
+    }
+}
+
+impl Header for Range {
+    const NAME: HeaderName = header::RANGE;
+
+    fn decode<'a, I>(values: I) -> Option<Self>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let value = single(values)?;
+        let (unit, ranges) = value.split_once('=')?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+        let ranges = ranges
+            .split(',')
+            .filter(|r| !r.trim().is_empty())
+            .map(ByteRange::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Self::from_ranges(ranges)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let ranges = self.0.iter().map(ToString::to_string).collect::<Vec<_>>().join(",");
+        encode_str(&format!("bytes={ranges}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{http::StatusCode, service::Service};
+
+    use super::*;
+
+    fn decode<T: Header>(values: &[&'static str]) -> Option<T> {
+        let values = values.iter().map(|v| HeaderValue::from_static(v)).collect::<Vec<_>>();
+        T::decode(values.iter())
+    }
+
+    #[test]
+    fn extract() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        let err = TypedHeader::<ContentType>::from_request(&ctx)
+            .now_or_panic()
+            .unwrap_err();
+        let res = err.call(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        ctx.req_mut()
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("nope"));
+        let err = TypedHeader::<ContentType>::from_request(&ctx)
+            .now_or_panic()
+            .unwrap_err();
+        assert!(err.upcast().downcast_ref::<InvalidHeaderValue>().is_some());
+
+        ctx.req_mut().headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("Application/JSON; charset=utf-8"),
+        );
+        let TypedHeader(ty) = TypedHeader::<ContentType>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(ty.mime_type(), "application/json");
+        assert_eq!(ty.param("CHARSET"), Some("utf-8"));
+
+        assert!(
+            Option::<TypedHeader<Range>>::from_request(&ctx)
+                .now_or_panic()
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn respond() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let res = TypedHeader(CacheControl::new().no_cache().max_age(60))
+            .respond(ctx)
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache, max-age=60"
+        );
+    }
+
+    #[test]
+    fn accept() {
+        let accept = decode::<Accept>(&["text/*;q=0.5, application/json", "text/html;q=0.8"]).unwrap();
+        assert_eq!(accept.items()[0].value(), "application/json");
+        assert_eq!(accept.quality("text/html"), 800);
+        assert_eq!(accept.quality("text/plain"), 500);
+        assert!(!accept.accepts("image/png"));
+        assert_eq!(accept.preferred(&["text/plain", "text/html"]), Some("text/html"));
+        assert_eq!(accept.encode(), "application/json, text/html;q=0.8, text/*;q=0.5");
+        assert!(decode::<Accept>(&["text/html;q=2"]).is_none());
+    }
+
+    #[test]
+    fn if_match() {
+        assert_eq!(decode::<IfMatch>(&["*"]), Some(IfMatch::Any));
+        let if_match = decode::<IfMatch>(&["\"a,b\", W/\"c\""]).unwrap();
+        assert!(if_match.matches(&EntityTag::strong("a,b").unwrap()));
+        assert!(!if_match.matches(&EntityTag::weak("c").unwrap()));
+        assert_eq!(if_match.encode(), "\"a,b\", W/\"c\"");
+        assert!(decode::<IfMatch>(&["abc"]).is_none());
+    }
+
+    #[test]
+    fn cache_control() {
+        let cache = decode::<CacheControl>(&["no-store, max-age=\"10\"", "max-stale, x-ext=1"]).unwrap();
+        assert!(cache.is_no_store());
+        assert_eq!(cache.get_max_age(), Some(10));
+        assert_eq!(cache.get_max_stale(), Some(u64::MAX));
+        assert_eq!(cache.encode(), "no-store, max-age=10, max-stale");
+        assert!(decode::<CacheControl>(&["max-age=abc"]).is_none());
+
+        let cache = CacheControl::new()
+            .must_revalidate()
+            .immutable()
+            .max_stale(30)
+            .min_fresh(5);
+        assert!(cache.is_must_revalidate());
+        assert!(cache.is_immutable());
+        assert!(!cache.is_no_transform());
+        assert!(!cache.is_only_if_cached());
+        assert_eq!(cache.get_min_fresh(), Some(5));
+        assert_eq!(cache.encode(), "must-revalidate, immutable, max-stale=30, min-fresh=5");
+    }
+
+    #[test]
+    fn content_type() {
+        let ty = ContentType::new("text/plain; charset=utf-8; format=\"flowed\"").unwrap();
+        assert_eq!(ty.param("format"), Some("flowed"));
+        assert_eq!(ty.encode(), "text/plain; charset=utf-8; format=\"flowed\"");
+        assert!(ContentType::new("text/plain; charset").is_none());
+        assert!(ContentType::new("text/plain; charset=utf-8\r\nx-injected: 1").is_none());
+        assert!(ContentType::new("text/plain; charset=a b").is_none());
+    }
+
+    #[test]
+    fn authorization() {
+        let auth = decode::<Authorization>(&["Bearer abc.def"]).unwrap();
+        assert!(auth.is_scheme("bearer"));
+        assert_eq!(auth.credentials(), "abc.def");
+        assert!(auth.encode().is_sensitive());
+        assert!(decode::<Authorization>(&["Bearer a", "Bearer b"]).is_none());
+
+        assert_eq!(Authorization::bearer("abc.def=").unwrap().encode(), "Bearer abc.def=");
+        assert!(Authorization::bearer("abc def").is_none());
+        assert!(Authorization::bearer("").is_none());
+        assert!(Authorization::new("Basic", "abc\r\n").is_none());
+        assert!(Authorization::new("Basic", "").is_some());
+    }
+
+    #[test]
+    fn range() {
+        let range = decode::<Range>(&["bytes=0-99, 200-, -50"]).unwrap();
+        assert_eq!(
+            range.ranges(),
+            &[ByteRange::FromTo(0, 99), ByteRange::From(200), ByteRange::Last(50)]
+        );
+        assert_eq!(range.ranges()[0].to_satisfiable(50), Some((0, 49)));
+        assert_eq!(range.ranges()[1].to_satisfiable(100), None);
+        assert_eq!(range.ranges()[2].to_satisfiable(30), Some((0, 29)));
+        assert_eq!(range.encode(), "bytes=0-99,200-,-50");
+        assert!(decode::<Range>(&["items=0-1"]).is_none());
+        assert!(decode::<Range>(&["bytes=5-1"]).is_none());
+
+        assert!(Range::bytes(5, 1).is_none());
+        assert!(Range::from_ranges(vec![]).is_none());
+        assert!(Range::from_ranges(vec![ByteRange::From(0), ByteRange::FromTo(5, 1)]).is_none());
+        let range = Range::from_ranges(vec![ByteRange::FromTo(1, 1), ByteRange::Last(5)]).unwrap();
+        assert_eq!(range.encode(), "bytes=1-1,-5");
+    }
+}
@@ -0,0 +1,450 @@
+//! type extractor validating value produced by other extractor.
+
+use core::{
+    fmt,
+    ops::{Bound, Deref, DerefMut, RangeBounds},
+};
+
+use std::error;
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::{Error, error_from_service},
+    handler::FromRequest,
+    http::{StatusCode, WebResponse, const_header_value::JSON, header::CONTENT_TYPE},
+    service::Service,
+};
+
+/// trait for type that can validate itself after being deserialized.
+///
+/// [ValidationErrors] can be used as error type for collecting field level errors and it would be rendered
+/// as "422 Unprocessable Entity" http response with json body. custom error type can be used for different
+/// rendering. e.g. a type implemented with `xitca_web::codegen::error_impl` macro.
+///
+/// extractors like `Json`, `Form`, `Query` and `Params` forward the trait to their inner type so they can
+/// be wrapped in [Valid].
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::handler::validate::{Validate, ValidationErrors};
+/// struct User {
+///     name: String,
+///     age: u8,
+///     address: Address,
+/// }
+///
+/// struct Address {
+///     city: String,
+/// }
+///
+/// impl Validate for User {
+///     type Error = ValidationErrors;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         ValidationErrors::new()
+///             .length("name", &self.name, 1..=32)
+///             .range("age", &self.age, 18..)
+///             // errors of nested type are prefixed with "address." path.
+///             .nested("address", self.address.validate())
+///             .finish()
+///     }
+/// }
+///
+/// impl Validate for Address {
+///     type Error = ValidationErrors;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         ValidationErrors::new()
+///             .check("city", !self.city.trim().is_empty(), "must not be blank")
+///             .finish()
+///     }
+/// }
+///
+/// let user = User { name: String::new(), age: 16, address: Address { city: String::from(" ") } };
+/// let errors = user.validate().unwrap_err();
+/// let paths = errors.errors().iter().map(|e| e.path()).collect::<Vec<_>>();
+/// assert_eq!(paths, ["name", "age", "address.city"]);
+/// ```
+pub trait Validate {
+    /// error type produced by failed validation.
+    type Error: Into<Error>;
+
+    /// validate value.
+    fn validate(&self) -> Result<(), Self::Error>;
+}
+
+impl<T> Validate for Option<T>
+where
+    T: Validate,
+{
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        match self {
+            Some(t) => t.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> Validate for Box<T>
+where
+    T: Validate + ?Sized,
+{
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        (**self).validate()
+    }
+}
+
+/// error of a single field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    path: String,
+    message: String,
+}
+
+impl FieldError {
+    /// path of field. nested field is separated by `.` and item of sequence is in form of `field[index]`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// message describing why the field is invalid.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// collection of field errors produced by failed validation.
+///
+/// rendered as "422 Unprocessable Entity" http response with json body in the form of
+/// `[{ "path": "name", "message": "..." }]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    /// construct an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add error of given field path.
+    pub fn add(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// add error with given message when condition is false.
+    pub fn check(mut self, path: &str, cond: bool, message: impl Into<String>) -> Self {
+        if !cond {
+            self.add(path, message);
+        }
+        self
+    }
+
+    /// add error when character count of given string is out of range.
+    pub fn length<R>(mut self, path: &str, value: &str, range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        if !range.contains(&value.chars().count()) {
+            self.add(path, format!("length {}", describe_range(&range)));
+        }
+        self
+    }
+
+    /// add error when given value is out of range.
+    pub fn range<T, R>(mut self, path: &str, value: &T, range: R) -> Self
+    where
+        T: PartialOrd + fmt::Display,
+        R: RangeBounds<T>,
+    {
+        if !range.contains(value) {
+            self.add(path, format!("value {}", describe_range(&range)));
+        }
+        self
+    }
+
+    /// add errors of nested value with given path as prefix.
+    pub fn nested(mut self, path: &str, res: Result<(), Self>) -> Self {
+        if let Err(errors) = res {
+            self.0.extend(errors.0.into_iter().map(|mut e| {
+                e.path = format!("{path}.{}", e.path);
+                e
+            }));
+        }
+        self
+    }
+
+    /// add errors of every item of sequence with `path[index]` as prefix.
+    pub fn nested_iter<I>(mut self, path: &str, iter: I) -> Self
+    where
+        I: IntoIterator<Item = Result<(), Self>>,
+    {
+        for (idx, res) in iter.into_iter().enumerate() {
+            self = self.nested(&format!("{path}[{idx}]"), res);
+        }
+        self
+    }
+
+    /// field errors in the order they are added.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// check if there is no error.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// finish validation. return Ok when there is no error.
+    pub fn finish(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+fn describe_range<T, R>(range: &R) -> String
+where
+    T: fmt::Display,
+    R: RangeBounds<T>,
+{
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Included(s), Bound::Included(e)) => format!("must be between {s} and {e}"),
+        (Bound::Included(s), Bound::Excluded(e)) => format!("must be at least {s} and less than {e}"),
+        (Bound::Included(s), Bound::Unbounded) => format!("must be at least {s}"),
+        (Bound::Excluded(s), Bound::Unbounded) => format!("must be greater than {s}"),
+        (Bound::Excluded(s), Bound::Included(e)) => format!("must be greater than {s} and at most {e}"),
+        (Bound::Excluded(s), Bound::Excluded(e)) => format!("must be greater than {s} and less than {e}"),
+        (Bound::Unbounded, Bound::Included(e)) => format!("must be at most {e}"),
+        (Bound::Unbounded, Bound::Excluded(e)) => format!("must be less than {e}"),
+        (Bound::Unbounded, Bound::Unbounded) => String::from("is invalid"),
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("validation failed")?;
+        for (i, e) in self.0.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { "; " };
+            write!(f, "{sep}{} {}", e.path, e.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ValidationErrors {}
+
+error_from_service!(ValidationErrors);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for ValidationErrors {
+    type Response = WebResponse;
+    type Error = core::convert::Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let errors = self
+            .0
+            .iter()
+            .map(|e| serde_json::json!({ "path": e.path, "message": e.message }))
+            .collect::<Vec<_>>();
+        let body = serde_json::to_vec(&errors).unwrap_or_default();
+        let mut res = ctx.into_response(ResponseBody::from(body));
+        *res.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+        res.headers_mut().insert(CONTENT_TYPE, JSON);
+        Ok(res)
+    }
+}
+
+/// extractor wrapping other extractor and validating it's value with [Validate] trait.
+///
+/// on failure error type of [Validate] would be returned.
+///
+/// # Examples
+/// ```rust
+/// # use serde::Deserialize;
+/// # use xitca_web::{
+/// #   handler::{handler_service, json::Json, validate::{Valid, Validate, ValidationErrors}},
+/// #   App, WebContext
+/// # };
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// impl Validate for User {
+///     type Error = ValidationErrors;
+///
+///     fn validate(&self) -> Result<(), Self::Error> {
+///         ValidationErrors::new().length("name", &self.name, 1..=32).finish()
+///     }
+/// }
+///
+/// // request with invalid name would be responded with 422 status code.
+/// async fn handler(Valid(Json(user)): Valid<Json<User>>) -> String {
+///     user.name
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+/// ```
+pub struct Valid<T>(pub T);
+
+impl<T> fmt::Debug for Valid<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Valid").field("value", &self.0).finish()
+    }
+}
+
+impl<T> Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Valid<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for Valid<T>
+where
+    T: for<'b> FromRequest<'b, WebContext<'r, C, B>, Error = Error> + Validate,
+{
+    type Type<'b> = Valid<T>;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let t = T::from_request(ctx).await?;
+        t.validate().map_err(Into::into)?;
+        Ok(Valid(t))
+    }
+}
+
+#[cfg(any(feature = "json", feature = "urlencoded", feature = "params"))]
+macro_rules! forward_validate {
+    ($([$($generic: tt)*] $ty: ty),* $(,)?) => {
+        $(
+            impl<T, $($generic)*> Validate for $ty
+            where
+                T: Validate,
+            {
+                type Error = T::Error;
+
+                #[inline]
+                fn validate(&self) -> Result<(), Self::Error> {
+                    self.0.validate()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "json")]
+forward_validate!([const LIMIT: usize] crate::handler::json::Json<T, LIMIT>);
+
+#[cfg(feature = "urlencoded")]
+forward_validate!(
+    [const LIMIT: usize] crate::handler::form::Form<T, LIMIT>,
+    [] crate::handler::query::Query<T>,
+);
+
+#[cfg(feature = "params")]
+forward_validate!([] crate::handler::params::Params<T>);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "json")]
+    use {
+        crate::{handler::json::Json, http::header::CONTENT_TYPE, test::collect_string_body},
+        xitca_unsafe_collection::futures::NowOrPanic,
+    };
+
+    #[cfg(feature = "json")]
+    #[derive(serde::Deserialize)]
+    struct User {
+        name: String,
+        tags: Vec<Tag>,
+    }
+
+    #[cfg(feature = "json")]
+    #[derive(serde::Deserialize)]
+    struct Tag {
+        label: String,
+    }
+
+    #[cfg(feature = "json")]
+    impl Validate for User {
+        type Error = ValidationErrors;
+
+        fn validate(&self) -> Result<(), Self::Error> {
+            ValidationErrors::new()
+                .length("name", &self.name, 2..=4)
+                .nested_iter("tags", self.tags.iter().map(Validate::validate))
+                .finish()
+        }
+    }
+
+    #[cfg(feature = "json")]
+    impl Validate for Tag {
+        type Error = ValidationErrors;
+
+        fn validate(&self) -> Result<(), Self::Error> {
+            ValidationErrors::new()
+                .check("label", !self.label.is_empty(), "must not be empty")
+                .finish()
+        }
+    }
+
+    #[test]
+    fn messages() {
+        let errors = ValidationErrors::new()
+            .range("a", &5, 1..3)
+            .range("b", &0.5, ..=0.1)
+            .length("c", "abc", 4..)
+            .range("d", &2, 1..3)
+            .finish()
+            .unwrap_err();
+        assert_eq!(
+            errors.to_string(),
+            "validation failed: a value must be at least 1 and less than 3; b value must be at most 0.1; c length must be at least 4"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn extract() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, JSON);
+        *ctx.body_borrow_mut() = r#"{"name":"a","tags":[{"label":"x"},{"label":""}]}"#.as_bytes().into();
+
+        let err = Valid::<Json<User>>::from_request(&ctx).now_or_panic().err().unwrap();
+        let res = err.call(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), JSON);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(
+            body,
+            r#"[{"message":"length must be between 2 and 4","path":"name"},{"message":"must not be empty","path":"tags[1].label"}]"#
+        );
+
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, JSON);
+        *ctx.body_borrow_mut() = r#"{"name":"abc","tags":[]}"#.as_bytes().into();
+        let Valid(Json(user)) = Valid::<Json<User>>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(user.name, "abc");
+    }
+}
@@ -265,6 +265,8 @@ pub mod body;
 pub mod error;
 pub mod handler;
 pub mod middleware;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod service;
 pub mod test;
 
@@ -310,13 +312,71 @@ pub mod codegen {
 
     pub use xitca_codegen::error_impl;
 
+    /// Derive macro for [Schema](crate::openapi::Schema) trait describing type with json schema.
+    ///
+    /// named struct is registered as reusable schema and it's doc comments are used as description.
+    /// `#[serde(rename = "..")]`, `#[serde(skip)]`, `#[serde(default)]` and `#[serde(rename_all = "..")]`
+    /// attributes are honored. unit only enum is described as string enum.
+    ///
+    /// # Example:
+    /// ```rust
+    /// # use xitca_web::codegen::Schema;
+    /// /// a registered user.
+    /// #[derive(Schema)]
+    /// struct User {
+    ///     /// name of user.
+    ///     name: String,
+    ///     #[serde(default)]
+    ///     age: u8,
+    ///     role: Role,
+    /// }
+    ///
+    /// #[derive(Schema)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Role {
+    ///     Admin,
+    ///     Guest,
+    /// }
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub use xitca_codegen::Schema;
+
     #[doc(hidden)]
     /// a hidden module for macro to access public types that are not framework user facing.
     pub mod __private {
         pub use xitca_http::util::service::router::{IntoObject, RouteObject, RouterMapErr, TypedRoute};
+
+        pub use crate::__typed_route_api as typed_route_api;
+
+        #[cfg(feature = "openapi")]
+        pub use serde_json;
     }
 }
 
+// describe typed route when openapi feature is enabled. used by #[route] macro with openapi attribute.
+#[cfg(feature = "openapi")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __typed_route_api {
+    ($ident: ident, $path: expr, $route: block) => {
+        impl $crate::openapi::DescribeTypedRoute for $ident {
+            fn describe_typed_route(api: &mut $crate::openapi::OpenApi) {
+                let route = $route;
+                $crate::openapi::DescribeRoute::describe_route(&route, $path, api);
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "openapi"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __typed_route_api {
+    ($($tt: tt)*) => {
+        compile_error!("#[route] macro with openapi attribute requires xitca-web's openapi feature");
+    };
+}
+
 pub mod http {
     //! http types
 
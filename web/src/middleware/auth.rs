@@ -0,0 +1,200 @@
+//! http authentication middleware.
+
+use core::{future::Future, marker::PhantomData};
+
+use crate::service::Service;
+
+/// builder for http authentication middleware.
+///
+/// credential type `T` is extracted from request and passed to async validator function. on success the
+/// identity produced by validator is inserted into request's extensions where it can be extracted with
+/// [ExtensionRef] or [ExtensionOwn]. on failure the error is returned and request does not reach
+/// enclosed service.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{auth::{AuthError, BearerToken, Scheme}, extension::ExtensionOwn, handler_service},
+/// #   middleware::auth::Authenticate,
+/// #   App, WebContext
+/// # };
+/// #[derive(Clone)]
+/// struct User(String);
+///
+/// async fn validate(token: BearerToken) -> Result<User, AuthError> {
+///     match token.as_str() {
+///         "secret" => Ok(User("admin".into())),
+///         _ => Err(AuthError::Rejected(Scheme::Bearer)),
+///     }
+/// }
+///
+/// async fn handler(ExtensionOwn(user): ExtensionOwn<User>) -> String {
+///     format!("hello,{}!", user.0)
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(Authenticate::new(validate));
+/// ```
+///
+/// [ExtensionRef]: crate::handler::extension::ExtensionRef
+/// [ExtensionOwn]: crate::handler::extension::ExtensionOwn
+pub struct Authenticate<F, T> {
+    validator: F,
+    _credential: PhantomData<fn(T)>,
+}
+
+impl<F, T> Clone for Authenticate<F, T>
+where
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            validator: self.validator.clone(),
+            _credential: PhantomData,
+        }
+    }
+}
+
+impl<F, T> Authenticate<F, T> {
+    /// construct a new middleware builder with given async validator function.
+    pub fn new<Fut, I, E>(validator: F) -> Self
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<I, E>>,
+    {
+        Self {
+            validator,
+            _credential: PhantomData,
+        }
+    }
+}
+
+impl<S, E, F, T> Service<Result<S, E>> for Authenticate<F, T>
+where
+    F: Clone,
+{
+    type Response = service::AuthenticateService<S, F, T>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::AuthenticateService {
+            service,
+            validator: self.validator.clone(),
+            _credential: PhantomData,
+        })
+    }
+}
+
+mod service {
+    use crate::{WebContext, error::Error, handler::FromRequest, http::WebResponse, service::ready::ReadyService};
+
+    use super::*;
+
+    pub struct AuthenticateService<S, F, T> {
+        pub(super) service: S,
+        pub(super) validator: F,
+        pub(super) _credential: PhantomData<fn(T)>,
+    }
+
+    impl<'r, C, B, S, ResB, F, Fut, T, I, E> Service<WebContext<'r, C, B>> for AuthenticateService<S, F, T>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<I, E>>,
+        T: for<'a> FromRequest<'a, WebContext<'r, C, B>, Type<'a> = T, Error = Error>,
+        I: Clone + Send + Sync + 'static,
+        Error: From<E>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let credential = T::from_request(&ctx).await?;
+            let identity = (self.validator)(credential).await?;
+            ctx.req_mut().extensions_mut().insert(identity);
+            self.service.call(ctx).await
+        }
+    }
+
+    impl<S, F, T> ReadyService for AuthenticateService<S, F, T>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::{
+            auth::{AuthError, BasicAuth, Scheme},
+            extension::ExtensionOwn,
+            handler_service,
+        },
+        http::{
+            StatusCode, WebRequest,
+            header::{AUTHORIZATION, WWW_AUTHENTICATE},
+            request,
+        },
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct User(String);
+
+    async fn validate(auth: BasicAuth) -> Result<User, AuthError> {
+        if auth.password() == "bar" {
+            Ok(User(auth.user().to_owned()))
+        } else {
+            Err(AuthError::Rejected(Scheme::Basic))
+        }
+    }
+
+    #[test]
+    fn authenticate() {
+        let service = App::new()
+            .at(
+                "/",
+                handler_service(|ExtensionOwn(user): ExtensionOwn<User>| async move { user.0 }),
+            )
+            .enclosed(Authenticate::new(validate))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert!(res.headers().contains_key(WWW_AUTHENTICATE));
+
+        // foo:baz
+        let req = request::Builder::default()
+            .header(AUTHORIZATION, "Basic Zm9vOmJheg==")
+            .body(Default::default())
+            .unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // foo:bar
+        let req = request::Builder::default()
+            .header(AUTHORIZATION, "Basic Zm9vOmJhcg==")
+            .body(Default::default())
+            .unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(collect_string_body(res.into_body()).now_or_panic().unwrap(), "foo");
+    }
+}
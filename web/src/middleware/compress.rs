@@ -32,7 +32,10 @@ mod service {
 
     use crate::{
         body::{BodyStream, NONE_BODY_HINT},
-        http::{BorrowReq, WebResponse, header::HeaderMap},
+        http::{
+            BorrowReq, WebResponse,
+            header::{CONTENT_TYPE, HeaderMap},
+        },
         service::{Service, ready::ReadyService},
     };
 
@@ -59,6 +62,15 @@ mod service {
                 _ => {}
             }
 
+            // server-sent events must be flushed to client as is.
+            if res
+                .headers()
+                .get(CONTENT_TYPE)
+                .is_some_and(|v| v.as_bytes().starts_with(b"text/event-stream"))
+            {
+                encoding = ContentEncoding::NoOp;
+            }
+
             Ok(encoder(res, encoding))
         }
     }
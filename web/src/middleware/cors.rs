@@ -0,0 +1,553 @@
+//! cross-origin resource sharing middleware.
+
+use core::time::Duration;
+
+use std::sync::Arc;
+
+use crate::{
+    http::{HeaderName, HeaderValue, Method},
+    service::Service,
+};
+
+/// builder for cross-origin resource sharing(CORS) middleware.
+///
+/// CORS preflight request(`OPTIONS` method with `Access-Control-Request-Method` header) is answered
+/// directly by the middleware and never reach the enclosed service. Preflight from disallowed origin or
+/// requesting disallowed method is answered without any CORS header and only requested headers that
+/// are allowed are echoed back. For this reason the middleware
+/// should be applied to [App] rather than individual route so preflight is handled before router
+/// produces [MethodNotAllowed] error for routes not accepting `OPTIONS` method.
+///
+/// For actual cross-origin request the middleware append according `Access-Control-*` headers to
+/// response produced by enclosed service. Error returned by enclosed service is rendered into response
+/// by the middleware and carry these headers too so browser can observe the actual error status.
+///
+/// # Examples
+/// ```rust
+/// # use std::time::Duration;
+/// # use xitca_web::{
+/// #   handler::handler_service,
+/// #   http::{header::CONTENT_TYPE, HeaderValue, Method},
+/// #   middleware::cors::Cors,
+/// #   route::get,
+/// #   App, WebContext
+/// # };
+/// App::new()
+///     .at("/", get(handler_service(|| async { "hello,world!" })))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(
+///         Cors::new()
+///             .allow_origin(HeaderValue::from_static("https://example.com"))
+///             .allow_methods([Method::GET, Method::POST])
+///             .allow_headers([CONTENT_TYPE])
+///             .allow_credentials(true)
+///             .max_age(Duration::from_secs(3600))
+///     );
+/// ```
+///
+/// [App]: crate::App
+/// [MethodNotAllowed]: crate::error::MethodNotAllowed
+#[derive(Clone)]
+pub struct Cors {
+    origin: AllowOrigin,
+    methods: AllowMethods,
+    headers: AllowHeaders,
+    expose_headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+#[derive(Clone)]
+enum AllowOrigin {
+    Any,
+    List(Vec<HeaderValue>),
+    Predicate(Arc<dyn Fn(&HeaderValue) -> bool + Send + Sync>),
+}
+
+#[derive(Clone)]
+enum AllowMethods {
+    Mirror,
+    List(Vec<Method>),
+}
+
+#[derive(Clone)]
+enum AllowHeaders {
+    Mirror,
+    List(Vec<HeaderName>),
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cors {
+    /// construct a new CORS middleware builder.
+    ///
+    /// no origin is allowed by default. `GET`, `HEAD` and `POST` methods are allowed by default.
+    pub fn new() -> Self {
+        Self {
+            origin: AllowOrigin::List(Vec::new()),
+            methods: AllowMethods::List(vec![Method::GET, Method::HEAD, Method::POST]),
+            headers: AllowHeaders::List(Vec::new()),
+            expose_headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// allow request from any origin.
+    ///
+    /// When combined with [Cors::allow_credentials] the request origin is echoed back instead of
+    /// wildcard `*` as the latter is rejected by browsers for credentialed request.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.origin = AllowOrigin::Any;
+        self
+    }
+
+    /// allow request from given origin. can be called multiple times for adding more origins.
+    pub fn allow_origin(self, origin: HeaderValue) -> Self {
+        self.allow_origins([origin])
+    }
+
+    /// allow request from a list of origins. can be called multiple times for adding more origins.
+    pub fn allow_origins<I>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderValue>,
+    {
+        match self.origin {
+            AllowOrigin::List(ref mut list) => list.extend(origins),
+            _ => self.origin = AllowOrigin::List(origins.into_iter().collect()),
+        }
+        self
+    }
+
+    /// allow request from origin matching given predicate function.
+    pub fn allow_origin_fn<F>(mut self, func: F) -> Self
+    where
+        F: Fn(&HeaderValue) -> bool + Send + Sync + 'static,
+    {
+        self.origin = AllowOrigin::Predicate(Arc::new(func));
+        self
+    }
+
+    /// allow given http methods in preflight response.
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.methods = AllowMethods::List(methods.into_iter().collect());
+        self
+    }
+
+    /// allow any http method by echoing the `Access-Control-Request-Method` of preflight request.
+    pub fn allow_any_method(mut self) -> Self {
+        self.methods = AllowMethods::Mirror;
+        self
+    }
+
+    /// allow given headers in preflight response.
+    pub fn allow_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.headers = AllowHeaders::List(headers.into_iter().collect());
+        self
+    }
+
+    /// allow any header by echoing the `Access-Control-Request-Headers` of preflight request.
+    pub fn allow_any_header(mut self) -> Self {
+        self.headers = AllowHeaders::Mirror;
+        self
+    }
+
+    /// expose given response headers to client side script.
+    pub fn expose_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.expose_headers.extend(headers);
+        self
+    }
+
+    /// allow request with credentials. (cookies, authorization headers or TLS client certificates)
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.credentials = allow;
+        self
+    }
+
+    /// set how long the result of preflight request can be cached by client.
+    pub fn max_age(mut self, dur: Duration) -> Self {
+        self.max_age = Some(dur);
+        self
+    }
+
+    fn finalize(&self) -> service::Inner {
+        let methods = match self.methods {
+            AllowMethods::Mirror => None,
+            AllowMethods::List(ref methods) => join(methods),
+        };
+
+        service::Inner {
+            origin: self.origin.clone(),
+            allow_methods: self.methods.clone(),
+            methods,
+            allow_headers: self.headers.clone(),
+            expose_headers: join(&self.expose_headers),
+            credentials: self.credentials,
+            max_age: self.max_age.map(|dur| HeaderValue::from(dur.as_secs())),
+        }
+    }
+}
+
+fn join<T: AsRef<str>>(items: &[T]) -> Option<HeaderValue> {
+    if items.is_empty() {
+        return None;
+    }
+    let value = items.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(",");
+    HeaderValue::try_from(value).ok()
+}
+
+impl<S, E> Service<Result<S, E>> for Cors {
+    type Response = service::CorsService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::CorsService {
+            service,
+            inner: self.finalize(),
+        })
+    }
+}
+
+mod service {
+    use core::convert::Infallible;
+
+    use crate::{
+        WebContext,
+        http::{
+            HeaderMap, StatusCode, WebResponse,
+            header::{
+                ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+                ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+                ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+            },
+        },
+        service::{Service, ready::ReadyService},
+    };
+
+    use super::*;
+
+    pub struct CorsService<S> {
+        pub(super) service: S,
+        pub(super) inner: Inner,
+    }
+
+    pub(super) struct Inner {
+        pub(super) origin: AllowOrigin,
+        pub(super) allow_methods: AllowMethods,
+        // joined value of allowed methods list.
+        pub(super) methods: Option<HeaderValue>,
+        pub(super) allow_headers: AllowHeaders,
+        pub(super) expose_headers: Option<HeaderValue>,
+        pub(super) credentials: bool,
+        pub(super) max_age: Option<HeaderValue>,
+    }
+
+    impl Inner {
+        // value of Access-Control-Allow-Origin header for given request origin.
+        fn allow_origin(&self, origin: Option<&HeaderValue>) -> Option<HeaderValue> {
+            match (&self.origin, origin) {
+                (AllowOrigin::Any, _) if !self.credentials => Some(HeaderValue::from_static("*")),
+                (AllowOrigin::Any, origin) => origin.cloned(),
+                (AllowOrigin::List(list), Some(origin)) => list.contains(origin).then(|| origin.clone()),
+                (AllowOrigin::Predicate(func), Some(origin)) => func(origin).then(|| origin.clone()),
+                _ => None,
+            }
+        }
+
+        // when allowed origin is derived from request origin the response must vary on it.
+        fn vary_origin(&self) -> bool {
+            !matches!(self.origin, AllowOrigin::Any) || self.credentials
+        }
+
+        fn extend_common(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) {
+            if let Some(value) = self.allow_origin(origin) {
+                headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, value);
+                if self.credentials {
+                    headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+                }
+            }
+        }
+
+        // preflight from disallowed origin or requesting disallowed method gets no CORS header and
+        // is rejected by client.
+        fn extend_preflight(&self, req: &HeaderMap, headers: &mut HeaderMap) {
+            headers.append(VARY, HeaderValue::from_static("origin"));
+            headers.append(VARY, HeaderValue::from_static("access-control-request-method"));
+            headers.append(VARY, HeaderValue::from_static("access-control-request-headers"));
+
+            let Some(allow_origin) = self.allow_origin(req.get(ORIGIN)) else {
+                return;
+            };
+
+            let Some(method) = req
+                .get(ACCESS_CONTROL_REQUEST_METHOD)
+                .and_then(|v| Method::from_bytes(v.as_bytes()).ok())
+            else {
+                return;
+            };
+
+            let methods = match self.allow_methods {
+                AllowMethods::List(ref list) if !list.contains(&method) => return,
+                AllowMethods::List(_) => self.methods.clone(),
+                AllowMethods::Mirror => HeaderValue::from_str(method.as_str()).ok(),
+            };
+
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+            if self.credentials {
+                headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+            }
+
+            if let Some(methods) = methods {
+                headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+            }
+
+            // only requested headers that are allowed are echoed back.
+            let requested = req
+                .get_all(ACCESS_CONTROL_REQUEST_HEADERS)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+                .filter(|name| match self.allow_headers {
+                    AllowHeaders::Mirror => true,
+                    AllowHeaders::List(ref list) => list.contains(name),
+                })
+                .collect::<Vec<_>>();
+            if let Some(allow_headers) = join(&requested) {
+                headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+            }
+
+            if let Some(ref max_age) = self.max_age {
+                headers.insert(ACCESS_CONTROL_MAX_AGE, max_age.clone());
+            }
+        }
+
+        fn extend_response(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) {
+            self.extend_common(origin, headers);
+
+            if let Some(ref expose) = self.expose_headers {
+                headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, expose.clone());
+            }
+
+            if self.vary_origin() {
+                headers.append(VARY, HeaderValue::from_static("origin"));
+            }
+        }
+    }
+
+    impl<'r, C, B, S, ResB, Err> Service<WebContext<'r, C, B>> for CorsService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Err>,
+        Err: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Infallible>,
+        ResB: Default,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Err;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let req = ctx.req();
+
+            if req.method() == Method::OPTIONS && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD) {
+                let mut res = WebResponse::new(ResB::default());
+                *res.status_mut() = StatusCode::NO_CONTENT;
+                self.inner.extend_preflight(req.headers(), res.headers_mut());
+                return Ok(res);
+            }
+
+            let origin = req.headers().get(ORIGIN).cloned();
+
+            let mut res = match self.service.call(ctx.reborrow()).await {
+                Ok(res) => res,
+                Err(e) => match e.call(ctx).await {
+                    Ok(res) => res,
+                    Err(i) => match i {},
+                },
+            };
+            self.inner.extend_response(origin.as_ref(), res.headers_mut());
+            Ok(res)
+        }
+    }
+
+    impl<S> ReadyService for CorsService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::handler_service,
+        http::{
+            StatusCode, WebRequest,
+            header::{
+                ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+                ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS,
+                ACCESS_CONTROL_REQUEST_METHOD, CONTENT_TYPE, ORIGIN, VARY,
+            },
+            request,
+        },
+        route::get,
+    };
+
+    use super::*;
+
+    fn request(method: Method, origin: &'static str) -> WebRequest {
+        request::Builder::default()
+            .method(method)
+            .header(ORIGIN, origin)
+            .body(Default::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn preflight() {
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .enclosed(
+                Cors::new()
+                    .allow_origin(HeaderValue::from_static("https://example.com"))
+                    .allow_headers([CONTENT_TYPE])
+                    .max_age(Duration::from_secs(60)),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let mut req = request(Method::OPTIONS, "https://example.com");
+        req.headers_mut()
+            .insert(ACCESS_CONTROL_REQUEST_METHOD, HeaderValue::from_static("POST"));
+        req.headers_mut()
+            .insert(ACCESS_CONTROL_REQUEST_HEADERS, HeaderValue::from_static("content-type"));
+
+        let res = service.call(req).now_or_panic().unwrap();
+
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        let headers = res.headers();
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "https://example.com");
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_METHODS).unwrap(), "GET,HEAD,POST");
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap(), "content-type");
+        assert_eq!(headers.get(ACCESS_CONTROL_MAX_AGE).unwrap(), "60");
+        assert_eq!(headers.get_all(VARY).iter().count(), 3);
+
+        // only requested headers that are allowed are echoed back.
+        let mut req = request(Method::OPTIONS, "https://example.com");
+        req.headers_mut()
+            .insert(ACCESS_CONTROL_REQUEST_METHOD, HeaderValue::from_static("GET"));
+        req.headers_mut().insert(
+            ACCESS_CONTROL_REQUEST_HEADERS,
+            HeaderValue::from_static("x-custom, Content-Type"),
+        );
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap(), "content-type");
+
+        // disallowed origin and method get no CORS header.
+        for (origin, method) in [("https://evil.com", "POST"), ("https://example.com", "DELETE")] {
+            let mut req = request(Method::OPTIONS, origin);
+            req.headers_mut()
+                .insert(ACCESS_CONTROL_REQUEST_METHOD, HeaderValue::from_static(method));
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::NO_CONTENT);
+            let headers = res.headers();
+            assert!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+            assert!(headers.get(ACCESS_CONTROL_ALLOW_METHODS).is_none());
+            assert!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).is_none());
+            assert!(headers.get(ACCESS_CONTROL_MAX_AGE).is_none());
+        }
+
+        // plain OPTIONS request is not preflight and goes to router.
+        let res = service
+            .call(request(Method::OPTIONS, "https://example.com"))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn actual_request() {
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .enclosed(
+                Cors::new()
+                    .allow_origin_fn(|origin| origin.as_bytes().ends_with(b".example.com"))
+                    .allow_credentials(true),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service
+            .call(request(Method::GET, "https://api.example.com"))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let headers = res.headers();
+        assert_eq!(
+            headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://api.example.com"
+        );
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(), "true");
+        assert_eq!(headers.get(VARY).unwrap(), "origin");
+
+        let res = service
+            .call(request(Method::GET, "https://evil.com"))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        assert_eq!(res.headers().get(VARY).unwrap(), "origin");
+
+        // error of enclosed service is rendered with cors headers.
+        let mut req = request(Method::GET, "https://api.example.com");
+        *req.uri_mut() = crate::http::Uri::from_static("/not_found");
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://api.example.com"
+        );
+    }
+
+    #[test]
+    fn any_origin() {
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .enclosed(Cors::new().allow_any_origin())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service
+            .call(request(Method::GET, "https://example.com"))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "*");
+        assert!(res.headers().get(VARY).is_none());
+    }
+}
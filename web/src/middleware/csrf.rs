@@ -0,0 +1,442 @@
+//! cross-site request forgery protection middleware.
+
+use core::fmt;
+
+use std::{collections::HashSet, error, sync::Arc};
+
+use cookie::Key;
+
+use crate::{
+    context::WebContext,
+    error::{Error, ExtensionNotFound, blank_error_service, error_from_service},
+    handler::FromRequest,
+    http::{HeaderName, StatusCode},
+    service::Service,
+};
+
+/// builder for cross-site request forgery(CSRF) protection middleware.
+///
+/// a random token is issued to client in a signed cookie and the same token must be submitted back by
+/// request with unsafe method(anything other than `GET`, `HEAD`, `OPTIONS` and `TRACE`) either through
+/// request header or urlencoded form field. request failing the check is rejected with [CsrfError]
+/// which produce "403 Forbidden" response.
+///
+/// the token can be extracted with [CsrfToken] and embedded into html form.
+///
+/// form field is only looked up from `application/x-www-form-urlencoded` request body. `multipart/form-data`
+/// body is not inspected and such request must submit the token through request header.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{cookie::Key, form::Form, handler_service, html::Html},
+/// #   middleware::csrf::{Csrf, CsrfToken},
+/// #   route::{get, post},
+/// #   App, WebContext
+/// # };
+/// // render html form with csrf token as hidden field.
+/// async fn form(token: CsrfToken) -> Html<String> {
+///     Html(format!(r#"<form method="post"><input type="hidden" name="csrf_token" value="{}"></form>"#, token.as_str()))
+/// }
+///
+/// // csrf token is checked before request reaching this handler.
+/// async fn submit() -> &'static str {
+///     "submitted"
+/// }
+///
+/// App::new()
+///     .at("/", get(handler_service(form)).post(handler_service(submit)))
+///     // webhook endpoint authenticating it's caller by other means.
+///     .at("/webhook", post(handler_service(submit)))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(Csrf::new(Key::generate()).exempt("/webhook"));
+/// ```
+pub struct Csrf {
+    config: Arc<Config>,
+}
+
+struct Config {
+    key: Key,
+    cookie_name: String,
+    header_name: HeaderName,
+    field_name: String,
+    secure: bool,
+    exempt: HashSet<String>,
+}
+
+impl Csrf {
+    /// construct a new CSRF middleware builder with given cookie signing key.
+    pub fn new(key: impl Into<Key>) -> Self {
+        Self {
+            config: Arc::new(Config {
+                key: key.into(),
+                cookie_name: String::from("csrf_token"),
+                header_name: HeaderName::from_static("x-csrf-token"),
+                field_name: String::from("csrf_token"),
+                secure: true,
+                exempt: HashSet::new(),
+            }),
+        }
+    }
+
+    /// set name of token cookie. default to "csrf_token".
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.config_mut().cookie_name = name.into();
+        self
+    }
+
+    /// set secure attribute of token cookie. default to true.
+    pub fn cookie_secure(mut self, secure: bool) -> Self {
+        self.config_mut().secure = secure;
+        self
+    }
+
+    /// set name of request header carrying token. default to "x-csrf-token".
+    pub fn header_name(mut self, name: HeaderName) -> Self {
+        self.config_mut().header_name = name;
+        self
+    }
+
+    /// set name of urlencoded form field carrying token. default to "csrf_token".
+    pub fn field_name(mut self, name: impl Into<String>) -> Self {
+        self.config_mut().field_name = name.into();
+        self
+    }
+
+    /// exempt request matching given route pattern from token check. pattern uses the same syntax as
+    /// [App::at](crate::App::at). e.g. `/webhook/{id}` exempts `/webhook/1` and `/webhook/2`.
+    /// can be called multiple times for adding more patterns.
+    ///
+    /// # Panics
+    /// when [Csrf] is used as middleware and given pattern is invalid or conflicts with other pattern.
+    pub fn exempt(mut self, path: impl Into<String>) -> Self {
+        self.config_mut().exempt.insert(path.into());
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut Config {
+        Arc::get_mut(&mut self.config).expect("Csrf must not be mutated after it's used as middleware")
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for Csrf {
+    type Response = service::CsrfService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        let mut exempt = xitca_router::Router::new();
+        for pattern in self.config.exempt.iter() {
+            exempt.insert(pattern.as_str(), ()).unwrap();
+        }
+
+        res.map(|service| service::CsrfService {
+            service,
+            config: self.config.clone(),
+            exempt,
+        })
+    }
+}
+
+/// CSRF token extractor. available when [Csrf] middleware is applied to application or route.
+#[derive(Clone, Debug)]
+pub struct CsrfToken(Arc<str>);
+
+impl CsrfToken {
+    /// get token as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for CsrfToken {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        ctx.req()
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| Error::from(ExtensionNotFound::from_type::<Self>()))
+    }
+}
+
+/// error type for request failing CSRF token check.
+#[derive(Debug)]
+pub enum CsrfError {
+    /// token is not found from request header, form field or cookie.
+    Missing,
+    /// submitted token does not match the one in cookie.
+    Mismatch,
+}
+
+impl fmt::Display for CsrfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => f.write_str("csrf token is missing"),
+            Self::Mismatch => f.write_str("csrf token mismatch"),
+        }
+    }
+}
+
+impl error::Error for CsrfError {}
+
+error_from_service!(CsrfError);
+blank_error_service!(CsrfError, StatusCode::FORBIDDEN);
+
+mod service {
+    use cookie::{Cookie, SameSite};
+
+    use crate::{
+        body::BodyStream,
+        bytes::Bytes,
+        handler::{body::Limit, form::DEFAULT_LIMIT},
+        http::{HeaderMap, Method, WebResponse, header::CONTENT_TYPE},
+        middleware::util::{random_hex, read_signed_cookie, write_signed_cookie},
+        service::ready::ReadyService,
+    };
+
+    use super::*;
+
+    pub struct CsrfService<S> {
+        pub(super) service: S,
+        pub(super) config: Arc<Config>,
+        pub(super) exempt: xitca_router::Router<()>,
+    }
+
+    // compare token in constant time.
+    fn token_eq(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+
+    impl Config {
+        fn cookie(&self, token: String) -> Cookie<'static> {
+            Cookie::build((self.cookie_name.clone(), token))
+                .path("/")
+                .secure(self.secure)
+                .http_only(true)
+                .same_site(SameSite::Strict)
+                .build()
+        }
+    }
+
+    // check content type is urlencoded form by it's media type essence. parameters like charset are ignored.
+    fn is_form(headers: &HeaderMap) -> bool {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .is_some_and(|essence| essence.trim().eq_ignore_ascii_case("application/x-www-form-urlencoded"))
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for CsrfService<S>
+    where
+        B: BodyStream + Default + From<Bytes>,
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let config = &*self.config;
+
+            let cookie = read_signed_cookie(ctx.req().headers(), &config.key, &config.cookie_name);
+
+            let safe = matches!(
+                *ctx.req().method(),
+                Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+            );
+
+            // middleware runs before routing so exempted patterns are matched against request path directly.
+            if !safe && self.exempt.at(ctx.req().uri().path()).is_err() {
+                let expected = cookie.as_deref().ok_or(CsrfError::Missing)?;
+
+                let submitted = match ctx.req().headers().get(&config.header_name) {
+                    Some(value) => Some(value.as_bytes().to_vec()),
+                    None if is_form(ctx.req().headers()) => {
+                        let (bytes, _) = ctx.extract::<(Bytes, Limit<DEFAULT_LIMIT>)>().await?;
+                        let token = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&bytes)?
+                            .into_iter()
+                            .find_map(|(k, v)| (k == config.field_name).then_some(v));
+                        // restore body for downstream service.
+                        *ctx.body_get_mut() = B::from(bytes);
+                        token.map(String::into_bytes)
+                    }
+                    None => None,
+                };
+
+                let submitted = submitted.ok_or(CsrfError::Missing)?;
+
+                if !token_eq(expected.as_bytes(), &submitted) {
+                    return Err(CsrfError::Mismatch.into());
+                }
+            }
+
+            let (token, issue) = match cookie {
+                Some(token) => (token, false),
+                None => (random_hex::<32>(), true),
+            };
+
+            ctx.req_mut()
+                .extensions_mut()
+                .insert(CsrfToken(Arc::from(token.as_str())));
+
+            let mut res = self.service.call(ctx.reborrow()).await?;
+
+            if issue {
+                write_signed_cookie(res.headers_mut(), &config.key, config.cookie(token))?;
+            }
+
+            Ok(res)
+        }
+    }
+
+    impl<S> ReadyService for CsrfService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        body::RequestBody,
+        bytes::Bytes,
+        handler::{form::Form, handler_service},
+        http::{
+            HeaderValue, Method, WebRequest,
+            const_header_value::APPLICATION_WWW_FORM_URLENCODED,
+            header::{CONTENT_TYPE, COOKIE, SET_COOKIE},
+            request,
+        },
+        route::{get, post},
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    struct Submit {
+        name: String,
+    }
+
+    async fn token(token: CsrfToken) -> String {
+        token.as_str().to_owned()
+    }
+
+    async fn submit(Form(form): Form<Submit>) -> String {
+        form.name
+    }
+
+    fn req(method: Method, path: &'static str, cookie: Option<&str>) -> WebRequest {
+        let mut req = request::Builder::default()
+            .method(method)
+            .uri(path)
+            .body(Default::default())
+            .unwrap();
+        if let Some(cookie) = cookie {
+            req.headers_mut().insert(COOKIE, HeaderValue::from_str(cookie).unwrap());
+        }
+        req
+    }
+
+    fn form(req: &mut WebRequest, body: &'static str) {
+        req.headers_mut().insert(CONTENT_TYPE, APPLICATION_WWW_FORM_URLENCODED);
+        let ext = core::mem::take(req.body_mut());
+        *req.body_mut() = ext.map_body(|_| RequestBody::from(Bytes::from_static(body.as_bytes())));
+    }
+
+    #[test]
+    fn csrf() {
+        let service = App::new()
+            .at("/", get(handler_service(token)).post(handler_service(submit)))
+            .at("/exempt", post(handler_service(|| async { "exempt" })))
+            .at("/hook/{id}", post(handler_service(|| async { "exempt" })))
+            .enclosed(Csrf::new(Key::generate()).exempt("/exempt").exempt("/hook/{id}"))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(req(Method::GET, "/", None)).now_or_panic().unwrap();
+        let cookie = res.headers().get(SET_COOKIE).unwrap().to_str().unwrap().to_owned();
+        let cookie = cookie.split(';').next().unwrap().to_owned();
+        let token = collect_string_body(res.into_body()).now_or_panic().unwrap();
+
+        // token cookie is issued once.
+        let res = service
+            .call(req(Method::GET, "/", Some(&cookie)))
+            .now_or_panic()
+            .unwrap();
+        assert!(res.headers().get(SET_COOKIE).is_none());
+        assert_eq!(collect_string_body(res.into_body()).now_or_panic().unwrap(), token);
+
+        let res = service.call(req(Method::POST, "/", None)).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = service
+            .call(req(Method::POST, "/", Some(&cookie)))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let mut r = req(Method::POST, "/", Some(&cookie));
+        r.headers_mut()
+            .insert(HeaderName::from_static("x-csrf-token"), HeaderValue::from_static("996"));
+        let res = service.call(r).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let mut r = req(Method::POST, "/", Some(&cookie));
+        r.headers_mut().insert(
+            HeaderName::from_static("x-csrf-token"),
+            HeaderValue::from_str(&token).unwrap(),
+        );
+        form(&mut r, "name=foo");
+        let res = service.call(r).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let mut r = req(Method::POST, "/", Some(&cookie));
+        let body = format!("name=bar&csrf_token={token}").leak();
+        form(&mut r, body);
+        let res = service.call(r).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(collect_string_body(res.into_body()).now_or_panic().unwrap(), "bar");
+
+        // media type parameters are ignored when looking up form field.
+        let mut r = req(Method::POST, "/", Some(&cookie));
+        let body = format!("name=baz&csrf_token={token}").leak();
+        form(&mut r, body);
+        r.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded; charset=UTF-8"),
+        );
+        let res = service.call(r).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(collect_string_body(res.into_body()).now_or_panic().unwrap(), "baz");
+
+        let res = service.call(req(Method::POST, "/exempt", None)).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // exempted route pattern matches any path of the route.
+        let res = service.call(req(Method::POST, "/hook/1", None)).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // path only sharing prefix with exempted pattern is checked.
+        let res = service
+            .call(req(Method::POST, "/exempt/1", None))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,446 @@
+//! conditional request middleware with entity tag.
+
+use core::future::{Future, Ready};
+
+use crate::{
+    error::Error,
+    http::{HeaderValue, WebRequest},
+    service::Service,
+};
+
+type NoValidator = fn(&WebRequest<()>) -> Ready<Result<Option<HeaderValue>, Error>>;
+
+/// conditional request middleware for dynamic responses.
+///
+/// response of `GET` and `HEAD` request with `200 OK` status code is buffered when it's body size is known
+/// and not exceeding the size limit. an entity tag is computed from the buffered body and appended to
+/// response as `ETag` header. streaming body without known size or exceeding the limit is skipped.
+/// when enclosed service provides `ETag` header by itself the value is reused and no buffering happens.
+///
+/// - `If-None-Match` header of `GET` and `HEAD` request is compared with entity tag and a `304 Not Modified`
+///   response without body is produced on match.
+/// - `If-Match` header of unsafe method request(`POST`, `PUT`, `PATCH`, `DELETE` etc) is only evaluated when
+///   a validator providing entity tag of current representation is set with [ETag::validator]. otherwise
+///   the request is passed to enclosed service as is.
+///
+/// strong entity tag is derived from 64 bit FNV-1a hash of response body which is stable across builds and
+/// platforms.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::etag::ETag, route::get, App, WebContext};
+/// App::new()
+///     .at("/", get(handler_service(|| async { "hello,world!" })))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // compute weak entity tag for response body not exceeding 1MB.
+///     .enclosed(ETag::weak().max_size(1024 * 1024));
+/// ```
+#[derive(Clone, Copy)]
+pub struct ETag<F = NoValidator> {
+    weak: bool,
+    max_size: usize,
+    validator: Option<F>,
+}
+
+impl Default for ETag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ETag {
+    /// construct a new middleware builder producing strong entity tag.
+    /// default body size limit is 64KB.
+    pub const fn new() -> Self {
+        Self {
+            weak: false,
+            max_size: 64 * 1024,
+            validator: None,
+        }
+    }
+
+    /// construct a new middleware builder producing weak entity tag.
+    pub const fn weak() -> Self {
+        Self {
+            weak: true,
+            ..Self::new()
+        }
+    }
+}
+
+impl<F> ETag<F> {
+    /// set max size in byte unit of response body that can be buffered for computing entity tag.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// set validator function evaluating `If-Match` precondition of unsafe method request.
+    ///
+    /// the function receives the request and resolves to entity tag of current representation of the
+    /// target resource or `None` when it does not exist. `412 Precondition Failed` response is produced
+    /// when the precondition fails and the request is not passed to enclosed service. the tag should be
+    /// the same one `GET` request of the resource observes. e.g. a version stored alongside the resource
+    /// that `GET` handler sets as `ETag` header.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, http::HeaderValue, middleware::etag::ETag, route::get, App, WebContext};
+    /// App::new()
+    ///     .at("/", get(handler_service(|| async { "hello,world!" })).put(handler_service(|| async { "updated" })))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .enclosed(ETag::new().validator(|req| {
+    ///         let _path = req.uri().path().to_owned();
+    ///         // look up version of resource with path.
+    ///         async move { Ok(Some(HeaderValue::from_static("\"v1\""))) }
+    ///     }));
+    /// ```
+    pub fn validator<F2, Fut>(self, func: F2) -> ETag<F2>
+    where
+        F2: Fn(&WebRequest<()>) -> Fut,
+        Fut: Future<Output = Result<Option<HeaderValue>, Error>>,
+    {
+        ETag {
+            weak: self.weak,
+            max_size: self.max_size,
+            validator: Some(func),
+        }
+    }
+}
+
+impl<S, E, F> Service<Result<S, E>> for ETag<F>
+where
+    F: Clone,
+{
+    type Response = service::ETagService<S, F>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::ETagService {
+            service,
+            config: self.clone(),
+        })
+    }
+}
+
+mod service {
+    use core::{future::poll_fn, pin::pin};
+
+    use crate::{
+        WebContext,
+        body::BodyStream,
+        bytes::{Bytes, BytesMut},
+        http::{
+            Method, StatusCode, WebResponse,
+            header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH},
+        },
+        service::ready::ReadyService,
+    };
+
+    use super::*;
+
+    pub struct ETagService<S, F> {
+        pub(super) service: S,
+        pub(super) config: ETag<F>,
+    }
+
+    impl<'r, C, B, S, ResB, F, Fut> Service<WebContext<'r, C, B>> for ETagService<S, F>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        ResB: BodyStream + From<Bytes> + Default,
+        F: Fn(&WebRequest<()>) -> Fut,
+        Fut: Future<Output = Result<Option<HeaderValue>, Error>>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let method = ctx.req().method();
+
+            if method == Method::GET || method == Method::HEAD {
+                let if_none_match = ctx.req().headers().get(IF_NONE_MATCH).cloned();
+                let res = self.service.call(ctx).await?;
+                let (res, tag) = self.tag(res).await?;
+                return match (if_none_match, tag) {
+                    (Some(cond), Some(tag)) if matches(&cond, &tag, false) => Ok(not_modified(res)),
+                    _ => Ok(res),
+                };
+            }
+
+            if is_unsafe(method) {
+                if let (Some(validator), Some(cond)) = (&self.config.validator, ctx.req().headers().get(IF_MATCH)) {
+                    let cond = cond.clone();
+                    let tag = validator(ctx.req()).await?;
+                    if !precondition(&cond, tag.as_ref()) {
+                        let mut res = WebResponse::new(ResB::default());
+                        *res.status_mut() = StatusCode::PRECONDITION_FAILED;
+                        return Ok(res);
+                    }
+                }
+            }
+
+            self.service.call(ctx).await
+        }
+    }
+
+    impl<S, F> ETagService<S, F> {
+        // look up entity tag of response. compute it from response body when possible.
+        async fn tag<ResB>(&self, res: WebResponse<ResB>) -> Result<(WebResponse<ResB>, Option<HeaderValue>), Error>
+        where
+            ResB: BodyStream + From<Bytes>,
+        {
+            if res.status() != StatusCode::OK {
+                return Ok((res, None));
+            }
+
+            if let Some(tag) = res.headers().get(ETAG) {
+                let tag = tag.clone();
+                return Ok((res, Some(tag)));
+            }
+
+            match res.body().size_hint() {
+                (low, Some(up)) if low <= up && up <= self.config.max_size => {}
+                _ => return Ok((res, None)),
+            }
+
+            let (mut parts, body) = res.into_parts();
+            let mut body = pin!(body);
+            let mut buf = BytesMut::new();
+
+            while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                let chunk = chunk.map_err(|e| Error::from(e.into()))?;
+                buf.extend_from_slice(chunk.as_ref());
+            }
+
+            let tag = self.compute(&buf);
+            parts.headers.insert(ETAG, tag.clone());
+
+            Ok((WebResponse::from_parts(parts, ResB::from(buf.freeze())), Some(tag)))
+        }
+
+        fn compute(&self, buf: &[u8]) -> HeaderValue {
+            let hash = fnv1a(buf);
+            let tag = if self.config.weak {
+                format!("W/\"{:x}-{hash:016x}\"", buf.len())
+            } else {
+                format!("\"{:x}-{hash:016x}\"", buf.len())
+            };
+            HeaderValue::try_from(tag).expect("hex string must be valid HeaderValue")
+        }
+    }
+
+    impl<S, F> ReadyService for ETagService<S, F>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    fn is_unsafe(method: &Method) -> bool {
+        !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+    }
+
+    // 64 bit FNV-1a hash. it's output is specified and does not change between builds.
+    pub(super) fn fnv1a(buf: &[u8]) -> u64 {
+        buf.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    // evaluate If-Match precondition against entity tag of current representation. `*` matches any
+    // existing representation and other tags are compared strongly.
+    pub(super) fn precondition(cond: &HeaderValue, tag: Option<&HeaderValue>) -> bool {
+        match tag {
+            Some(_) if cond.as_bytes().trim_ascii() == b"*" => true,
+            Some(tag) => matches(cond, tag, true),
+            None => false,
+        }
+    }
+
+    // compare conditional header value with entity tag. strong comparison requires both tags to be strong and
+    // byte equal. weak comparison ignores weak indicator.
+    pub(super) fn matches(cond: &HeaderValue, tag: &HeaderValue, strong: bool) -> bool {
+        let tag = tag.as_bytes();
+        if strong && tag.starts_with(b"W/") {
+            return false;
+        }
+        let tag = opaque(tag);
+        cond.as_bytes().split(|b| *b == b',').map(<[u8]>::trim_ascii).any(|c| {
+            if c == b"*" {
+                return true;
+            }
+            if strong && c.starts_with(b"W/") {
+                return false;
+            }
+            opaque(c) == tag
+        })
+    }
+
+    fn opaque(tag: &[u8]) -> &[u8] {
+        tag.strip_prefix(b"W/").unwrap_or(tag)
+    }
+
+    fn not_modified<ResB>(res: WebResponse<ResB>) -> WebResponse<ResB>
+    where
+        ResB: From<Bytes>,
+    {
+        let (mut parts, _) = res.into_parts();
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(CONTENT_LENGTH);
+        parts.headers.remove(CONTENT_TYPE);
+        WebResponse::from_parts(parts, ResB::from(Bytes::new()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::handler_service,
+        http::{
+            Method, StatusCode, WebRequest,
+            header::{ETAG, HeaderValue, IF_MATCH, IF_NONE_MATCH},
+            request,
+        },
+        route::get,
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let tag = HeaderValue::from_static("\"abc\"");
+        let weak = HeaderValue::from_static("W/\"abc\"");
+
+        assert!(service::matches(&HeaderValue::from_static("\"abc\""), &tag, true));
+        assert!(service::matches(
+            &HeaderValue::from_static("\"x\", \"abc\""),
+            &tag,
+            true
+        ));
+        assert!(service::matches(&HeaderValue::from_static("*"), &tag, true));
+        assert!(!service::matches(&HeaderValue::from_static("W/\"abc\""), &tag, true));
+        assert!(!service::matches(&HeaderValue::from_static("\"abc\""), &weak, true));
+        assert!(service::matches(&HeaderValue::from_static("W/\"abc\""), &tag, false));
+        assert!(service::matches(&HeaderValue::from_static("\"abc\""), &weak, false));
+        assert!(!service::matches(&HeaderValue::from_static("\"abcd\""), &tag, false));
+    }
+
+    #[test]
+    fn conditional() {
+        let service = App::new()
+            .at(
+                "/",
+                get(handler_service(|| async { "hello,world!" })).post(handler_service(|| async { "posted" })),
+            )
+            .enclosed(ETag::new())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let tag = res.headers().get(ETAG).unwrap().clone();
+        // tag is derived from stable hash of body.
+        assert_eq!(tag, "\"c-a5cc89871b9dec76\"");
+        assert_eq!(
+            collect_string_body(res.into_body()).now_or_panic().unwrap(),
+            "hello,world!"
+        );
+
+        let req = request::Builder::default()
+            .header(IF_NONE_MATCH, tag.clone())
+            .body(Default::default())
+            .unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers().get(ETAG).unwrap(), tag);
+        assert_eq!(collect_string_body(res.into_body()).now_or_panic().unwrap(), "");
+
+        let req = request::Builder::default()
+            .header(IF_NONE_MATCH, "\"996\"")
+            .body(Default::default())
+            .unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // If-Match is not evaluated without validator.
+        let req = request::Builder::default()
+            .method(Method::POST)
+            .header(IF_MATCH, "\"996\"")
+            .body(Default::default())
+            .unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(collect_string_body(res.into_body()).now_or_panic().unwrap(), "posted");
+    }
+
+    #[test]
+    fn validator() {
+        let service = App::new()
+            .at(
+                "/",
+                get(handler_service(|| async { "hello,world!" })).post(handler_service(|| async { "posted" })),
+            )
+            .at("/missing", handler_service(|| async { "posted" }))
+            .enclosed(ETag::new().validator(|req| {
+                let exists = req.uri().path() == "/";
+                async move { Ok(exists.then(|| HeaderValue::from_static("\"v1\""))) }
+            }))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let status = |path: &'static str, cond: &'static str| {
+            let req = request::Builder::default()
+                .method(Method::POST)
+                .uri(path)
+                .header(IF_MATCH, cond)
+                .body(Default::default())
+                .unwrap();
+            service.call(req).now_or_panic().unwrap().status()
+        };
+
+        assert_eq!(status("/", "\"996\""), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(status("/", "W/\"v1\""), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(status("/", "\"996\", \"v1\""), StatusCode::OK);
+        assert_eq!(status("/", "*"), StatusCode::OK);
+        // resource without current representation fails any precondition.
+        assert_eq!(status("/missing", "*"), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[test]
+    fn weak_and_limit() {
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .enclosed(ETag::weak())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert!(res.headers().get(ETAG).unwrap().as_bytes().starts_with(b"W/"));
+
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .enclosed(ETag::new().max_size(4))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert!(res.headers().get(ETAG).is_none());
+    }
+}
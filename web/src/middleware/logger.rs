@@ -14,8 +14,8 @@ use crate::service::Service;
 ///     // log http request and error with default setting.
 ///     .enclosed(Logger::new());
 /// ```
-pub struct Logger {
-    logger: middleware::Logger,
+pub struct Logger<const CLIENT_IP: bool = false> {
+    logger: middleware::Logger<CLIENT_IP>,
 }
 
 impl Default for Logger {
@@ -43,14 +43,24 @@ impl Logger {
             logger: middleware::Logger::with_level(level),
         }
     }
+
+    /// record client ip of request. client ip is resolved by [ClientIpResolver] middleware enclosed outside of
+    /// Logger and falls back to peer address of connection when absent.
+    ///
+    /// [ClientIpResolver]: crate::middleware::ClientIpResolver
+    pub fn with_client_ip(self) -> Logger<true> {
+        Logger {
+            logger: self.logger.with_client_ip(),
+        }
+    }
 }
 
-impl<Arg> Service<Arg> for Logger
+impl<Arg, const CLIENT_IP: bool> Service<Arg> for Logger<CLIENT_IP>
 where
-    middleware::Logger: Service<Arg>,
+    middleware::Logger<CLIENT_IP>: Service<Arg>,
 {
-    type Response = <middleware::Logger as Service<Arg>>::Response;
-    type Error = <middleware::Logger as Service<Arg>>::Error;
+    type Response = <middleware::Logger<CLIENT_IP> as Service<Arg>>::Response;
+    type Error = <middleware::Logger<CLIENT_IP> as Service<Arg>>::Error;
 
     async fn call(&self, arg: Arg) -> Result<Self::Response, Self::Error> {
         self.logger.call(arg).await
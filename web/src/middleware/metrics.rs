@@ -0,0 +1,595 @@
+//! prometheus metrics middleware and exposition service.
+
+use core::{convert::Infallible, fmt, fmt::Write};
+
+use std::sync::{Arc, Mutex};
+
+use xitca_http::util::service::router::{MatchedPath, PathGen, RouteGen, RouterMapErr};
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::Error,
+    http::{
+        WebResponse,
+        header::{CONTENT_TYPE, HeaderValue},
+    },
+    service::Service,
+};
+
+/// default histogram buckets of request latency in seconds.
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+const UNMATCHED: &str = "<unmatched>";
+
+/// middleware recording http request metrics in prometheus format.
+///
+/// following metrics are recorded with `route`, `method` and `status` labels where `route` is the
+/// matched route pattern of [App::at] (`<unmatched>` when no route is matched) and `method` is one of
+/// the standard methods (`OTHER` for extension method):
+/// - `http_requests_total` counter.
+/// - `http_request_duration_seconds` histogram of time used until response head is produced.
+///
+/// `http_requests_in_flight` gauge without label counts requests currently being served.
+///
+/// when server is running with [HttpServer] connection metrics of server workers are also exposed
+/// with `worker` label:
+/// - `xitca_server_connections_accepted_total` counter.
+/// - `xitca_server_connections_active` gauge.
+/// - `xitca_server_accept_errors_total` counter.
+///
+/// every worker thread records to it's own atomic counters without locking and counters are aggregated
+/// when metrics are rendered by [Metrics::service].
+///
+/// error produced by enclosed service is rendered into response and it's status is recorded.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::metrics::Metrics, App, WebContext};
+/// let metrics = Metrics::new();
+///
+/// App::new()
+///     .at("/users/{id}", handler_service(|| async { "user" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // expose metrics in prometheus text format.
+///     .at("/metrics", metrics.service())
+///     // record metrics of all routes.
+///     .enclosed(metrics);
+/// ```
+///
+/// [App::at]: crate::App::at
+/// [HttpServer]: crate::HttpServer
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics")
+            .field("buckets", &self.registry.buckets)
+            .finish()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// construct a new metrics middleware with [DEFAULT_BUCKETS].
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_BUCKETS)
+    }
+
+    /// construct a new metrics middleware with given histogram buckets of request latency in seconds.
+    ///
+    /// # Panics
+    /// when buckets are not in ascending order.
+    pub fn with_buckets(buckets: impl Into<Vec<f64>>) -> Self {
+        let buckets = buckets.into();
+        assert!(
+            buckets.windows(2).all(|w| w[0] < w[1]),
+            "histogram buckets must be in ascending order"
+        );
+        Self {
+            registry: Arc::new(Registry {
+                buckets: buckets.into_boxed_slice(),
+                series: Mutex::new(Vec::new()),
+                in_flight: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// construct exposition service rendering recorded metrics in prometheus text format.
+    pub fn service(&self) -> MetricsService {
+        MetricsService {
+            registry: self.registry.clone(),
+        }
+    }
+
+    /// render recorded metrics in prometheus text format.
+    pub fn render(&self) -> String {
+        self.registry.render()
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for Metrics {
+    type Response = service::MetricsMiddleware<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::MetricsMiddleware::new(service, self.registry.clone()))
+    }
+}
+
+/// service exposing metrics recorded by [Metrics] in prometheus text format.
+#[derive(Clone)]
+pub struct MetricsService {
+    registry: Arc<Registry>,
+}
+
+impl PathGen for MetricsService {}
+
+impl RouteGen for MetricsService {
+    type Route<R> = RouterMapErr<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        RouterMapErr(route)
+    }
+}
+
+impl Service for MetricsService {
+    type Response = Self;
+    type Error = Infallible;
+
+    async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for MetricsService {
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(ResponseBody::from(self.registry.render()));
+        res.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+        );
+        Ok(res)
+    }
+}
+
+struct Registry {
+    buckets: Box<[f64]>,
+    // series and gauges of all workers. lock is only taken when a worker sees a new label set and when
+    // metrics are rendered.
+    series: Mutex<Vec<Arc<series::Series>>>,
+    in_flight: Mutex<Vec<Arc<series::Gauge>>>,
+}
+
+impl Registry {
+    fn render(&self) -> String {
+        let mut buf = String::new();
+        self.render_http(&mut buf).expect("writing to String must not fail");
+        #[cfg(feature = "__server")]
+        render_server(&mut buf).expect("writing to String must not fail");
+        buf
+    }
+
+    fn render_http(&self, buf: &mut String) -> fmt::Result {
+        let aggregated = series::aggregate(&self.series.lock().unwrap(), self.buckets.len());
+
+        writeln!(buf, "# HELP http_requests_total Total number of http requests.")?;
+        writeln!(buf, "# TYPE http_requests_total counter")?;
+        for (labels, s) in aggregated.iter() {
+            writeln!(buf, "http_requests_total{{{labels}}} {}", s.count)?;
+        }
+
+        writeln!(
+            buf,
+            "# HELP http_request_duration_seconds Latency of http requests in seconds."
+        )?;
+        writeln!(buf, "# TYPE http_request_duration_seconds histogram")?;
+        for (labels, s) in aggregated.iter() {
+            let mut cumulative = 0;
+            for (le, count) in self.buckets.iter().zip(s.buckets.iter()) {
+                cumulative += count;
+                writeln!(
+                    buf,
+                    "http_request_duration_seconds_bucket{{{labels},le=\"{le}\"}} {cumulative}"
+                )?;
+            }
+            writeln!(
+                buf,
+                "http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                s.count
+            )?;
+            writeln!(buf, "http_request_duration_seconds_sum{{{labels}}} {}", s.sum)?;
+            writeln!(buf, "http_request_duration_seconds_count{{{labels}}} {}", s.count)?;
+        }
+
+        let in_flight = self.in_flight.lock().unwrap().iter().map(|g| g.get()).sum::<u64>();
+        writeln!(
+            buf,
+            "# HELP http_requests_in_flight Number of http requests being served."
+        )?;
+        writeln!(buf, "# TYPE http_requests_in_flight gauge")?;
+        writeln!(buf, "http_requests_in_flight {in_flight}")
+    }
+}
+
+#[cfg(feature = "__server")]
+fn render_server(buf: &mut String) -> fmt::Result {
+    let workers = xitca_server::metrics::workers();
+
+    type Value = fn(&xitca_server::metrics::WorkerMetrics) -> u64;
+
+    let metrics: [(&str, &str, &str, Value); 3] = [
+        (
+            "xitca_server_connections_accepted_total",
+            "counter",
+            "Total number of accepted connections.",
+            |w| w.accepted(),
+        ),
+        (
+            "xitca_server_connections_active",
+            "gauge",
+            "Number of connections being served.",
+            |w| w.active(),
+        ),
+        (
+            "xitca_server_accept_errors_total",
+            "counter",
+            "Total number of errors occurred when accepting connections.",
+            |w| w.accept_errors(),
+        ),
+    ];
+
+    for (name, ty, help, value) in metrics {
+        writeln!(buf, "# HELP {name} {help}")?;
+        writeln!(buf, "# TYPE {name} {ty}")?;
+        for worker in workers.iter() {
+            writeln!(
+                buf,
+                "{name}{{worker=\"{}\"}} {}",
+                series::Escape(worker.name()),
+                value(worker)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+mod series {
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    use std::collections::BTreeMap;
+
+    use crate::http::Method;
+
+    use super::*;
+
+    // label set of a series.
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    pub(super) struct Key {
+        pub(super) route: MatchedPath,
+        pub(super) method: &'static str,
+        pub(super) status: u16,
+    }
+
+    // extension method is sent by client at will. it's collapsed into one label so client can't grow
+    // the number of series.
+    pub(super) fn method_label(method: &Method) -> &'static str {
+        match *method {
+            Method::GET => "GET",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::DELETE => "DELETE",
+            Method::HEAD => "HEAD",
+            Method::OPTIONS => "OPTIONS",
+            Method::CONNECT => "CONNECT",
+            Method::PATCH => "PATCH",
+            Method::TRACE => "TRACE",
+            _ => "OTHER",
+        }
+    }
+
+    pub(super) struct Series {
+        key: Key,
+        count: AtomicU64,
+        sum_nanos: AtomicU64,
+        buckets: Box<[AtomicU64]>,
+    }
+
+    impl Series {
+        pub(super) fn new(key: Key, buckets: usize) -> Self {
+            Self {
+                key,
+                count: AtomicU64::new(0),
+                sum_nanos: AtomicU64::new(0),
+                buckets: (0..buckets).map(|_| AtomicU64::new(0)).collect(),
+            }
+        }
+
+        // record a request with index of the first bucket it fits in. index out of range only counts
+        // toward +Inf bucket.
+        pub(super) fn observe(&self, bucket: usize, nanos: u64) {
+            self.count.fetch_add(1, Ordering::Relaxed);
+            self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+            if let Some(b) = self.buckets.get(bucket) {
+                b.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub(super) struct Gauge(AtomicU64);
+
+    impl Gauge {
+        pub(super) fn inc(&self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+
+        pub(super) fn dec(&self) {
+            self.0.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        pub(super) fn get(&self) -> u64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    pub(super) struct Aggregated {
+        pub(super) count: u64,
+        pub(super) sum: f64,
+        pub(super) buckets: Vec<u64>,
+    }
+
+    // merge series of the same label set from all workers. the map is ordered by rendered labels.
+    pub(super) fn aggregate(series: &[Arc<Series>], buckets: usize) -> BTreeMap<String, Aggregated> {
+        let mut map = BTreeMap::new();
+        for s in series {
+            let route = s.key.route.get().unwrap_or(UNMATCHED);
+            let labels = format!(
+                "method=\"{}\",route=\"{}\",status=\"{}\"",
+                s.key.method,
+                Escape(route),
+                s.key.status
+            );
+            let agg = map.entry(labels).or_insert_with(|| Aggregated {
+                count: 0,
+                sum: 0.0,
+                buckets: vec![0; buckets],
+            });
+            agg.count += s.count.load(Ordering::Relaxed);
+            agg.sum += s.sum_nanos.load(Ordering::Relaxed) as f64 / 1e9;
+            for (agg, b) in agg.buckets.iter_mut().zip(s.buckets.iter()) {
+                *agg += b.load(Ordering::Relaxed);
+            }
+        }
+        map
+    }
+
+    // escape label value according to prometheus text format.
+    pub(super) struct Escape<'a>(pub(super) &'a str);
+
+    impl fmt::Display for Escape<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for c in self.0.chars() {
+                match c {
+                    '\\' => f.write_str("\\\\")?,
+                    '"' => f.write_str("\\\"")?,
+                    '\n' => f.write_str("\\n")?,
+                    c => f.write_char(c)?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+mod service {
+    use core::cell::RefCell;
+
+    use std::{collections::HashMap, time::Instant};
+
+    use crate::service::ready::ReadyService;
+
+    use super::{
+        series::{Gauge, Key, Series, method_label},
+        *,
+    };
+
+    pub struct MetricsMiddleware<S> {
+        service: S,
+        registry: Arc<Registry>,
+        // series of current worker. new label set is registered to registry on first sight.
+        series: RefCell<HashMap<Key, Arc<Series>>>,
+        in_flight: Arc<Gauge>,
+    }
+
+    impl<S> MetricsMiddleware<S> {
+        pub(super) fn new(service: S, registry: Arc<Registry>) -> Self {
+            let in_flight = Arc::new(Gauge::default());
+            registry.in_flight.lock().unwrap().push(in_flight.clone());
+            Self {
+                service,
+                registry,
+                series: RefCell::new(HashMap::new()),
+                in_flight,
+            }
+        }
+
+        fn observe(&self, key: Key, start: Instant) {
+            let elapsed = start.elapsed();
+            let secs = elapsed.as_secs_f64();
+            let bucket = self
+                .registry
+                .buckets
+                .iter()
+                .position(|le| secs <= *le)
+                .unwrap_or(self.registry.buckets.len());
+            let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+
+            let mut series = self.series.borrow_mut();
+            let s = series.entry(key).or_insert_with_key(|key| {
+                let s = Arc::new(Series::new(key.clone(), self.registry.buckets.len()));
+                self.registry.series.lock().unwrap().push(s.clone());
+                s
+            });
+            s.observe(bucket, nanos);
+        }
+    }
+
+    // error is rendered into response to obtain it's status which requires default request body type.
+    impl<'r, C, S> Service<WebContext<'r, C>> for MetricsMiddleware<S>
+    where
+        C: 'static,
+        S: for<'r2> Service<WebContext<'r2, C>, Response = WebResponse, Error = Error>,
+    {
+        type Response = WebResponse;
+        type Error = Error;
+
+        async fn call(&self, mut ctx: WebContext<'r, C>) -> Result<Self::Response, Self::Error> {
+            let start = Instant::now();
+            let method = method_label(ctx.req().method());
+
+            let res = {
+                let _guard = InFlight::new(&self.in_flight);
+                self.service.call(ctx.reborrow()).await
+            };
+
+            // router update matched path of request which is kept in context after response is produced.
+            let route = ctx.req().body().matched_path().clone();
+
+            let res = match res {
+                Ok(res) => res,
+                Err(e) => match e.call(ctx).await {
+                    Ok(res) => res,
+                    Err(i) => match i {},
+                },
+            };
+
+            let key = Key {
+                route,
+                method,
+                status: res.status().as_u16(),
+            };
+            self.observe(key, start);
+
+            Ok(res)
+        }
+    }
+
+    impl<S> ReadyService for MetricsMiddleware<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    // decrease in flight gauge when request is finished or cancelled.
+    struct InFlight<'a>(&'a Gauge);
+
+    impl<'a> InFlight<'a> {
+        fn new(gauge: &'a Gauge) -> Self {
+            gauge.inc();
+            Self(gauge)
+        }
+    }
+
+    impl Drop for InFlight<'_> {
+        fn drop(&mut self) {
+            self.0.dec();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::handler_service,
+        http::{Method, StatusCode, Uri, WebRequest},
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[test]
+    fn record_and_render() {
+        let metrics = Metrics::with_buckets([0.5, 1.0]);
+
+        let service = App::new()
+            .at("/users/{id}", handler_service(|| async { "user" }))
+            .at("/metrics", metrics.service())
+            .enclosed(metrics.clone())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        for (path, status) in [
+            ("/users/1", StatusCode::OK),
+            ("/users/2", StatusCode::OK),
+            ("/not_found", StatusCode::NOT_FOUND),
+        ] {
+            let mut req = WebRequest::default();
+            *req.uri_mut() = Uri::from_static(path);
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), status);
+        }
+
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static("/metrics");
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+
+        assert!(body.contains(r#"http_requests_total{method="GET",route="/users/{id}",status="200"} 2"#));
+        assert!(body.contains(r#"http_requests_total{method="GET",route="<unmatched>",status="404"} 1"#));
+        assert!(body.contains(
+            r#"http_request_duration_seconds_bucket{method="GET",route="/users/{id}",status="200",le="0.5"} 2"#
+        ));
+        assert!(body.contains(
+            r#"http_request_duration_seconds_bucket{method="GET",route="/users/{id}",status="200",le="+Inf"} 2"#
+        ));
+        assert!(
+            body.contains(r#"http_request_duration_seconds_count{method="GET",route="/users/{id}",status="200"} 2"#)
+        );
+        // the metrics request itself is in flight while rendering.
+        assert!(body.contains("http_requests_in_flight 1"));
+
+        assert_eq!(metrics.render().matches("http_requests_total{").count(), 3);
+
+        // extension methods share the same label.
+        for method in ["AAAA", "AAAB"] {
+            let mut req = WebRequest::default();
+            *req.method_mut() = Method::from_bytes(method.as_bytes()).unwrap();
+            *req.uri_mut() = Uri::from_static("/not_found");
+            service.call(req).now_or_panic().unwrap();
+        }
+        let body = metrics.render();
+        assert!(body.contains(r#"http_requests_total{method="OTHER",route="<unmatched>",status="404"} 2"#));
+        assert_eq!(body.matches("http_requests_total{").count(), 4);
+    }
+
+    #[test]
+    fn escape() {
+        let escaped = series::Escape("a\"b\\c\nd").to_string();
+        assert_eq!(escaped, r#"a\"b\\c\nd"#);
+    }
+}
@@ -293,18 +293,35 @@
 //! [`RequestBody`]: crate::body::RequestBody
 //! [`WebResponse<B>`]: crate::http::WebResponse
 
+#[cfg(feature = "auth")]
+pub mod auth;
 #[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
 pub mod compress;
+#[cfg(feature = "csrf")]
+pub mod csrf;
 #[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
 pub mod decompress;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 #[cfg(feature = "rate-limit")]
 pub mod rate_limit;
+#[cfg(feature = "security-headers")]
+pub mod security_headers;
+#[cfg(feature = "session")]
+pub mod session;
 #[cfg(not(target_family = "wasm"))]
 pub mod sync;
+#[cfg(feature = "timeout")]
+pub mod timeout;
 #[cfg(feature = "tower-http-compat")]
 pub mod tower_http_compat;
 
+#[cfg(any(feature = "session", feature = "csrf", feature = "security-headers"))]
+pub(crate) mod util;
+
+pub mod cors;
 pub mod eraser;
+pub mod etag;
 pub mod limit;
 
 #[cfg(feature = "logger")]
@@ -317,7 +334,7 @@ mod context;
 
 pub use catch_unwind::CatchUnwind;
 pub use context::WebContext;
-pub use xitca_http::util::middleware::Extension;
+pub use xitca_http::util::middleware::{Extension, client_ip::ClientIpResolver, request_trace::RequestTrace};
 pub use xitca_service::middleware::{AsyncFn, Group, UncheckedReady};
 
 #[cfg(test)]
@@ -335,6 +352,30 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn request_trace() {
+        use crate::handler::request_id::RequestId;
+
+        async fn root(id: RequestId) -> String {
+            id.to_string()
+        }
+
+        let res = App::new()
+            .at("/", handler_service(root))
+            .enclosed(RequestTrace::new())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap()
+            .call(Request::new(RequestExt::<RequestBody>::default()))
+            .now_or_panic()
+            .unwrap();
+
+        let id = res.headers().get("x-request-id").unwrap().clone();
+        let string = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(id, string.as_str());
+    }
+
     #[test]
     fn extension() {
         async fn root(ExtensionRef(ext): ExtensionRef<'_, String>) -> String {
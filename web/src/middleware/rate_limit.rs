@@ -1,12 +1,18 @@
-//! client ip address based rate limiting.
+//! rate limiting based on client ip address or custom key.
 
-use core::time::Duration;
+use core::{hash::Hash, net::IpAddr, time::Duration};
 
-use http_rate::Quota;
+use std::collections::HashSet;
 
-use crate::service::Service;
+pub use http_rate::{AsyncStateStore, EvictionPolicy, HeaderStyle, Quota};
 
-/// builder for client ip address based rate limiting middleware.
+use crate::{handler::client_ip::ClientIp, http::WebRequest, service::Service};
+
+/// builder for rate limiting middleware.
+///
+/// by default request is keyed on client ip address. forwarding headers are not trusted and peer address
+/// of connection is used unless [ClientIpResolver] middleware is enclosed outside of RateLimit, in which
+/// case it's resolved [ClientIp] is used.
 ///
 /// # Examples
 /// ```rust
@@ -17,7 +23,22 @@ use crate::service::Service;
 ///     // rate limit to 60 rps for one ip address.
 ///     .enclosed(RateLimit::per_minute(60));
 /// ```
-pub struct RateLimit(Quota);
+///
+/// [ClientIp]: crate::handler::client_ip::ClientIp
+/// [ClientIpResolver]: crate::middleware::ClientIpResolver
+pub struct RateLimit<
+    K = IpAddr,
+    F = fn(&WebRequest<()>) -> IpAddr,
+    SF = Box<dyn Fn(Quota) -> service::MemoryStore<K> + Send + Sync>,
+> {
+    quota: Quota,
+    groups: Vec<(&'static str, Quota)>,
+    key: F,
+    exempt: HashSet<K>,
+    eviction: EvictionPolicy,
+    style: HeaderStyle,
+    store: SF,
+}
 
 macro_rules! constructor {
     ($method: tt) => {
@@ -27,7 +48,7 @@ macro_rules! constructor {
         /// # Panics
         /// - When max_burst is zero.
         pub fn $method(max_burst: u32) -> Self {
-            Self(Quota::$method(max_burst))
+            Self::new(Quota::$method(max_burst))
         }
     };
 }
@@ -43,24 +64,244 @@ impl RateLimit {
     /// # Panics
     /// - When the Duration is zero.
     pub fn with_period(replenish_1_per: Duration) -> Self {
-        Self(Quota::with_period(replenish_1_per).unwrap())
+        Self::new(Quota::with_period(replenish_1_per).unwrap())
+    }
+
+    fn new(quota: Quota) -> Self {
+        let eviction = EvictionPolicy::default();
+        Self {
+            quota,
+            groups: Vec::new(),
+            key: client_ip,
+            exempt: HashSet::new(),
+            store: memory_store(eviction.clone()),
+            eviction,
+            style: HeaderStyle::default(),
+        }
+    }
+}
+
+fn client_ip(req: &WebRequest<()>) -> IpAddr {
+    ClientIp::from_extensions(req.extensions(), req.body().socket_addr()).ip()
+}
+
+// default store keeping rate limit state in memory of current process.
+fn memory_store<K>(eviction: EvictionPolicy) -> Box<dyn Fn(Quota) -> service::MemoryStore<K> + Send + Sync>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+{
+    Box::new(move |quota| service::MemoryStore::new(http_rate::RateLimit::keyed(quota).with_eviction(eviction.clone())))
+}
+
+impl<K, F> RateLimit<K, F>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+{
+    /// Set eviction policy of rate limit state. By default stale keys are swept every 60 seconds and the
+    /// number of keys is not bounded. Bounding the number of keys prevents a scan from large number of
+    /// source addresses from exhausting memory.
+    ///
+    /// When middleware is constructed inside tokio runtime(e.g. by worker of [HttpServer]) stale keys are
+    /// swept by a timer task running on the runtime. Otherwise sweeping is driven by rate limit checks.
+    ///
+    /// [HttpServer]: crate::HttpServer
+    /// Eviction policy only applies to the default in memory state store. See [RateLimit::store] for
+    /// using other state store.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, middleware::rate_limit::{EvictionPolicy, RateLimit}, App, WebContext};
+    /// let eviction = EvictionPolicy::new().max_keys(100_000);
+    ///
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .enclosed(RateLimit::per_second(10).eviction(eviction.clone()));
+    ///
+    /// // total number of evicted keys can be observed from the policy.
+    /// assert_eq!(eviction.evicted(), 0);
+    /// ```
+    pub fn eviction(mut self, eviction: EvictionPolicy) -> Self {
+        self.store = memory_store(eviction.clone());
+        self.eviction = eviction;
+        self
     }
 }
 
-impl<S, E> Service<Result<S, E>> for RateLimit {
-    type Response = service::RateLimitService<S>;
+impl<K, F, SF> RateLimit<K, F, SF>
+where
+    K: Clone + Hash + Eq,
+{
+    /// Change how request is keyed with given key extraction function. requests with the same key
+    /// share the same quota. previously exempted keys and state store are discarded as key type can be
+    /// changed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, middleware::rate_limit::RateLimit, App, WebContext};
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .enclosed(
+    ///         RateLimit::per_second(10)
+    ///             // rate limit per api key and method. request without api key share the same quota.
+    ///             .key(|req| {
+    ///                 let key = req.headers().get("x-api-key").map(|v| v.as_bytes().to_vec());
+    ///                 (key, req.method().clone())
+    ///             }),
+    ///     );
+    /// ```
+    pub fn key<K2, F2>(self, key: F2) -> RateLimit<K2, F2>
+    where
+        F2: Fn(&WebRequest<()>) -> K2,
+        K2: Clone + Hash + Eq + Send + Sync + 'static,
+    {
+        RateLimit {
+            quota: self.quota,
+            groups: self.groups,
+            key,
+            exempt: HashSet::new(),
+            store: memory_store(self.eviction.clone()),
+            eviction: self.eviction,
+            style: self.style,
+        }
+    }
+
+    /// Change where rate limit state is kept with given constructor of [AsyncStateStore]. constructor is
+    /// called with the quota the builder is constructed with and quota of every [RateLimit::group]. By
+    /// default state is kept in memory of current process.
+    ///
+    /// Error of state store is converted into [Error](crate::error::Error) and returned as response.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use std::thread;
+    /// # use http_rate::remote::{RemoteStore, Server};
+    /// # use xitca_web::{handler::handler_service, middleware::rate_limit::RateLimit, App, WebContext};
+    /// // limiter process shared by multiple replicas of service.
+    /// let server = Server::bind("127.0.0.1:0").unwrap();
+    /// let addr = server.local_addr().unwrap();
+    /// thread::spawn(move || server.run());
+    ///
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .enclosed(RateLimit::per_second(10).store(move |quota| RemoteStore::new(addr, quota)));
+    /// ```
+    pub fn store<SF2, S>(self, store: SF2) -> RateLimit<K, F, SF2>
+    where
+        SF2: Fn(Quota) -> S,
+        S: AsyncStateStore<K>,
+    {
+        RateLimit {
+            quota: self.quota,
+            groups: self.groups,
+            key: self.key,
+            exempt: self.exempt,
+            eviction: self.eviction,
+            style: self.style,
+            store,
+        }
+    }
+
+    /// Apply a different quota to requests with path starting with given prefix. prefix is matched on
+    /// path segment boundary. e.g. `/api` matches `/api` and `/api/users` but not `/apis`.
+    ///
+    /// groups are checked in the order they are added and the first matching one is used. request not
+    /// matching any group is limited by the quota the builder is constructed with. every group tracks
+    /// it's own quota for a key.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, middleware::rate_limit::{Quota, RateLimit}, App, WebContext};
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .enclosed(
+    ///         RateLimit::per_second(10)
+    ///             // expensive routes get tighter quota.
+    ///             .group("/upload", Quota::per_minute(5))
+    ///             .group("/search", Quota::per_second(2)),
+    ///     );
+    /// ```
+    pub fn group(mut self, prefix: &'static str, quota: Quota) -> Self {
+        self.groups.push((prefix, quota));
+        self
+    }
+
+    /// Set style of rate limit headers added to response. By default legacy `x-ratelimit-*` headers are
+    /// used. Response of client ran out of quota carries `retry-after` header regardless of style.
+    ///
+    /// With [HeaderStyle::Ietf] successful response carries `ratelimit-limit`, `ratelimit-remaining`,
+    /// `ratelimit-reset` and `ratelimit-policy` headers.
+    ///
+    /// Error of rate limited request renders response with the style and [http_rate::TooManyRequests] can be
+    /// obtained from it's [source](std::error::Error::source).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, middleware::rate_limit::{HeaderStyle, RateLimit}, App, WebContext};
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .enclosed(RateLimit::per_second(10).header_style(HeaderStyle::Ietf));
+    /// ```
+    pub fn header_style(mut self, style: HeaderStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Exempt given key from rate limiting. request with exempted key bypass rate limiter entirely and
+    /// it's response does not contain rate limit headers.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use core::net::{IpAddr, Ipv4Addr};
+    /// # use xitca_web::{handler::handler_service, middleware::rate_limit::RateLimit, App, WebContext};
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     // requests from localhost are not limited.
+    ///     .enclosed(RateLimit::per_second(10).exempt(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    /// ```
+    pub fn exempt(mut self, key: K) -> Self {
+        self.exempt.insert(key);
+        self
+    }
+}
+
+impl<S, E, K, F, SF, St> Service<Result<S, E>> for RateLimit<K, F, SF>
+where
+    K: Clone + Hash + Eq,
+    F: Clone,
+    SF: Fn(Quota) -> St,
+{
+    type Response = service::RateLimitService<S, K, F, St>;
     type Error = E;
 
     async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
         res.map(|service| service::RateLimitService {
             service,
-            rate_limit: http_rate::RateLimit::new(self.0),
+            key: self.key.clone(),
+            exempt: self.exempt.clone(),
+            style: self.style,
+            rate_limit: (self.store)(self.quota),
+            groups: self
+                .groups
+                .iter()
+                .map(|(prefix, quota)| (*prefix, (self.store)(*quota)))
+                .collect(),
         })
     }
 }
 
 mod service {
-    use core::convert::Infallible;
+    use core::{convert::Infallible, fmt};
+
+    use std::error;
+
+    use http_rate::{RateSnapshot, TooManyRequests};
+    use tokio::task::AbortHandle;
 
     use crate::{
         WebContext,
@@ -70,30 +311,131 @@ mod service {
         service::{Service, ready::ReadyService},
     };
 
-    pub struct RateLimitService<S> {
+    use super::*;
+
+    // rate limit state kept in memory with stale keys swept by a timer task when runtime is available.
+    pub struct MemoryStore<K>
+    where
+        K: Clone + Hash + Eq,
+    {
+        limit: http_rate::RateLimit<K>,
+        sweeper: Option<AbortHandle>,
+    }
+
+    impl<K> MemoryStore<K>
+    where
+        K: Clone + Hash + Eq + Send + Sync + 'static,
+    {
+        pub(super) fn new(limit: http_rate::RateLimit<K>) -> Self {
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    let limit = limit.with_external_sweep();
+                    let task = handle.spawn(sweep(limit.clone()));
+                    Self {
+                        limit,
+                        sweeper: Some(task.abort_handle()),
+                    }
+                }
+                Err(_) => Self { limit, sweeper: None },
+            }
+        }
+    }
+
+    async fn sweep<K>(limit: http_rate::RateLimit<K>)
+    where
+        K: Clone + Hash + Eq,
+    {
+        let interval = limit.sweep_interval();
+        loop {
+            tokio::time::sleep(interval).await;
+            // yield between batches so sweeping does not stall other tasks of the runtime.
+            while !limit.sweep_batch() {
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+
+    impl<K> Drop for MemoryStore<K>
+    where
+        K: Clone + Hash + Eq,
+    {
+        fn drop(&mut self) {
+            if let Some(ref sweeper) = self.sweeper {
+                sweeper.abort();
+            }
+        }
+    }
+
+    impl<K> AsyncStateStore<K> for MemoryStore<K>
+    where
+        K: Clone + Hash + Eq,
+    {
+        type Error = Infallible;
+
+        #[inline]
+        async fn check(&self, key: &K) -> Result<Result<RateSnapshot, TooManyRequests>, Self::Error> {
+            Ok(self.limit.check(key))
+        }
+    }
+
+    pub struct RateLimitService<S, K, F, St>
+    where
+        K: Clone + Hash + Eq,
+    {
         pub(super) service: S,
-        pub(super) rate_limit: http_rate::RateLimit,
+        pub(super) key: F,
+        pub(super) exempt: HashSet<K>,
+        pub(super) style: HeaderStyle,
+        pub(super) rate_limit: St,
+        pub(super) groups: Vec<(&'static str, St)>,
+    }
+
+    impl<S, K, F, St> RateLimitService<S, K, F, St>
+    where
+        K: Clone + Hash + Eq,
+    {
+        fn limiter(&self, path: &str) -> &St {
+            self.groups
+                .iter()
+                .find(|(prefix, _)| match path.strip_prefix(prefix.trim_end_matches('/')) {
+                    Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                    None => false,
+                })
+                .map(|(_, limit)| limit)
+                .unwrap_or(&self.rate_limit)
+        }
     }
 
-    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for RateLimitService<S>
+    impl<'r, C, B, S, ResB, K, F, St> Service<WebContext<'r, C, B>> for RateLimitService<S, K, F, St>
     where
         S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        K: Clone + Hash + Eq,
+        F: Fn(&WebRequest<()>) -> K,
+        St: AsyncStateStore<K>,
+        St::Error: Into<Error>,
     {
         type Response = WebResponse<ResB>;
         type Error = Error;
 
         async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-            let headers = ctx.req().headers();
-            let addr = ctx.req().body().socket_addr();
-            let snap = self.rate_limit.rate_limit(headers, addr).map_err(Error::from_service)?;
+            let key = (self.key)(ctx.req());
+            if self.exempt.contains(&key) {
+                return self.service.call(ctx).await;
+            }
+            let snap = self
+                .limiter(ctx.req().uri().path())
+                .check(&key)
+                .await
+                .map_err(Into::into)?
+                .map_err(|e| Error::from_service(RateLimited { e, style: self.style }))?;
             self.service.call(ctx).await.map(|mut res| {
-                snap.extend_response(&mut res);
+                snap.extend_response_with(&mut res, self.style);
                 res
             })
         }
     }
 
-    impl<'r, C, B> Service<WebContext<'r, C, B>> for http_rate::TooManyRequests {
+    impl<'r, C, B> Service<WebContext<'r, C, B>> for TooManyRequests {
         type Response = WebResponse;
         type Error = Infallible;
 
@@ -104,9 +446,40 @@ mod service {
         }
     }
 
-    impl<S> ReadyService for RateLimitService<S>
+    // error of rate limited request carrying header style of middleware. TooManyRequests is it's source.
+    #[derive(Debug)]
+    struct RateLimited {
+        e: TooManyRequests,
+        style: HeaderStyle,
+    }
+
+    impl fmt::Display for RateLimited {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(&self.e, f)
+        }
+    }
+
+    impl error::Error for RateLimited {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            Some(&self.e)
+        }
+    }
+
+    impl<'r, C, B> Service<WebContext<'r, C, B>> for RateLimited {
+        type Response = WebResponse;
+        type Error = Infallible;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let mut res = ctx.into_response(ResponseBody::empty());
+            self.e.extend_response_with(&mut res, self.style);
+            Ok(res)
+        }
+    }
+
+    impl<S, K, F, St> ReadyService for RateLimitService<S, K, F, St>
     where
         S: ReadyService,
+        K: Clone + Hash + Eq,
     {
         type Ready = S::Ready;
 
@@ -116,3 +489,194 @@ mod service {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use core::net::SocketAddr;
+
+    use std::thread;
+
+    use http_rate::remote::{RemoteStore, Server};
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App, WebContext,
+        error::Error,
+        handler::handler_service,
+        http::{
+            StatusCode, Uri, WebResponse,
+            header::{HeaderValue, RETRY_AFTER},
+        },
+    };
+
+    use super::*;
+
+    fn request(path: &'static str, key: &'static str) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static(path);
+        req.headers_mut().insert("x-api-key", HeaderValue::from_static(key));
+        req
+    }
+
+    #[test]
+    fn custom_key() {
+        let service = App::new()
+            .at("/", handler_service(|| async { "" }))
+            .at("/upload", handler_service(|| async { "" }))
+            .enclosed(
+                RateLimit::per_minute(2)
+                    .key(|req| req.headers().get("x-api-key").cloned())
+                    .group("/upload", Quota::per_minute(1))
+                    .exempt(Some(HeaderValue::from_static("internal"))),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let status = |path, key| service.call(request(path, key)).now_or_panic().unwrap().status();
+
+        assert_eq!(status("/", "foo"), StatusCode::OK);
+        assert_eq!(status("/", "foo"), StatusCode::OK);
+        let res = service.call(request("/", "foo")).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "30");
+        assert!(res.headers().get("x-ratelimit-after").is_some());
+        // different key has it's own quota.
+        assert_eq!(status("/", "bar"), StatusCode::OK);
+
+        // group has it's own quota.
+        assert_eq!(status("/upload", "foo"), StatusCode::OK);
+        assert_eq!(status("/upload", "foo"), StatusCode::TOO_MANY_REQUESTS);
+
+        // exempted key is not limited and rate limit headers are not added.
+        for _ in 0..4 {
+            let res = service.call(request("/upload", "internal")).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(res.headers().get("x-ratelimit-limit").is_none());
+        }
+    }
+
+    async fn remote_app(addr: SocketAddr) -> impl Service<WebRequest, Response = WebResponse<impl Sized>> {
+        App::new()
+            .at("/", handler_service(|| async { "" }))
+            .enclosed(
+                RateLimit::per_minute(2)
+                    .key(|req| req.headers().get("x-api-key").unwrap().to_str().unwrap().to_owned())
+                    .store(move |quota| RemoteStore::new(addr, quota)),
+            )
+            .finish()
+            .call(())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn timer_sweep() {
+        let eviction = EvictionPolicy::new().sweep_interval(Duration::from_millis(50));
+
+        let service = App::new()
+            .at("/", handler_service(|| async { "" }))
+            .enclosed(
+                RateLimit::per_second(1000)
+                    .key(|req| req.headers().get("x-api-key").cloned())
+                    .eviction(eviction.clone()),
+            )
+            .finish()
+            .call(())
+            .await
+            .unwrap();
+
+        for key in ["foo", "bar"] {
+            let res = service.call(request("/", key)).await.ok().unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+        assert_eq!(eviction.evicted(), 0);
+
+        // stale keys are swept by timer task without any rate limit check.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(eviction.evicted(), 2);
+    }
+
+    #[tokio::test]
+    async fn remote_store() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        // two replicas of service sharing the same limiter process.
+        let service1 = remote_app(addr).await;
+        let service2 = remote_app(addr).await;
+
+        let res = service1.call(request("/", "foo")).await.ok().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "1");
+
+        let res = service2.call(request("/", "foo")).await.ok().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "0");
+
+        // quota is exhausted on both replicas.
+        for service in [&service1, &service2] {
+            let res = service.call(request("/", "foo")).await.ok().unwrap();
+            assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        }
+
+        // different key has it's own quota.
+        let res = service2.call(request("/", "bar")).await.ok().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn remote_store_error() {
+        // limiter process is not reachable.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let service = remote_app(addr).await;
+        let res = service.call(request("/", "foo")).await.ok().unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    async fn too_many_requests<S, C, B, Res>(service: &S, ctx: WebContext<'_, C, B>) -> Result<Res, Error>
+    where
+        S: for<'r> Service<WebContext<'r, C, B>, Response = Res, Error = Error>,
+    {
+        service.call(ctx).await.inspect_err(|e| {
+            // error is sourced from TooManyRequests regardless of header style.
+            let source = e.upcast().source().unwrap();
+            assert!(source.downcast_ref::<http_rate::TooManyRequests>().is_some());
+        })
+    }
+
+    #[test]
+    fn ietf_headers() {
+        let service = App::new()
+            .at("/", handler_service(|| async { "" }))
+            .enclosed(
+                RateLimit::per_minute(1)
+                    .key(|req| req.headers().get("x-api-key").cloned())
+                    .header_style(HeaderStyle::Ietf),
+            )
+            .enclosed_fn(too_many_requests)
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(request("/", "foo")).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("ratelimit-limit").unwrap(), "1");
+        assert_eq!(res.headers().get("ratelimit-remaining").unwrap(), "0");
+        assert_eq!(res.headers().get("ratelimit-reset").unwrap(), "60");
+        assert_eq!(res.headers().get("ratelimit-policy").unwrap(), "1;w=60");
+        assert!(res.headers().get("x-ratelimit-limit").is_none());
+
+        let res = service.call(request("/", "foo")).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "60");
+        assert!(res.headers().get("x-ratelimit-after").is_none());
+    }
+}
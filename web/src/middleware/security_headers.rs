@@ -0,0 +1,423 @@
+//! security related response headers middleware.
+
+use core::time::Duration;
+
+use std::sync::Arc;
+
+use crate::{
+    WebContext,
+    error::{Error, ExtensionNotFound},
+    handler::FromRequest,
+    http::header::{
+        CONTENT_SECURITY_POLICY, HeaderName, HeaderValue, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+        X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+    },
+    middleware::util::random_hex,
+    service::Service,
+};
+
+const PERMISSIONS_POLICY: HeaderName = HeaderName::from_static("permissions-policy");
+
+const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+/// builder for security related response headers middleware.
+///
+/// by default following headers are appended to response:
+/// - `Strict-Transport-Security: max-age=31536000; includeSubDomains`
+/// - `X-Content-Type-Options: nosniff`
+/// - `X-Frame-Options: DENY`
+/// - `Referrer-Policy: strict-origin-when-cross-origin`
+///
+/// `Content-Security-Policy` and `Permissions-Policy` are opt-in.
+///
+/// header already present in response is not overwritten. which means middleware enclosing a route or
+/// header set by handler always takes priority over the middleware enclosing application. header removed
+/// with [SecurityHeaders::disable] by middleware enclosing a route is not filled by outer middleware either.
+///
+/// error returned by enclosed service is rendered into response by the middleware so error response
+/// carries these headers too.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, html::Html},
+/// #   http::header::HeaderValue,
+/// #   middleware::security_headers::{CspNonce, SecurityHeaders},
+/// #   route::get,
+/// #   service::ServiceExt,
+/// #   App, WebContext
+/// # };
+/// async fn index(nonce: CspNonce) -> Html<String> {
+///     Html(format!(r#"<script nonce="{}">console.log("hello,world!")</script>"#, nonce.as_str()))
+/// }
+///
+/// App::new()
+///     .at("/", get(handler_service(index)))
+///     // allow the page to be framed by same origin site.
+///     .at(
+///         "/frame",
+///         get(handler_service(|| async { "framed" })
+///             .enclosed(SecurityHeaders::new().frame_options(HeaderValue::from_static("SAMEORIGIN")))),
+///     )
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(SecurityHeaders::new().content_security_policy("script-src {nonce} 'strict-dynamic'"));
+/// ```
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    headers: Vec<(HeaderName, HeaderValue)>,
+    csp: Option<Box<str>>,
+    disabled: Vec<HeaderName>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecurityHeaders {
+    /// construct a new middleware builder with default headers.
+    pub fn new() -> Self {
+        Self {
+            headers: vec![
+                (
+                    STRICT_TRANSPORT_SECURITY,
+                    HeaderValue::from_static("max-age=31536000; includeSubDomains"),
+                ),
+                (X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")),
+                (X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+                (
+                    REFERRER_POLICY,
+                    HeaderValue::from_static("strict-origin-when-cross-origin"),
+                ),
+            ],
+            csp: None,
+            disabled: Vec::new(),
+        }
+    }
+
+    /// set `Strict-Transport-Security` header.
+    pub fn strict_transport_security(self, max_age: Duration, include_sub_domains: bool, preload: bool) -> Self {
+        let mut value = format!("max-age={}", max_age.as_secs());
+        if include_sub_domains {
+            value.push_str("; includeSubDomains");
+        }
+        if preload {
+            value.push_str("; preload");
+        }
+        let value = HeaderValue::try_from(value).expect("Strict-Transport-Security must be valid HeaderValue");
+        self.header(STRICT_TRANSPORT_SECURITY, value)
+    }
+
+    /// set `Content-Security-Policy` header.
+    ///
+    /// when policy contains `{nonce}` placeholder a random nonce is generated for every request. the placeholder
+    /// is replaced with `'nonce-<value>'` source expression and the value can be extracted with [CspNonce].
+    ///
+    /// # Panics
+    /// when policy contains character not allowed in header value.
+    pub fn content_security_policy(mut self, policy: &str) -> Self {
+        HeaderValue::try_from(policy).expect("Content-Security-Policy must be valid HeaderValue");
+        self.csp = Some(Box::from(policy));
+        self.disabled.retain(|n| *n != CONTENT_SECURITY_POLICY);
+        self
+    }
+
+    /// set `X-Frame-Options` header. value is usually `DENY` or `SAMEORIGIN`.
+    pub fn frame_options(self, value: HeaderValue) -> Self {
+        self.header(X_FRAME_OPTIONS, value)
+    }
+
+    /// set `Referrer-Policy` header.
+    pub fn referrer_policy(self, value: HeaderValue) -> Self {
+        self.header(REFERRER_POLICY, value)
+    }
+
+    /// set `Permissions-Policy` header.
+    pub fn permissions_policy(self, value: HeaderValue) -> Self {
+        self.header(PERMISSIONS_POLICY, value)
+    }
+
+    /// remove header with given name from middleware. `Content-Security-Policy` is removed when it's name
+    /// is given.
+    ///
+    /// the header is also kept out of response by [SecurityHeaders] middleware enclosing this one.
+    pub fn disable(mut self, name: HeaderName) -> Self {
+        if name == CONTENT_SECURITY_POLICY {
+            self.csp = None;
+        }
+        self.headers.retain(|(n, _)| *n != name);
+        if !self.disabled.contains(&name) {
+            self.disabled.push(name);
+        }
+        self
+    }
+
+    fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.disabled.retain(|n| *n != name);
+        match self.headers.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.headers.push((name, value)),
+        }
+        self
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for SecurityHeaders {
+    type Response = service::SecurityHeadersService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        let csp = self
+            .csp
+            .as_deref()
+            .map(|policy| match policy.contains(NONCE_PLACEHOLDER) {
+                true => service::Csp::Nonce(Box::from(policy)),
+                false => service::Csp::Static(HeaderValue::try_from(policy).unwrap()),
+            });
+        let inner = Arc::new(service::Inner {
+            headers: self.headers.clone(),
+            csp,
+            disabled: self.disabled.clone(),
+        });
+        res.map(|service| service::SecurityHeadersService { service, inner })
+    }
+}
+
+/// per request nonce for `Content-Security-Policy`. available when [SecurityHeaders] middleware is applied
+/// with policy containing `{nonce}` placeholder.
+#[derive(Clone, Debug)]
+pub struct CspNonce(Arc<str>);
+
+impl CspNonce {
+    fn generate() -> Self {
+        Self(Arc::from(random_hex::<16>()))
+    }
+
+    /// get nonce as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for CspNonce {
+    type Type<'b> = Self;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        ctx.req()
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| Error::from(ExtensionNotFound::from_type::<Self>()))
+    }
+}
+
+// names of headers disabled by middleware enclosing route. stored in response extensions and outer
+// middleware would not fill them.
+#[derive(Clone)]
+struct Disabled(Vec<HeaderName>);
+
+mod service {
+    use core::convert::Infallible;
+
+    use crate::{http::WebResponse, service::ready::ReadyService};
+
+    use super::*;
+
+    pub struct SecurityHeadersService<S> {
+        pub(super) service: S,
+        pub(super) inner: Arc<Inner>,
+    }
+
+    pub(super) struct Inner {
+        pub(super) headers: Vec<(HeaderName, HeaderValue)>,
+        pub(super) csp: Option<Csp>,
+        pub(super) disabled: Vec<HeaderName>,
+    }
+
+    pub(super) enum Csp {
+        Static(HeaderValue),
+        // policy with nonce placeholder.
+        Nonce(Box<str>),
+    }
+
+    impl<'r, C, B, S, ResB, Err> Service<WebContext<'r, C, B>> for SecurityHeadersService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Err>,
+        Err: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Infallible>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Err;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let csp = self.inner.csp.as_ref().map(|csp| {
+                let policy = match csp {
+                    Csp::Static(value) => return value.clone(),
+                    Csp::Nonce(policy) => policy,
+                };
+
+                // reuse nonce from outer middleware so all policies of the same request agree on one value.
+                let extensions = ctx.req_mut().extensions_mut();
+                let nonce = match extensions.get::<CspNonce>() {
+                    Some(nonce) => nonce.clone(),
+                    None => {
+                        let nonce = CspNonce::generate();
+                        extensions.insert(nonce.clone());
+                        nonce
+                    }
+                };
+
+                let policy = policy.replace(NONCE_PLACEHOLDER, &format!("'nonce-{}'", nonce.as_str()));
+                HeaderValue::try_from(policy).unwrap()
+            });
+
+            // error is rendered into response so it carries security headers too.
+            let mut res = match self.service.call(ctx.reborrow()).await {
+                Ok(res) => res,
+                Err(e) => match e.call(ctx).await {
+                    Ok(res) => res,
+                    Err(i) => match i {},
+                },
+            };
+
+            // take disabled names from inner middleware and pass them on together with this middleware's.
+            let mut disabled = res
+                .extensions_mut()
+                .remove::<Disabled>()
+                .map(|d| d.0)
+                .unwrap_or_default();
+
+            let headers = res.headers_mut();
+
+            for (name, value) in self.inner.headers.iter() {
+                if !headers.contains_key(name) && !disabled.contains(name) {
+                    headers.insert(name.clone(), value.clone());
+                }
+            }
+
+            if let Some(csp) = csp {
+                if !headers.contains_key(CONTENT_SECURITY_POLICY) && !disabled.contains(&CONTENT_SECURITY_POLICY) {
+                    headers.insert(CONTENT_SECURITY_POLICY, csp);
+                }
+            }
+
+            disabled.extend(self.inner.disabled.iter().cloned());
+            if !disabled.is_empty() {
+                res.extensions_mut().insert(Disabled(disabled));
+            }
+
+            Ok(res)
+        }
+    }
+
+    impl<S> ReadyService for SecurityHeadersService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::handler_service,
+        http::{StatusCode, WebRequest},
+        route::get,
+        service::ServiceExt,
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[test]
+    fn headers() {
+        let service = App::new()
+            .at(
+                "/",
+                get(handler_service(
+                    |nonce: CspNonce| async move { nonce.as_str().to_owned() },
+                )),
+            )
+            .at(
+                "/frame",
+                get(handler_service(|| async { "framed" }).enclosed(
+                    SecurityHeaders::new()
+                        .frame_options(HeaderValue::from_static("SAMEORIGIN"))
+                        .disable(STRICT_TRANSPORT_SECURITY),
+                )),
+            )
+            .enclosed(
+                SecurityHeaders::new()
+                    .strict_transport_security(Duration::from_secs(60), false, true)
+                    .content_security_policy("script-src {nonce}")
+                    .permissions_policy(HeaderValue::from_static("camera=()"))
+                    .disable(REFERRER_POLICY),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let headers = res.headers().clone();
+        assert_eq!(headers.get(STRICT_TRANSPORT_SECURITY).unwrap(), "max-age=60; preload");
+        assert_eq!(headers.get(X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert_eq!(headers.get(X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert_eq!(headers.get(PERMISSIONS_POLICY).unwrap(), "camera=()");
+        assert!(headers.get(REFERRER_POLICY).is_none());
+        let nonce = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(nonce.len(), 32);
+        assert_eq!(
+            headers.get(CONTENT_SECURITY_POLICY).unwrap().to_str().unwrap(),
+            format!("script-src 'nonce-{nonce}'")
+        );
+
+        let mut req = WebRequest::default();
+        *req.uri_mut() = "/frame".parse().unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        let headers = res.headers();
+        assert_eq!(headers.get(X_FRAME_OPTIONS).unwrap(), "SAMEORIGIN");
+        // disabled by route middleware and application middleware does not fill it.
+        assert!(headers.get(STRICT_TRANSPORT_SECURITY).is_none());
+        assert_eq!(headers.get(REFERRER_POLICY).unwrap(), "strict-origin-when-cross-origin");
+
+        // error response carries headers too.
+        let mut req = WebRequest::default();
+        *req.uri_mut() = "/not_found".parse().unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let headers = res.headers();
+        assert_eq!(headers.get(STRICT_TRANSPORT_SECURITY).unwrap(), "max-age=60; preload");
+        assert_eq!(headers.get(X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert!(headers.get(CONTENT_SECURITY_POLICY).is_some());
+    }
+
+    #[test]
+    fn static_csp() {
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .enclosed(SecurityHeaders::new().content_security_policy("default-src 'self'"))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(
+            res.headers().get(CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'"
+        );
+    }
+}
@@ -0,0 +1,534 @@
+//! server side session middleware.
+
+use core::{future::Future, time::Duration};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use cookie::{Key, SameSite};
+
+use crate::{
+    error::Error,
+    handler::session::{SessionRecord, unix_now},
+    service::Service,
+};
+
+/// trait for session storage backend.
+///
+/// the `key` passed to methods is the value of session cookie. for server side store it's typically a
+/// random session id. for [CookieStore] it's the serialized session record itself.
+pub trait SessionStore {
+    /// load session record associated with given key. return None when the key is unknown to store.
+    fn load(&self, key: &str) -> impl Future<Output = Result<Option<SessionRecord>, Error>>;
+
+    /// persist session record and return the key it can be loaded with afterwards.
+    /// key is None when session is new or it's id is being rotated.
+    fn save(&self, key: Option<&str>, record: &SessionRecord) -> impl Future<Output = Result<String, Error>>;
+
+    /// remove session record associated with given key.
+    fn remove(&self, key: &str) -> impl Future<Output = Result<(), Error>>;
+}
+
+/// in memory session store. session data is shared between all server threads and lost when process exit.
+///
+/// expired sessions are removed lazily when they are loaded again. [MemoryStore::retain_recent] can be
+/// used for explicit clean up.
+#[derive(Clone, Default)]
+pub struct MemoryStore(Arc<Mutex<HashMap<String, SessionRecord>>>);
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// remove sessions not accessed within given duration.
+    pub fn retain_recent(&self, idle: Duration) {
+        let deadline = unix_now().saturating_sub(idle.as_secs());
+        self.0.lock().unwrap().retain(|_, record| record.accessed >= deadline);
+    }
+
+    /// count of sessions inside store.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// check if store contains no session.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SessionStore for MemoryStore {
+    async fn load(&self, key: &str) -> Result<Option<SessionRecord>, Error> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    async fn save(&self, key: Option<&str>, record: &SessionRecord) -> Result<String, Error> {
+        let key = match key {
+            Some(key) => key.to_owned(),
+            None => super::util::random_hex::<32>(),
+        };
+        self.0.lock().unwrap().insert(key.clone(), record.clone());
+        Ok(key)
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), Error> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// pure cookie session store. session record is serialized and stored in the session cookie itself.
+///
+/// session cookie is signed and can not be tampered with but it's content is readable by client.
+/// browsers typically limit cookie size to 4096 bytes and this store is only suitable for small
+/// amount of session data.
+#[derive(Clone, Copy, Default)]
+pub struct CookieStore;
+
+impl SessionStore for CookieStore {
+    async fn load(&self, key: &str) -> Result<Option<SessionRecord>, Error> {
+        Ok(SessionRecord::from_json(key))
+    }
+
+    async fn save(&self, _: Option<&str>, record: &SessionRecord) -> Result<String, Error> {
+        Ok(record.to_json())
+    }
+
+    async fn remove(&self, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// builder for session middleware. session cookie is signed with given [Key] and session data is
+/// persisted with given [SessionStore].
+///
+/// session can be accessed in handler function with [Session] extractor.
+///
+/// # Examples
+/// ```rust
+/// # use std::time::Duration;
+/// # use xitca_web::{
+/// #   handler::{cookie::Key, handler_service, session::Session},
+/// #   middleware::session::{MemoryStore, Session as SessionMiddleware},
+/// #   App, WebContext
+/// # };
+/// async fn handler(session: Session) -> &'static str {
+///     // renew session id after login to prevent session fixation.
+///     session.renew();
+///     "logged in"
+/// }
+///
+/// App::new()
+///     .at("/login", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(
+///         SessionMiddleware::new(MemoryStore::new(), Key::generate())
+///             // expire session when it's not accessed for 30 minutes.
+///             .idle_timeout(Duration::from_secs(30 * 60))
+///             // expire session 12 hours after it's creation regardless of activity.
+///             .absolute_timeout(Duration::from_secs(12 * 60 * 60))
+///     );
+/// ```
+///
+/// [Session]: crate::handler::session::Session
+pub struct Session<St> {
+    store: St,
+    config: Arc<Config>,
+}
+
+struct Config {
+    key: Key,
+    cookie_name: String,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    same_site: SameSite,
+    idle_timeout: Option<Duration>,
+    absolute_timeout: Option<Duration>,
+}
+
+impl<St> Session<St> {
+    /// construct a new session middleware builder with given store and cookie signing key.
+    pub fn new(store: St, key: impl Into<Key>) -> Self {
+        Self {
+            store,
+            config: Arc::new(Config {
+                key: key.into(),
+                cookie_name: String::from("id"),
+                path: String::from("/"),
+                secure: true,
+                http_only: true,
+                same_site: SameSite::Lax,
+                idle_timeout: None,
+                absolute_timeout: None,
+            }),
+        }
+    }
+
+    /// set name of session cookie. default to "id".
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.config_mut().cookie_name = name.into();
+        self
+    }
+
+    /// set path attribute of session cookie. default to "/".
+    pub fn cookie_path(mut self, path: impl Into<String>) -> Self {
+        self.config_mut().path = path.into();
+        self
+    }
+
+    /// set secure attribute of session cookie. default to true.
+    pub fn cookie_secure(mut self, secure: bool) -> Self {
+        self.config_mut().secure = secure;
+        self
+    }
+
+    /// set http only attribute of session cookie. default to true.
+    pub fn cookie_http_only(mut self, http_only: bool) -> Self {
+        self.config_mut().http_only = http_only;
+        self
+    }
+
+    /// set same site attribute of session cookie. default to [SameSite::Lax].
+    pub fn cookie_same_site(mut self, same_site: SameSite) -> Self {
+        self.config_mut().same_site = same_site;
+        self
+    }
+
+    /// expire session when it's not accessed within given duration.
+    pub fn idle_timeout(mut self, dur: Duration) -> Self {
+        self.config_mut().idle_timeout = Some(dur);
+        self
+    }
+
+    /// expire session when given duration passed since it's creation.
+    pub fn absolute_timeout(mut self, dur: Duration) -> Self {
+        self.config_mut().absolute_timeout = Some(dur);
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut Config {
+        Arc::get_mut(&mut self.config).expect("Session must not be mutated after it's used as middleware")
+    }
+}
+
+impl<S, E, St> Service<Result<S, E>> for Session<St>
+where
+    St: Clone,
+{
+    type Response = service::SessionService<S, St>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::SessionService {
+            service,
+            store: self.store.clone(),
+            config: self.config.clone(),
+        })
+    }
+}
+
+mod service {
+    use cookie::Cookie;
+
+    use crate::{
+        WebContext,
+        handler::session::Session,
+        http::WebResponse,
+        middleware::util::{read_signed_cookie, write_signed_cookie},
+        service::ready::ReadyService,
+    };
+
+    use super::*;
+
+    pub struct SessionService<S, St> {
+        pub(super) service: S,
+        pub(super) store: St,
+        pub(super) config: Arc<Config>,
+    }
+
+    impl Config {
+        pub(super) fn is_expired(&self, record: &SessionRecord, now: u64) -> bool {
+            let expired =
+                |start: u64, dur: Option<Duration>| dur.is_some_and(|dur| now >= start.saturating_add(dur.as_secs()));
+            expired(record.accessed, self.idle_timeout) || expired(record.created, self.absolute_timeout)
+        }
+
+        pub(super) fn cookie(&self, value: String, record: &SessionRecord, now: u64) -> Cookie<'static> {
+            let mut cookie = Cookie::build((self.cookie_name.clone(), value))
+                .path(self.path.clone())
+                .secure(self.secure)
+                .http_only(self.http_only)
+                .same_site(self.same_site)
+                .build();
+
+            let remain = |start: u64, dur: Duration| start.saturating_add(dur.as_secs()).saturating_sub(now);
+            let max_age = match (self.idle_timeout, self.absolute_timeout) {
+                (Some(idle), Some(abs)) => Some(remain(now, idle).min(remain(record.created, abs))),
+                (Some(idle), None) => Some(remain(now, idle)),
+                (None, Some(abs)) => Some(remain(record.created, abs)),
+                (None, None) => None,
+            };
+
+            if let Some(max_age) = max_age {
+                cookie.set_max_age(cookie::time::Duration::seconds(
+                    i64::try_from(max_age).unwrap_or(i64::MAX),
+                ));
+            }
+
+            cookie
+        }
+
+        fn removal_cookie(&self) -> Cookie<'static> {
+            let mut cookie = Cookie::build(self.cookie_name.clone()).path(self.path.clone()).build();
+            cookie.make_removal();
+            cookie
+        }
+    }
+
+    impl<'r, C, B, S, St, ResB> Service<WebContext<'r, C, B>> for SessionService<S, St>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        St: SessionStore,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let config = &*self.config;
+            let now = unix_now();
+
+            let mut key = read_signed_cookie(ctx.req().headers(), &config.key, &config.cookie_name);
+            // a session cookie is sent by client but the session is expired or unknown to store.
+            let mut stale = false;
+
+            let record = match key {
+                Some(ref k) => match self.store.load(k).await? {
+                    Some(record) if !config.is_expired(&record, now) => Some(record),
+                    Some(_) => {
+                        self.store.remove(k).await?;
+                        None
+                    }
+                    None => None,
+                },
+                None => None,
+            };
+
+            let is_new = record.is_none();
+            if is_new {
+                stale = key.take().is_some();
+            }
+
+            let session = Session::new(record.unwrap_or_else(SessionRecord::new));
+            ctx.req_mut().extensions_mut().insert(session.clone());
+
+            let mut res = self.service.call(ctx.reborrow()).await?;
+
+            let (record, changed, renew, purge) = {
+                let mut inner = session.lock();
+                (
+                    core::mem::take(&mut inner.record),
+                    inner.changed,
+                    inner.renew,
+                    inner.purge,
+                )
+            };
+
+            if purge {
+                if let Some(ref key) = key {
+                    self.store.remove(key).await?;
+                }
+                if key.is_some() || stale {
+                    write_signed_cookie(res.headers_mut(), &config.key, config.removal_cookie())?;
+                }
+                return Ok(res);
+            }
+
+            // new session without data does not need to be persisted.
+            if is_new && record.data.is_empty() {
+                if stale {
+                    write_signed_cookie(res.headers_mut(), &config.key, config.removal_cookie())?;
+                }
+                return Ok(res);
+            }
+
+            // session with idle timeout must be touched on every access.
+            if !(changed || renew || is_new || config.idle_timeout.is_some()) {
+                return Ok(res);
+            }
+
+            let mut record = record;
+            record.accessed = now;
+
+            if renew {
+                if let Some(key) = key.take() {
+                    self.store.remove(&key).await?;
+                }
+            }
+
+            let value = self.store.save(key.as_deref(), &record).await?;
+            write_signed_cookie(res.headers_mut(), &config.key, config.cookie(value, &record, now))?;
+
+            Ok(res)
+        }
+    }
+
+    impl<S, St> ReadyService for SessionService<S, St>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::{handler_service, session::Session as SessionExtract},
+        http::{
+            HeaderValue, WebRequest,
+            header::{COOKIE, SET_COOKIE},
+            request,
+        },
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    async fn counter(session: SessionExtract) -> Result<String, Error> {
+        let count = session.get::<u64>("count")?.unwrap_or(0) + 1;
+        session.insert("count", count)?;
+        Ok(count.to_string())
+    }
+
+    async fn logout(session: SessionExtract) -> &'static str {
+        session.purge();
+        "bye"
+    }
+
+    async fn renew(session: SessionExtract) -> &'static str {
+        session.renew();
+        "renew"
+    }
+
+    fn req(path: &'static str, cookie: Option<&HeaderValue>) -> WebRequest {
+        let mut req = request::Builder::default().uri(path).body(Default::default()).unwrap();
+        if let Some(cookie) = cookie {
+            let cookie = cookie.to_str().unwrap().split(';').next().unwrap();
+            req.headers_mut().insert(COOKIE, HeaderValue::from_str(cookie).unwrap());
+        }
+        req
+    }
+
+    fn run<St>(store: St, check_store: impl Fn(usize))
+    where
+        St: SessionStore + Clone + Send + Sync + 'static,
+    {
+        let service = App::new()
+            .at("/", handler_service(counter))
+            .at("/logout", handler_service(logout))
+            .at("/renew", handler_service(renew))
+            .enclosed(Session::new(store, Key::generate()).idle_timeout(Duration::from_secs(60)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(req("/", None)).now_or_panic().unwrap();
+        let cookie = res.headers().get(SET_COOKIE).unwrap().clone();
+        assert!(cookie.to_str().unwrap().contains("Max-Age=60"));
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "1");
+        check_store(1);
+
+        let res = service.call(req("/", Some(&cookie))).now_or_panic().unwrap();
+        let cookie = res.headers().get(SET_COOKIE).unwrap().clone();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "2");
+
+        let res = service.call(req("/renew", Some(&cookie))).now_or_panic().unwrap();
+        let renewed = res.headers().get(SET_COOKIE).unwrap().clone();
+        check_store(1);
+
+        let res = service.call(req("/", Some(&renewed))).now_or_panic().unwrap();
+        let cookie = res.headers().get(SET_COOKIE).unwrap().clone();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "3");
+
+        // tampered cookie is ignored and a new session is created.
+        let tampered = HeaderValue::from_str(&cookie.to_str().unwrap().replacen("id=", "id=x", 1)).unwrap();
+        let res = service.call(req("/", Some(&tampered))).now_or_panic().unwrap();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "1");
+
+        let res = service.call(req("/logout", Some(&cookie))).now_or_panic().unwrap();
+        let removal = res.headers().get(SET_COOKIE).unwrap();
+        assert!(removal.to_str().unwrap().starts_with("id=;"));
+    }
+
+    #[test]
+    fn memory_store() {
+        let store = MemoryStore::new();
+        run(store.clone(), |len| assert_eq!(store.len(), len));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn cookie_store() {
+        run(CookieStore, |_| {});
+    }
+
+    #[test]
+    fn expiry() {
+        let config = Session::new(CookieStore, Key::generate())
+            .idle_timeout(Duration::from_secs(10))
+            .absolute_timeout(Duration::from_secs(100))
+            .config;
+
+        let record = SessionRecord {
+            created: 1000,
+            accessed: 1050,
+            data: Default::default(),
+        };
+
+        assert!(!config.is_expired(&record, 1055));
+        assert!(config.is_expired(&record, 1060));
+
+        let record = SessionRecord {
+            accessed: 1095,
+            ..record
+        };
+        assert!(!config.is_expired(&record, 1099));
+        assert!(config.is_expired(&record, 1100));
+    }
+
+    #[test]
+    fn expiry_overflow() {
+        let config = Session::new(CookieStore, Key::generate())
+            .idle_timeout(Duration::MAX)
+            .absolute_timeout(Duration::MAX)
+            .config;
+
+        let record = SessionRecord {
+            created: 1000,
+            accessed: 1050,
+            data: Default::default(),
+        };
+
+        assert!(!config.is_expired(&record, u64::MAX - 1));
+
+        let cookie = config.cookie(String::from("996"), &record, 1050);
+        assert!(cookie.max_age().unwrap().is_positive());
+    }
+}
@@ -0,0 +1,346 @@
+//! request timeout middleware.
+
+use core::{fmt, time::Duration};
+
+use std::error;
+
+use crate::{
+    WebContext,
+    error::{Error, error_from_service},
+    http::{StatusCode, WebResponse},
+    service::Service,
+};
+
+/// builder for request timeout middleware. can be applied to [App] or individual route.
+///
+/// handler execution is capped by given duration. when it expires [TimeoutError::Handler] is produced and
+/// by default it's rendered as `503 Service Unavailable` response.
+///
+/// optionally the idle time between request body chunks can be capped with [Timeout::body_idle]. when it
+/// expires [TimeoutError::BodyIdle] is produced from request body stream and by default it's rendered as
+/// `408 Request Timeout` response.
+///
+/// # Type mutation
+/// [Timeout::body_idle] would mutate request body type from `B` to [TimeoutBody<B>]. Service enclosed by
+/// it must be able to handle it's mutation or utilize [`TypeEraser`] to erase the mutation.
+/// For more explanation please reference [`type mutation`](crate::middleware#type-mutation).
+///
+/// # Examples
+/// ```rust
+/// # use std::time::Duration;
+/// # use xitca_web::{handler::handler_service, middleware::timeout::Timeout, route::get, service::ServiceExt, App, WebContext};
+/// App::new()
+///     .at("/", get(handler_service(|| async { "hello,world!" })))
+///     // cap handler execution to 5 seconds for "/slow" route.
+///     .at("/slow", get(handler_service(|| async { "slow" }).enclosed(Timeout::new(Duration::from_secs(5)))))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // cap handler execution to 30 seconds and request body idle time to 10 seconds for all routes.
+///     .enclosed(Timeout::new(Duration::from_secs(30)).body_idle(Duration::from_secs(10)));
+/// ```
+///
+/// [App]: crate::App
+/// [TimeoutBody<B>]: TimeoutBody
+/// [`TypeEraser`]: crate::middleware::eraser::TypeEraser
+#[derive(Clone, Copy)]
+pub struct Timeout<I = ()> {
+    handler: Duration,
+    body_idle: I,
+}
+
+impl Timeout {
+    /// construct a new middleware builder with given cap of handler execution time.
+    pub const fn new(handler: Duration) -> Self {
+        Self { handler, body_idle: () }
+    }
+
+    /// set max idle time between request body chunks.
+    pub const fn body_idle(self, dur: Duration) -> Timeout<Duration> {
+        Timeout {
+            handler: self.handler,
+            body_idle: dur,
+        }
+    }
+}
+
+impl<S, E, I> Service<Result<S, E>> for Timeout<I>
+where
+    I: Copy,
+{
+    type Response = service::TimeoutService<S, I>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::TimeoutService {
+            service,
+            handler: self.handler,
+            body_idle: self.body_idle,
+        })
+    }
+}
+
+/// error type produced by [Timeout] middleware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutError {
+    /// handler execution exceeds the time cap.
+    Handler,
+    /// request body stream stays idle longer than the time cap.
+    BodyIdle,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Handler => f.write_str("request handler timed out"),
+            Self::BodyIdle => f.write_str("request body read timed out"),
+        }
+    }
+}
+
+impl error::Error for TimeoutError {}
+
+error_from_service!(TimeoutError);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for TimeoutError {
+    type Response = WebResponse;
+    type Error = core::convert::Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let status = match self {
+            Self::Handler => StatusCode::SERVICE_UNAVAILABLE,
+            Self::BodyIdle => StatusCode::REQUEST_TIMEOUT,
+        };
+        status.call(ctx).await
+    }
+}
+
+mod service {
+    use core::{
+        cell::RefCell,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_core::stream::Stream;
+    use pin_project_lite::pin_project;
+    use tokio::time::{Instant, Sleep, sleep, timeout};
+    use xitca_http::Request;
+
+    use crate::{body::BodyStream, error::BodyError, service::ready::ReadyService};
+
+    use super::*;
+
+    pub struct TimeoutService<S, I> {
+        pub(super) service: S,
+        pub(super) handler: Duration,
+        pub(super) body_idle: I,
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for TimeoutService<S, ()>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            timeout(self.handler, self.service.call(ctx))
+                .await
+                .map_err(|_| Error::from(TimeoutError::Handler))?
+        }
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for TimeoutService<S, Duration>
+    where
+        B: BodyStream + Default,
+        S: for<'r2> Service<WebContext<'r2, C, TimeoutBody<B>>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let (parts, ext) = ctx.take_request().into_parts();
+            let state = ctx.ctx;
+            let (ext, body) = ext.replace_body(());
+            let mut body = RefCell::new(TimeoutBody::new(body, self.body_idle));
+            let mut req = Request::from_parts(parts, ext);
+
+            timeout(
+                self.handler,
+                self.service.call(WebContext::new(&mut req, &mut body, state)),
+            )
+            .await
+            .map_err(|_| Error::from(TimeoutError::Handler))
+            .and_then(|res| res)
+            .inspect_err(|_| {
+                let body = body.into_inner().into_inner();
+                *ctx.body_borrow_mut() = body;
+            })
+        }
+    }
+
+    impl<S, I> ReadyService for TimeoutService<S, I>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    pin_project! {
+        /// request body type produced by [Timeout::body_idle]. yield [TimeoutError::BodyIdle] when
+        /// no chunk arrives in the configured duration.
+        pub struct TimeoutBody<B> {
+            dur: Duration,
+            sleep: Option<Pin<Box<Sleep>>>,
+            expired: bool,
+            #[pin]
+            body: B
+        }
+    }
+
+    impl<B: Default> Default for TimeoutBody<B> {
+        fn default() -> Self {
+            Self::new(B::default(), Duration::ZERO)
+        }
+    }
+
+    impl<B> TimeoutBody<B> {
+        const fn new(body: B, dur: Duration) -> Self {
+            Self {
+                dur,
+                // timer is lazily constructed when body stream is pending for the first time.
+                sleep: None,
+                expired: false,
+                body,
+            }
+        }
+
+        fn into_inner(self) -> B {
+            self.body
+        }
+    }
+
+    impl<B> Stream for TimeoutBody<B>
+    where
+        B: BodyStream,
+    {
+        type Item = Result<B::Chunk, BodyError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+
+            if *this.expired {
+                return Poll::Ready(None);
+            }
+
+            match this.body.poll_next(cx) {
+                Poll::Ready(Some(res)) => {
+                    if let Some(sleep) = this.sleep.as_mut() {
+                        sleep.as_mut().reset(Instant::now() + *this.dur);
+                    }
+                    Poll::Ready(Some(res.map_err(Into::into)))
+                }
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => {
+                    let sleep = this.sleep.get_or_insert_with(|| Box::pin(sleep(*this.dur)));
+                    match sleep.as_mut().poll(cx) {
+                        Poll::Ready(_) => {
+                            *this.expired = true;
+                            // search error module for downcast_ref::<TimeoutError>() before considering change
+                            // the error type.
+                            Poll::Ready(Some(Err(BodyError::from(TimeoutError::BodyIdle))))
+                        }
+                        Poll::Pending => Poll::Pending,
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub use service::{TimeoutBody, TimeoutService};
+
+#[cfg(test)]
+mod test {
+    use core::future::poll_fn;
+
+    use futures_util::stream::{self, StreamExt};
+
+    use crate::{
+        App,
+        body::{BodyStream, BoxBody},
+        bytes::Bytes,
+        error::BodyError,
+        handler::{body::Body, handler_service},
+        http::WebRequest,
+        route::get,
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handler_timeout() {
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "hello,world!" })))
+            .at(
+                "/slow",
+                get(handler_service(|| async {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    "slow"
+                })),
+            )
+            .enclosed(Timeout::new(Duration::from_millis(20)))
+            .finish()
+            .call(())
+            .await
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(collect_string_body(res.into_body()).await.unwrap(), "hello,world!");
+
+        let mut req = WebRequest::default();
+        *req.uri_mut() = "/slow".parse().unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn body_idle_timeout() {
+        async fn handler<B: BodyStream>(Body(body): Body<B>) -> Result<String, Error> {
+            let mut body = core::pin::pin!(body);
+            let mut buf = String::new();
+            while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                let chunk = chunk.map_err(|e| Error::from(e.into()))?;
+                buf.push_str(core::str::from_utf8(chunk.as_ref()).unwrap());
+            }
+            Ok(buf)
+        }
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .enclosed(Timeout::new(Duration::from_secs(5)).body_idle(Duration::from_millis(20)))
+            .finish()
+            .call(())
+            .await
+            .unwrap();
+
+        let item = || async { Ok::<_, BodyError>(Bytes::from_static(b"996")) };
+
+        let body = stream::once(item()).chain(stream::once(item()));
+        let req = WebRequest::default().map(|ext| ext.map_body(|_: ()| BoxBody::new(body).into()));
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(collect_string_body(res.into_body()).await.unwrap(), "996996");
+
+        let body = stream::once(item()).chain(stream::pending());
+        let req = WebRequest::default().map(|ext| ext.map_body(|_: ()| BoxBody::new(body).into()));
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+}
@@ -0,0 +1,63 @@
+//! helpers shared by session, csrf and security headers middlewares.
+
+use core::fmt::Write;
+
+#[cfg(any(feature = "session", feature = "csrf"))]
+pub(crate) use self::cookie::*;
+
+// hex encoded string of N random bytes.
+pub(crate) fn random_hex<const N: usize>() -> String {
+    rand::random::<[u8; N]>()
+        .iter()
+        .fold(String::with_capacity(N * 2), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+#[cfg(any(feature = "session", feature = "csrf"))]
+mod cookie {
+    use cookie::{Cookie, CookieJar, Key};
+
+    use crate::{
+        error::{Error, ErrorStatus},
+        http::{
+            HeaderMap, HeaderValue,
+            header::{COOKIE, SET_COOKIE},
+        },
+    };
+
+    // read value of cookie with given name signed with given key from request headers.
+    pub(crate) fn read_signed_cookie(headers: &HeaderMap, key: &Key, name: &str) -> Option<String> {
+        let mut jar = CookieJar::new();
+        for val in headers.get_all(COOKIE) {
+            let Ok(val) = val.to_str() else { continue };
+            for val in val.split(';') {
+                if let Ok(cookie) = Cookie::parse_encoded(val.trim().to_owned()) {
+                    jar.add_original(cookie);
+                }
+            }
+        }
+        jar.signed(key).get(name).map(|c| c.value().to_owned())
+    }
+
+    // sign cookie with given key and append it to response headers. removal cookie with empty value is
+    // not signed.
+    pub(crate) fn write_signed_cookie(
+        headers: &mut HeaderMap,
+        key: &Key,
+        cookie: Cookie<'static>,
+    ) -> Result<(), Error> {
+        let mut jar = CookieJar::new();
+        if cookie.value().is_empty() {
+            jar.add(cookie);
+        } else {
+            jar.signed_mut(key).add(cookie);
+        }
+        for cookie in jar.delta() {
+            let value = HeaderValue::try_from(cookie.encoded().to_string()).map_err(|_| ErrorStatus::internal())?;
+            headers.append(SET_COOKIE, value);
+        }
+        Ok(())
+    }
+}
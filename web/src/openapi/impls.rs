@@ -0,0 +1,532 @@
+use std::borrow::Cow;
+
+use serde_json::{Value, json};
+
+use crate::{
+    body::{BoxBody, ResponseBody},
+    bytes::{Bytes, BytesMut},
+    context::WebContext,
+    error::{Error, ErrorStatus},
+    handler::{
+        body::{Body, Limit},
+        extension::{ExtensionOwn, ExtensionRef, ExtensionsRef},
+        header::{HeaderRef, map_to_header_name},
+        html::Html,
+        negotiate::Negotiate,
+        path::{PathOwn, PathRef},
+        redirect::Redirect,
+        request_id::RequestId,
+        state::{StateOwn, StateRef},
+        text::Text,
+        typed_header::{Header, TypedHeader},
+        uri::{UriOwn, UriRef},
+    },
+    http::{
+        HeaderMap, Method, RequestExt, StatusCode, Uri, WebRequest, WebResponse,
+        header::{HeaderName, HeaderValue},
+    },
+};
+
+use super::{Components, Operation, OperationInput, OperationOutput, Parameter, ParameterIn, Response, Schema};
+
+const OCTET_STREAM: &str = "application/octet-stream";
+const TEXT_PLAIN: &str = "text/plain";
+#[cfg(feature = "json")]
+const APPLICATION_JSON: &str = "application/json";
+#[cfg(feature = "json")]
+const APPLICATION_NDJSON: &str = "application/x-ndjson";
+
+impl OperationInput for () {}
+
+macro_rules! input_tuple_impl {
+    ($($ty: ident),+) => {
+        impl<$($ty,)+> OperationInput for ($($ty,)+)
+        where
+            $($ty: OperationInput,)+
+        {
+            fn operation_input(op: &mut Operation, components: &mut Components) {
+                $($ty::operation_input(op, components);)+
+            }
+        }
+    };
+}
+
+input_tuple_impl!(A);
+input_tuple_impl!(A, B);
+input_tuple_impl!(A, B, C);
+input_tuple_impl!(A, B, C, D);
+input_tuple_impl!(A, B, C, D, E);
+input_tuple_impl!(A, B, C, D, E, F);
+input_tuple_impl!(A, B, C, D, E, F, G);
+input_tuple_impl!(A, B, C, D, E, F, G, H);
+input_tuple_impl!(A, B, C, D, E, F, G, H, I);
+
+// describe extractor as optional. parameters(except path) and request body added by it are not required.
+fn optional_input<T>(op: &mut Operation, components: &mut Components)
+where
+    T: OperationInput,
+{
+    let len = op.parameters.len();
+    let has_body = op.request_body.is_some();
+
+    T::operation_input(op, components);
+
+    for param in op.parameters[len..].iter_mut() {
+        param.required = false;
+    }
+    if !has_body {
+        if let Some(body) = op.request_body.as_mut() {
+            body.required = false;
+        }
+    }
+}
+
+impl<T> OperationInput for Option<T>
+where
+    T: OperationInput,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        optional_input::<T>(op, components)
+    }
+}
+
+impl<T, E> OperationInput for Result<T, E>
+where
+    T: OperationInput,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        optional_input::<T>(op, components)
+    }
+}
+
+// expand properties of object schema into parameters. non object schema is returned as is.
+#[cfg(any(feature = "urlencoded", feature = "params"))]
+fn object_params(schema: Value, location: ParameterIn, op: &mut Operation, components: &Components) -> Option<Value> {
+    let resolved = components.resolve(&schema);
+    let Some(props) = resolved.get("properties").and_then(Value::as_object) else {
+        return Some(schema);
+    };
+
+    let required = resolved.get("required").and_then(Value::as_array);
+    for (name, prop) in props.iter() {
+        let required = required.is_some_and(|r| r.iter().any(|r| r == name));
+        let mut param = Parameter::new(name.as_str(), location, prop.clone()).required(required);
+        if let Some(desc) = prop.get("description").and_then(Value::as_str) {
+            param = param.description(desc);
+        }
+        op.parameter(param);
+    }
+
+    None
+}
+
+#[cfg(feature = "urlencoded")]
+impl<T> OperationInput for crate::handler::query::Query<T>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        object_params(schema, ParameterIn::Query, op, components);
+    }
+}
+
+#[cfg(feature = "params")]
+impl<T> OperationInput for crate::handler::params::Params<T>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        // scalar params type describes the path parameter it's matched against.
+        if let Some(schema) = object_params(schema, ParameterIn::Path, op, components) {
+            op.path_schemas.push(schema);
+        }
+    }
+}
+
+impl<const HEADER_NAME: usize> OperationInput for HeaderRef<'_, HEADER_NAME> {
+    fn operation_input(op: &mut Operation, _: &mut Components) {
+        let name = map_to_header_name::<HEADER_NAME>();
+        op.parameter(Parameter::new(
+            name.as_str(),
+            ParameterIn::Header,
+            json!({ "type": "string" }),
+        ));
+    }
+}
+
+#[cfg(feature = "validate")]
+impl<T> OperationInput for crate::handler::validate::Valid<T>
+where
+    T: OperationInput,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        T::operation_input(op, components)
+    }
+}
+
+impl<T> OperationInput for TypedHeader<T>
+where
+    T: Header,
+{
+    fn operation_input(op: &mut Operation, _: &mut Components) {
+        op.parameter(Parameter::new(
+            T::NAME.as_str(),
+            ParameterIn::Header,
+            json!({ "type": "string" }),
+        ));
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T, const LIMIT: usize> OperationInput for crate::handler::json::Json<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        op.request_body(APPLICATION_JSON, schema);
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T, const LIMIT: usize> OperationInput for crate::handler::json::LazyJson<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        crate::handler::json::Json::<T, LIMIT>::operation_input(op, components)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T, const LIMIT: usize> OperationInput for crate::handler::msgpack::MsgPack<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        op.request_body("application/msgpack", schema);
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl<T, const LIMIT: usize> OperationInput for crate::handler::cbor::Cbor<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        op.request_body("application/cbor", schema);
+    }
+}
+
+// json lines content is described by schema of a single line.
+#[cfg(feature = "json")]
+impl<T, B, const LIMIT: usize> OperationInput for crate::handler::json_lines::JsonLines<T, B, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        op.request_body(APPLICATION_NDJSON, schema);
+    }
+}
+
+#[cfg(feature = "urlencoded")]
+impl<T, const LIMIT: usize> OperationInput for crate::handler::form::Form<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        op.request_body("application/x-www-form-urlencoded", schema);
+    }
+}
+
+#[cfg(feature = "urlencoded")]
+impl<T, const LIMIT: usize> OperationInput for crate::handler::form::LazyForm<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        crate::handler::form::Form::<T, LIMIT>::operation_input(op, components)
+    }
+}
+
+#[cfg(feature = "multipart")]
+impl<B> OperationInput for crate::handler::multipart::Multipart<B> {
+    fn operation_input(op: &mut Operation, _: &mut Components) {
+        op.request_body("multipart/form-data", json!({ "type": "object" }));
+    }
+}
+
+impl OperationInput for String {
+    fn operation_input(op: &mut Operation, _: &mut Components) {
+        op.request_body(TEXT_PLAIN, json!({ "type": "string" }));
+    }
+}
+
+macro_rules! input_bytes_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl OperationInput for $ty {
+                fn operation_input(op: &mut Operation, _: &mut Components) {
+                    op.request_body(OCTET_STREAM, json!({ "type": "string", "format": "binary" }));
+                }
+            }
+
+            impl<const LIMIT: usize> OperationInput for ($ty, Limit<LIMIT>) {
+                fn operation_input(op: &mut Operation, components: &mut Components) {
+                    <$ty>::operation_input(op, components)
+                }
+            }
+        )*
+    };
+}
+
+input_bytes_impl!(Bytes, BytesMut, Vec<u8>);
+
+// extractors that don't take part in operation description.
+macro_rules! input_noop_impl {
+    ($([$($generic: tt)*] $ty: ty),* $(,)?) => {
+        $(
+            impl<$($generic)*> OperationInput for $ty {}
+        )*
+    };
+}
+
+input_noop_impl!(
+    ['a, 'r, C, B] &'a WebContext<'r, C, B>,
+    ['a, S: ?Sized] StateRef<'a, S>,
+    [S] StateOwn<S>,
+    ['a, T] ExtensionRef<'a, T>,
+    [T] ExtensionOwn<T>,
+    ['a] ExtensionsRef<'a>,
+    ['a] PathRef<'a>,
+    [] PathOwn,
+    ['a] UriRef<'a>,
+    [] UriOwn,
+    ['a] &'a Uri,
+    [] Uri,
+    ['a] &'a HeaderMap,
+    [] HeaderMap,
+    ['a] &'a Method,
+    [] Method,
+    ['a] &'a core::net::SocketAddr,
+    [] core::net::SocketAddr,
+    ['a] &'a WebRequest<()>,
+    [] WebRequest<()>,
+    ['a] &'a RequestExt<()>,
+    [] RequestExt<()>,
+    [] RequestId,
+    [B] Body<B>,
+);
+
+#[cfg(feature = "params")]
+input_noop_impl!(
+    ['a] crate::handler::params::ParamsRef<'a>,
+    ['a, T] crate::handler::params::LazyParams<'a, T>,
+);
+
+#[cfg(feature = "auth")]
+input_noop_impl!(
+    [] crate::handler::auth::BasicAuth,
+    [] crate::handler::auth::BearerToken,
+);
+
+#[cfg(feature = "jwt")]
+input_noop_impl!([T] crate::handler::jwt::Jwt<T>);
+
+#[cfg(feature = "cookie")]
+input_noop_impl!([K] crate::handler::cookie::CookieJar<K>);
+
+#[cfg(feature = "session")]
+input_noop_impl!([] crate::handler::session::Session);
+
+#[cfg(feature = "csrf")]
+input_noop_impl!([] crate::middleware::csrf::CsrfToken);
+
+#[cfg(feature = "security-headers")]
+input_noop_impl!([] crate::middleware::security_headers::CspNonce);
+
+#[cfg(feature = "websocket")]
+input_noop_impl!([B: crate::body::BodyStream] crate::handler::websocket::WebSocket<B>);
+
+#[cfg(feature = "sse")]
+input_noop_impl!([] crate::handler::sse::LastEventId);
+
+fn ok_response(op: &mut Operation, content_type: &str, schema: Value) {
+    op.response(
+        StatusCode::OK,
+        Response::new("successful response").content(content_type, schema),
+    );
+}
+
+#[cfg(feature = "json")]
+impl<T, const LIMIT: usize> OperationOutput for crate::handler::json::Json<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_output(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        ok_response(op, APPLICATION_JSON, schema);
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T, const LIMIT: usize> OperationOutput for crate::handler::msgpack::MsgPack<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_output(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        ok_response(op, "application/msgpack", schema);
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl<T, const LIMIT: usize> OperationOutput for crate::handler::cbor::Cbor<T, LIMIT>
+where
+    T: Schema,
+{
+    fn operation_output(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        ok_response(op, "application/cbor", schema);
+    }
+}
+
+#[cfg(feature = "json")]
+impl<S, T, E> OperationOutput for crate::handler::json_lines::JsonLinesResponse<S>
+where
+    S: futures_core::Stream<Item = Result<T, E>>,
+    T: Schema,
+{
+    fn operation_output(op: &mut Operation, components: &mut Components) {
+        let schema = T::schema(components);
+        ok_response(op, APPLICATION_NDJSON, schema);
+    }
+}
+
+#[cfg(feature = "json")]
+impl OperationOutput for Value {
+    fn operation_output(op: &mut Operation, _: &mut Components) {
+        ok_response(op, APPLICATION_JSON, json!({}));
+    }
+}
+
+macro_rules! output_text_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl OperationOutput for $ty {
+                fn operation_output(op: &mut Operation, _: &mut Components) {
+                    ok_response(op, TEXT_PLAIN, json!({ "type": "string" }));
+                }
+            }
+        )*
+    };
+}
+
+output_text_impl!(&'static str, String, Box<str>, Cow<'static, str>);
+
+impl<T> OperationOutput for Text<T> {
+    fn operation_output(op: &mut Operation, _: &mut Components) {
+        ok_response(op, TEXT_PLAIN, json!({ "type": "string" }));
+    }
+}
+
+impl<T> OperationOutput for Html<T> {
+    fn operation_output(op: &mut Operation, _: &mut Components) {
+        ok_response(op, "text/html", json!({ "type": "string" }));
+    }
+}
+
+macro_rules! output_bytes_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl OperationOutput for $ty {
+                fn operation_output(op: &mut Operation, _: &mut Components) {
+                    ok_response(op, OCTET_STREAM, json!({ "type": "string", "format": "binary" }));
+                }
+            }
+        )*
+    };
+}
+
+output_bytes_impl!(Bytes, BytesMut, Vec<u8>);
+
+#[cfg(feature = "sse")]
+impl<S> OperationOutput for crate::handler::sse::Sse<S> {
+    fn operation_output(op: &mut Operation, _: &mut Components) {
+        ok_response(op, "text/event-stream", json!({ "type": "string" }));
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl<B> OperationOutput for crate::handler::websocket::WebSocket<B>
+where
+    B: crate::body::BodyStream,
+{
+    fn operation_output(op: &mut Operation, _: &mut Components) {
+        op.response(StatusCode::SWITCHING_PROTOCOLS, Response::new("websocket upgrade"));
+    }
+}
+
+impl<T, E> OperationOutput for Result<T, E>
+where
+    T: OperationOutput,
+{
+    fn operation_output(op: &mut Operation, components: &mut Components) {
+        T::operation_output(op, components);
+        op.default_response(Response::new("error response"));
+    }
+}
+
+macro_rules! output_tuple_impl {
+    ($($ty: ident),+) => {
+        impl<$($ty,)+> OperationOutput for ($($ty,)+)
+        where
+            $($ty: OperationOutput,)+
+        {
+            fn operation_output(op: &mut Operation, components: &mut Components) {
+                $($ty::operation_output(op, components);)+
+            }
+        }
+    };
+}
+
+output_tuple_impl!(A);
+output_tuple_impl!(A, B);
+output_tuple_impl!(A, B, C);
+output_tuple_impl!(A, B, C, D);
+output_tuple_impl!(A, B, C, D, E);
+output_tuple_impl!(A, B, C, D, E, F);
+
+// responders with response not known from their types.
+macro_rules! output_noop_impl {
+    ($([$($generic: tt)*] $ty: ty),* $(,)?) => {
+        $(
+            impl<$($generic)*> OperationOutput for $ty {}
+        )*
+    };
+}
+
+output_noop_impl!(
+    [] StatusCode,
+    [] Redirect,
+    [] Error,
+    [] ErrorStatus,
+    [B] WebResponse<B>,
+    [B] ResponseBody<B>,
+    [] BoxBody,
+    [] HeaderMap,
+    [] (HeaderName, HeaderValue),
+    [const N: usize] [(HeaderName, HeaderValue); N],
+    [] Vec<(HeaderName, HeaderValue)>,
+    [T] TypedHeader<T>,
+    [T] Negotiate<T>,
+);
+
+#[cfg(feature = "cookie")]
+output_noop_impl!([K] crate::handler::cookie::CookieJar<K>);
@@ -0,0 +1,911 @@
+//! openapi 3.1 document generation.
+//!
+//! handler services registered with [App::at_api] are described by their extractor and responder types.
+//! extractors describe parameters and request body through [OperationInput] trait. responders describe
+//! responses through [OperationOutput] trait. types carried by extractors and responders describe their
+//! json schema through [Schema] trait. other routes with method guard are added to document as operations
+//! without description.
+//!
+//! # Examples
+//! ```rust
+//! # use serde::{Deserialize, Serialize};
+//! # use serde_json::Value;
+//! # use xitca_web::{
+//! #   handler::{handler_service, json::Json},
+//! #   openapi::{Components, Describe, Info, ObjectSchema, Schema},
+//! #   route::get,
+//! #   App, WebContext
+//! # };
+//! #[derive(Deserialize, Serialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! impl Schema for User {
+//!     fn schema(components: &mut Components) -> Value {
+//!         components.reference("User", |components| ObjectSchema::new(components).field::<String>("name").finish())
+//!     }
+//! }
+//!
+//! async fn get_user() -> Json<User> {
+//!     Json(User { name: String::from("user") })
+//! }
+//!
+//! async fn update_user(Json(user): Json<User>) -> Json<User> {
+//!     Json(user)
+//! }
+//!
+//! let app = App::new()
+//!     // routes registered with at_api are described in openapi document.
+//!     .at_api(
+//!         "/users/{id}",
+//!         get(Describe::new(handler_service(get_user)).summary("get user").tag("user"))
+//!             .put(handler_service(update_user)),
+//!     )
+//!     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+//!     ;
+//!
+//! // generate document from registered routes and serve it as json.
+//! let api = app.openapi(Info::new("user service", "1.0.0"));
+//! app.at("/openapi.json", api);
+//! ```
+//!
+//! [App::at_api]: crate::App::at_api
+
+mod impls;
+mod schema;
+
+pub use schema::{Components, ObjectSchema, Schema, nullable, with_description};
+
+use core::convert::Infallible;
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+use xitca_http::util::service::{
+    handler::{AsyncFn2, HandlerService},
+    route::{MethodNotAllowedBuilder, Route},
+    router::{PathGen, RouteGen, RouterMapErr},
+};
+
+use crate::{
+    body::ResponseBody,
+    bytes::Bytes,
+    context::WebContext,
+    error::Error,
+    http::{Method, StatusCode, WebResponse, const_header_value::JSON, header::CONTENT_TYPE},
+    service::{Service, pipeline::PipelineT},
+};
+
+/// trait for extractor type describing how it takes part in an operation.
+///
+/// default implementation describes nothing.
+pub trait OperationInput {
+    /// describe parameters and request body of operation.
+    fn operation_input(op: &mut Operation, components: &mut Components) {
+        let _ = (op, components);
+    }
+}
+
+/// trait for responder type describing how it takes part in an operation.
+///
+/// default implementation describes nothing.
+pub trait OperationOutput {
+    /// describe responses of operation.
+    fn operation_output(op: &mut Operation, components: &mut Components) {
+        let _ = (op, components);
+    }
+}
+
+/// trait for service builder that can describe itself as an operation.
+pub trait DescribeOperation {
+    fn describe_operation(&self, op: &mut Operation, components: &mut Components);
+}
+
+/// trait for route service builder that can describe operations of given path.
+pub trait DescribeRoute {
+    fn describe_route(&self, path: &str, api: &mut OpenApi);
+}
+
+/// trait for typed route generated by `#[route]` macro with `openapi` attribute.
+pub trait DescribeTypedRoute {
+    fn describe_typed_route(api: &mut OpenApi);
+}
+
+/// general information of openapi document.
+#[derive(Clone, Debug)]
+pub struct Info {
+    title: String,
+    version: String,
+    description: Option<String>,
+}
+
+impl Info {
+    /// construct info with given title and version of api.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+        }
+    }
+
+    /// set description of api.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    fn to_json(&self) -> Value {
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(ref description) = self.description {
+            info["description"] = description.as_str().into();
+        }
+        info
+    }
+}
+
+impl Default for Info {
+    fn default() -> Self {
+        Self::new("xitca-web", "0.1.0")
+    }
+}
+
+/// openapi document.
+///
+/// the document can be used as service where it would be served as json.
+#[derive(Clone, Debug, Default)]
+pub struct OpenApi {
+    info: Info,
+    paths: BTreeMap<String, BTreeMap<&'static str, Operation>>,
+    components: Components,
+}
+
+impl OpenApi {
+    /// construct an empty document with given info.
+    pub fn new(info: Info) -> Self {
+        Self {
+            info,
+            paths: BTreeMap::new(),
+            components: Components::default(),
+        }
+    }
+
+    /// describe route service with given path and add it's operations to document.
+    pub fn route<R>(mut self, path: &str, route: &R) -> Self
+    where
+        R: DescribeRoute + ?Sized,
+    {
+        route.describe_route(path, &mut self);
+        self
+    }
+
+    /// add operation with given path and method to document.
+    ///
+    /// path is in the format of [App::at] and path parameter not described by operation is added as string.
+    /// operation with method not supported by openapi(CONNECT and extension methods) is ignored.
+    ///
+    /// [App::at]: crate::App::at
+    pub fn operation(&mut self, path: &str, method: &Method, mut op: Operation) {
+        let Some(method) = method_key(method) else { return };
+
+        let (path, params) = normalize_path(path);
+        let mut schemas = core::mem::take(&mut op.path_schemas).into_iter();
+        for name in params {
+            let exists = op
+                .parameters
+                .iter()
+                .any(|p| p.location == ParameterIn::Path && p.name == name);
+            if !exists {
+                let schema = schemas.next().unwrap_or_else(|| json!({ "type": "string" }));
+                op.parameter(Parameter::new(name, ParameterIn::Path, schema));
+            }
+        }
+
+        self.paths.entry(path).or_default().insert(method, op);
+    }
+
+    /// get mutable reference of reusable schemas.
+    pub fn components(&mut self) -> &mut Components {
+        &mut self.components
+    }
+
+    /// generate json value of document.
+    pub fn to_json(&self) -> Value {
+        let paths = self
+            .paths
+            .iter()
+            .map(|(path, item)| {
+                let item = item
+                    .iter()
+                    .map(|(method, op)| (String::from(*method), op.to_json()))
+                    .collect::<Map<_, _>>();
+                (path.clone(), Value::Object(item))
+            })
+            .collect::<Map<_, _>>();
+
+        let mut doc = json!({
+            "openapi": "3.1.0",
+            "info": self.info.to_json(),
+            "paths": paths,
+        });
+
+        if !self.components.is_empty() {
+            doc["components"] = self.components.to_json();
+        }
+
+        doc
+    }
+
+    // add operation without description for given path and methods when they are not described.
+    pub(crate) fn undescribed(&mut self, path: &str, methods: &[Method]) {
+        let (key, _) = normalize_path(path);
+        for method in methods {
+            let described =
+                method_key(method).is_some_and(|m| self.paths.get(&key).is_some_and(|item| item.contains_key(m)));
+            if !described {
+                self.operation(path, method, Operation::default());
+            }
+        }
+    }
+
+    pub(crate) fn with_info(&self, info: Info) -> Self {
+        Self { info, ..self.clone() }
+    }
+
+    // merge operations from nested document with given path prefix.
+    pub(crate) fn merge(&mut self, prefix: &str, other: &Self) {
+        let prefix = prefix.trim_end_matches('/');
+        for (path, item) in other.paths.iter() {
+            for (method, op) in item.iter() {
+                let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+                self.operation(&format!("{prefix}{path}"), &method, op.clone());
+            }
+        }
+        self.components.merge(&other.components);
+    }
+}
+
+fn method_key(method: &Method) -> Option<&'static str> {
+    Some(match *method {
+        Method::GET => "get",
+        Method::PUT => "put",
+        Method::POST => "post",
+        Method::DELETE => "delete",
+        Method::OPTIONS => "options",
+        Method::HEAD => "head",
+        Method::PATCH => "patch",
+        Method::TRACE => "trace",
+        _ => return None,
+    })
+}
+
+// convert router path to openapi path template and collect names of path parameters.
+fn normalize_path(path: &str) -> (String, Vec<String>) {
+    let mut res = String::with_capacity(path.len());
+    let mut params = Vec::new();
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = rest[start + 1..start + end].trim_start_matches('*');
+        res.push('{');
+        res.push_str(name);
+        res.push('}');
+        if !name.is_empty() {
+            params.push(name.to_owned());
+        }
+        rest = &rest[start + end + 1..];
+    }
+    res.push_str(rest);
+
+    if res.is_empty() {
+        res.push('/');
+    }
+
+    (res, params)
+}
+
+/// operation of a path and method pair.
+#[derive(Clone, Debug, Default)]
+pub struct Operation {
+    operation_id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+    parameters: Vec<Parameter>,
+    request_body: Option<RequestBody>,
+    responses: BTreeMap<String, Response>,
+    // schemas of path parameters described without name. they are matched with path parameters by order.
+    path_schemas: Vec<Value>,
+}
+
+impl Operation {
+    /// set unique identifier of operation.
+    pub fn operation_id(&mut self, id: impl Into<String>) -> &mut Self {
+        self.operation_id = Some(id.into());
+        self
+    }
+
+    /// set short summary of operation.
+    pub fn summary(&mut self, summary: impl Into<String>) -> &mut Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// set verbose description of operation.
+    pub fn description(&mut self, description: impl Into<String>) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// add tag for grouping operation.
+    pub fn tag(&mut self, tag: impl Into<String>) -> &mut Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// mark operation as deprecated.
+    pub fn deprecated(&mut self) -> &mut Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// add parameter to operation. existing parameter with the same name and location is replaced.
+    pub fn parameter(&mut self, param: Parameter) -> &mut Self {
+        match self
+            .parameters
+            .iter_mut()
+            .find(|p| p.name == param.name && p.location == param.location)
+        {
+            Some(p) => *p = param,
+            None => self.parameters.push(param),
+        }
+        self
+    }
+
+    /// add content type and it's schema to request body of operation.
+    pub fn request_body(&mut self, content_type: &str, schema: Value) -> &mut Self {
+        self.request_body
+            .get_or_insert_with(|| RequestBody {
+                required: true,
+                content: BTreeMap::new(),
+            })
+            .content
+            .insert(content_type.to_owned(), schema);
+        self
+    }
+
+    /// add response with given status code. content of response with the same status code is merged.
+    pub fn response(&mut self, status: StatusCode, res: Response) -> &mut Self {
+        self.add_response(status.as_str().to_owned(), res)
+    }
+
+    /// add response for status code not covered by other responses. content is merged with existing one.
+    pub fn default_response(&mut self, res: Response) -> &mut Self {
+        self.add_response(String::from("default"), res)
+    }
+
+    fn add_response(&mut self, key: String, res: Response) -> &mut Self {
+        match self.responses.get_mut(&key) {
+            Some(r) => r.content.extend(res.content),
+            None => {
+                self.responses.insert(key, res);
+            }
+        }
+        self
+    }
+
+    // merge metadata and responses of other operation into self.
+    fn merge(&mut self, other: &Self) {
+        if other.operation_id.is_some() {
+            self.operation_id.clone_from(&other.operation_id);
+        }
+        if other.summary.is_some() {
+            self.summary.clone_from(&other.summary);
+        }
+        if other.description.is_some() {
+            self.description.clone_from(&other.description);
+        }
+        for tag in other.tags.iter() {
+            self.tag(tag.as_str());
+        }
+        self.deprecated |= other.deprecated;
+        for param in other.parameters.iter() {
+            self.parameter(param.clone());
+        }
+        for (key, res) in other.responses.iter() {
+            self.add_response(key.clone(), res.clone());
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let mut op = Map::new();
+        if !self.tags.is_empty() {
+            op.insert("tags".into(), json!(self.tags));
+        }
+        if let Some(ref summary) = self.summary {
+            op.insert("summary".into(), summary.as_str().into());
+        }
+        if let Some(ref description) = self.description {
+            op.insert("description".into(), description.as_str().into());
+        }
+        if let Some(ref id) = self.operation_id {
+            op.insert("operationId".into(), id.as_str().into());
+        }
+        if !self.parameters.is_empty() {
+            let params = self.parameters.iter().map(Parameter::to_json).collect();
+            op.insert("parameters".into(), Value::Array(params));
+        }
+        if let Some(ref body) = self.request_body {
+            op.insert(
+                "requestBody".into(),
+                json!({ "content": content_json(&body.content), "required": body.required }),
+            );
+        }
+
+        // openapi requires at least one response.
+        let responses = if self.responses.is_empty() {
+            json!({ "default": { "description": "response" } })
+        } else {
+            let responses = self
+                .responses
+                .iter()
+                .map(|(key, res)| (key.clone(), res.to_json()))
+                .collect::<Map<_, _>>();
+            Value::Object(responses)
+        };
+        op.insert("responses".into(), responses);
+
+        if self.deprecated {
+            op.insert("deprecated".into(), true.into());
+        }
+
+        Value::Object(op)
+    }
+}
+
+fn content_json(content: &BTreeMap<String, Value>) -> Value {
+    content
+        .iter()
+        .map(|(ty, schema)| (ty.clone(), json!({ "schema": schema })))
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+#[derive(Clone, Debug)]
+struct RequestBody {
+    required: bool,
+    content: BTreeMap<String, Value>,
+}
+
+/// location of [Parameter].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterIn {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+/// parameter of operation.
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    name: String,
+    location: ParameterIn,
+    required: bool,
+    description: Option<String>,
+    schema: Value,
+}
+
+impl Parameter {
+    /// construct a required parameter with given name, location and schema.
+    pub fn new(name: impl Into<String>, location: ParameterIn, schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            location,
+            required: true,
+            description: None,
+            schema,
+        }
+    }
+
+    /// set if parameter is required. path parameter is always required.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// set description of parameter.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    fn to_json(&self) -> Value {
+        let location = match self.location {
+            ParameterIn::Path => "path",
+            ParameterIn::Query => "query",
+            ParameterIn::Header => "header",
+            ParameterIn::Cookie => "cookie",
+        };
+        let mut param = json!({
+            "name": self.name,
+            "in": location,
+            "required": self.required || self.location == ParameterIn::Path,
+            "schema": self.schema,
+        });
+        if let Some(ref description) = self.description {
+            param["description"] = description.as_str().into();
+        }
+        param
+    }
+}
+
+/// response of operation.
+#[derive(Clone, Debug)]
+pub struct Response {
+    description: String,
+    content: BTreeMap<String, Value>,
+}
+
+impl Response {
+    /// construct a response with given description and no content.
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            content: BTreeMap::new(),
+        }
+    }
+
+    /// add content type and it's schema to response.
+    pub fn content(mut self, content_type: &str, schema: Value) -> Self {
+        self.content.insert(content_type.to_owned(), schema);
+        self
+    }
+
+    fn to_json(&self) -> Value {
+        let mut res = json!({ "description": self.description });
+        if !self.content.is_empty() {
+            res["content"] = content_json(&self.content);
+        }
+        res
+    }
+}
+
+/// wrapper type attaching metadata to operation described by inner service builder.
+///
+/// metadata set by this type takes priority over the one described by inner service.
+pub struct Describe<S> {
+    service: S,
+    op: Operation,
+}
+
+impl<S> Describe<S> {
+    /// construct a new wrapper for given service builder.
+    pub fn new(service: S) -> Self {
+        Self {
+            service,
+            op: Operation::default(),
+        }
+    }
+
+    /// set unique identifier of operation.
+    pub fn operation_id(mut self, id: impl Into<String>) -> Self {
+        self.op.operation_id(id);
+        self
+    }
+
+    /// set short summary of operation.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.op.summary(summary);
+        self
+    }
+
+    /// set verbose description of operation.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.op.description(description);
+        self
+    }
+
+    /// add tag for grouping operation.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.op.tag(tag);
+        self
+    }
+
+    /// mark operation as deprecated.
+    pub fn deprecated(mut self) -> Self {
+        self.op.deprecated();
+        self
+    }
+
+    /// add parameter to operation.
+    pub fn parameter(mut self, param: Parameter) -> Self {
+        self.op.parameter(param);
+        self
+    }
+
+    /// add response with given status code to operation.
+    pub fn response(mut self, status: StatusCode, res: Response) -> Self {
+        self.op.response(status, res);
+        self
+    }
+}
+
+impl<S, Arg> Service<Arg> for Describe<S>
+where
+    S: Service<Arg>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    #[inline]
+    async fn call(&self, arg: Arg) -> Result<Self::Response, Self::Error> {
+        self.service.call(arg).await
+    }
+}
+
+impl<S> PathGen for Describe<S>
+where
+    S: PathGen,
+{
+    fn path_gen(&mut self, prefix: &str) -> String {
+        self.service.path_gen(prefix)
+    }
+
+    fn path_visit(&self, path: &str, visitor: &mut dyn FnMut(&str, &[Method])) {
+        self.service.path_visit(path, visitor)
+    }
+}
+
+impl<S> RouteGen for Describe<S>
+where
+    S: RouteGen,
+{
+    type Route<R> = S::Route<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        S::route_gen(route)
+    }
+}
+
+impl<S> DescribeOperation for Describe<S>
+where
+    S: DescribeOperation,
+{
+    fn describe_operation(&self, op: &mut Operation, components: &mut Components) {
+        self.service.describe_operation(op, components);
+        op.merge(&self.op);
+    }
+}
+
+impl<F, T, M> DescribeOperation for HandlerService<F, T, M>
+where
+    F: AsyncFn2<T>,
+    T: OperationInput,
+    F::Output: OperationOutput,
+{
+    fn describe_operation(&self, op: &mut Operation, components: &mut Components) {
+        T::operation_input(op, components);
+        F::Output::operation_output(op, components);
+    }
+}
+
+impl<F, S, M> DescribeOperation for PipelineT<F, S, M>
+where
+    F: DescribeOperation,
+{
+    fn describe_operation(&self, op: &mut Operation, components: &mut Components) {
+        self.first.describe_operation(op, components)
+    }
+}
+
+impl<R, N, const M: usize> DescribeRoute for Route<R, N, M>
+where
+    R: DescribeOperation,
+    N: DescribeRoute,
+{
+    fn describe_route(&self, path: &str, api: &mut OpenApi) {
+        for method in self.methods() {
+            let mut op = Operation::default();
+            self.service().describe_operation(&mut op, &mut api.components);
+            api.operation(path, method, op);
+        }
+        self.next_route().describe_route(path, api);
+    }
+}
+
+impl<R> DescribeRoute for MethodNotAllowedBuilder<R> {
+    fn describe_route(&self, _: &str, _: &mut OpenApi) {}
+}
+
+impl<F, S, M> DescribeRoute for PipelineT<F, S, M>
+where
+    F: DescribeRoute,
+{
+    fn describe_route(&self, path: &str, api: &mut OpenApi) {
+        self.first.describe_route(path, api)
+    }
+}
+
+impl PathGen for OpenApi {}
+
+impl RouteGen for OpenApi {
+    type Route<R> = RouterMapErr<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        RouterMapErr(route)
+    }
+}
+
+impl Service for OpenApi {
+    type Response = OpenApiService;
+    type Error = Infallible;
+
+    async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+        let doc = serde_json::to_vec(&self.to_json()).expect("openapi document must be serializable");
+        Ok(OpenApiService(Bytes::from(doc)))
+    }
+}
+
+/// service serving openapi document as json. produced by using [OpenApi] as service builder.
+pub struct OpenApiService(Bytes);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for OpenApiService {
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(ResponseBody::from(self.0.clone()));
+        res.headers_mut().insert(CONTENT_TYPE, JSON);
+        Ok(res)
+    }
+}
+
+#[cfg(all(test, feature = "params", feature = "urlencoded"))]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        handler::{handler_service, json::Json, params::Params, query::Query},
+        http::WebRequest,
+        route::{delete, get},
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[derive(serde::Deserialize, serde::Serialize)]
+    struct Pet {
+        name: String,
+        tags: Option<Vec<String>>,
+    }
+
+    impl Schema for Pet {
+        fn schema(components: &mut Components) -> Value {
+            components.reference("Pet", |c| {
+                ObjectSchema::new(c)
+                    .field::<String>("name")
+                    .field::<Option<Vec<String>>>("tags")
+                    .finish()
+            })
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Filter {
+        #[allow(dead_code)]
+        limit: u32,
+        #[allow(dead_code)]
+        name: Option<String>,
+    }
+
+    impl Schema for Filter {
+        fn schema(components: &mut Components) -> Value {
+            ObjectSchema::new(components)
+                .field::<u32>("limit")
+                .field::<Option<String>>("name")
+                .finish()
+        }
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_path(""), ("/".into(), vec![]));
+        assert_eq!(
+            normalize_path("/users/{id}/{*rest}"),
+            ("/users/{id}/{rest}".into(), vec!["id".into(), "rest".into()])
+        );
+    }
+
+    #[test]
+    fn document() {
+        async fn list(_: Query<Filter>) -> Json<Vec<Pet>> {
+            unimplemented!()
+        }
+
+        async fn show(_: Params<u64>) -> Result<Json<Pet>, Error> {
+            unimplemented!()
+        }
+
+        async fn create(_: Json<Pet>) -> StatusCode {
+            unimplemented!()
+        }
+
+        let nest = App::new().at_api(
+            "/pets",
+            get(Describe::new(handler_service(list)).summary("list pets").tag("pet")).post(handler_service(create)),
+        );
+
+        let app = App::new()
+            .at_api("/v1/", nest)
+            .at_api("/pets/{id}", get(handler_service(show)))
+            // routes not described are in document as bare operations.
+            .at(
+                "/health",
+                get(handler_service(|| async { "ok" })).head(handler_service(|| async { "" })),
+            )
+            .at("/v2", App::new().at("/pets/{id}", delete(handler_service(show))));
+
+        let api = app.openapi(Info::new("pet store", "1.0.0"));
+        let doc = api.to_json();
+
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(doc["info"]["title"], "pet store");
+
+        let list = &doc["paths"]["/v1/pets"]["get"];
+        assert_eq!(list["summary"], "list pets");
+        assert_eq!(list["tags"], json!(["pet"]));
+        assert_eq!(
+            list["parameters"],
+            json!([
+                { "name": "limit", "in": "query", "required": true, "schema": { "type": "integer", "format": "int32", "minimum": 0 } },
+                { "name": "name", "in": "query", "required": false, "schema": { "type": ["string", "null"] } },
+            ])
+        );
+        assert_eq!(
+            list["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "type": "array", "items": { "$ref": "#/components/schemas/Pet" } })
+        );
+
+        let create = &doc["paths"]["/v1/pets"]["post"];
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/Pet"
+        );
+        assert_eq!(create["responses"]["default"]["description"], "response");
+
+        let show = &doc["paths"]["/pets/{id}"]["get"];
+        assert_eq!(show["parameters"][0]["name"], "id");
+        assert_eq!(show["parameters"][0]["in"], "path");
+        assert_eq!(show["parameters"][0]["schema"]["format"], "int64");
+        assert!(show["responses"]["200"].is_object());
+        assert!(show["responses"]["default"].is_object());
+
+        assert_eq!(doc["components"]["schemas"]["Pet"]["required"], json!(["name"]));
+
+        assert!(doc["paths"]["/health"]["get"]["responses"]["default"].is_object());
+        assert!(doc["paths"]["/health"]["head"]["responses"]["default"].is_object());
+        let delete = &doc["paths"]["/v2/pets/{id}"]["delete"];
+        assert_eq!(delete["parameters"][0]["name"], "id");
+        assert_eq!(delete["parameters"][0]["schema"]["type"], "string");
+        assert!(delete.get("summary").is_none());
+
+        let service = app.at("/openapi.json", api).finish().call(()).now_or_panic().unwrap();
+        let mut req = WebRequest::default();
+        *req.uri_mut() = "/openapi.json".parse().unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), doc);
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    rc::Rc,
+    sync::Arc,
+};
+
+use serde_json::{Map, Value, json};
+
+/// trait for type that can describe itself with json schema.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::openapi::{Components, ObjectSchema, Schema};
+/// # use serde_json::Value;
+/// struct User {
+///     name: String,
+///     age: Option<u8>,
+/// }
+///
+/// impl Schema for User {
+///     fn schema(components: &mut Components) -> Value {
+///         // named schema is registered once and referenced everywhere.
+///         components.reference("User", |components| {
+///             ObjectSchema::new(components)
+///                 .field::<String>("name")
+///                 .field::<Option<u8>>("age")
+///                 .finish()
+///         })
+///     }
+/// }
+/// ```
+///
+/// with `codegen` crate feature enabled the trait can be derived with `xitca_web::codegen::Schema` macro.
+pub trait Schema {
+    /// generate json schema of type.
+    fn schema(components: &mut Components) -> Value;
+
+    /// whether value of type is required when it's used as object field or parameter.
+    fn required() -> bool {
+        true
+    }
+}
+
+/// reusable schemas shared by openapi document.
+#[derive(Clone, Debug, Default)]
+pub struct Components {
+    schemas: BTreeMap<String, Value>,
+}
+
+const REF_PREFIX: &str = "#/components/schemas/";
+
+impl Components {
+    /// register named schema produced by given closure and return a `$ref` schema pointing to it.
+    ///
+    /// closure is only called once for the same name. recursive type is supported by referencing itself
+    /// inside the closure.
+    pub fn reference<F>(&mut self, name: &str, func: F) -> Value
+    where
+        F: FnOnce(&mut Self) -> Value,
+    {
+        if !self.schemas.contains_key(name) {
+            // reserve the name before calling closure to break recursion.
+            self.schemas.insert(name.to_owned(), Value::Null);
+            let schema = func(self);
+            self.schemas.insert(name.to_owned(), schema);
+        }
+        json!({ "$ref": format!("{REF_PREFIX}{name}") })
+    }
+
+    /// get named schema.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.schemas.get(name)
+    }
+
+    /// follow `$ref` of given schema. schema is returned as is when it's not a reference.
+    pub fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix(REF_PREFIX))
+            .and_then(|name| self.get(name))
+            .unwrap_or(schema)
+    }
+
+    pub(super) fn merge(&mut self, other: &Self) {
+        for (name, schema) in other.schemas.iter() {
+            self.schemas.entry(name.clone()).or_insert_with(|| schema.clone());
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    pub(super) fn to_json(&self) -> Value {
+        json!({ "schemas": self.schemas })
+    }
+}
+
+/// builder for json schema of object type.
+pub struct ObjectSchema<'a> {
+    components: &'a mut Components,
+    properties: Map<String, Value>,
+    required: Vec<Value>,
+    description: Option<String>,
+}
+
+impl<'a> ObjectSchema<'a> {
+    /// construct an object schema with no property.
+    pub fn new(components: &'a mut Components) -> Self {
+        Self {
+            components,
+            properties: Map::new(),
+            required: Vec::new(),
+            description: None,
+        }
+    }
+
+    /// set description of object.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// add property with schema of given type. property is required unless [Schema::required] says otherwise.
+    pub fn field<T>(self, name: &str) -> Self
+    where
+        T: Schema + ?Sized,
+    {
+        let schema = T::schema(self.components);
+        self.property(name, schema, T::required())
+    }
+
+    /// add property with given schema.
+    pub fn property(mut self, name: &str, schema: Value, required: bool) -> Self {
+        if required {
+            self.required.push(Value::String(name.to_owned()));
+        }
+        self.properties.insert(name.to_owned(), schema);
+        self
+    }
+
+    /// get mutable reference of [Components] for generating property schema.
+    pub fn components(&mut self) -> &mut Components {
+        self.components
+    }
+
+    /// finish building and produce json schema.
+    pub fn finish(self) -> Value {
+        let mut schema = Map::new();
+        schema.insert("type".into(), "object".into());
+        if let Some(description) = self.description {
+            schema.insert("description".into(), description.into());
+        }
+        schema.insert("properties".into(), Value::Object(self.properties));
+        if !self.required.is_empty() {
+            schema.insert("required".into(), Value::Array(self.required));
+        }
+        Value::Object(schema)
+    }
+}
+
+/// attach description to given schema.
+pub fn with_description(mut schema: Value, description: &str) -> Value {
+    if let Value::Object(ref mut map) = schema {
+        map.insert("description".into(), description.into());
+    }
+    schema
+}
+
+/// make given schema accept null value.
+pub fn nullable(mut schema: Value) -> Value {
+    if let Some(ty) = schema.get_mut("type") {
+        match ty {
+            Value::String(t) => {
+                let types = json!([t, "null"]);
+                *ty = types;
+                return schema;
+            }
+            Value::Array(types) => {
+                if !types.iter().any(|t| t == "null") {
+                    types.push("null".into());
+                }
+                return schema;
+            }
+            _ => {}
+        }
+    }
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+macro_rules! schema_impl {
+    ($schema: tt, $($ty: ty),*) => {
+        $(
+            impl Schema for $ty {
+                fn schema(_: &mut Components) -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+schema_impl!({ "type": "boolean" }, bool);
+schema_impl!({ "type": "integer", "format": "int32" }, i8, i16, i32);
+schema_impl!({ "type": "integer", "format": "int64" }, i64, isize);
+schema_impl!({ "type": "integer" }, i128);
+schema_impl!({ "type": "integer", "format": "int32", "minimum": 0 }, u8, u16, u32);
+schema_impl!({ "type": "integer", "format": "int64", "minimum": 0 }, u64, usize);
+schema_impl!({ "type": "integer", "minimum": 0 }, u128);
+schema_impl!({ "type": "number", "format": "float" }, f32);
+schema_impl!({ "type": "number", "format": "double" }, f64);
+schema_impl!({ "type": "string" }, str, String, Cow<'_, str>);
+schema_impl!({ "type": "string", "minLength": 1, "maxLength": 1 }, char);
+schema_impl!({ "type": "null" }, ());
+schema_impl!({}, Value);
+
+impl<T> Schema for Option<T>
+where
+    T: Schema,
+{
+    fn schema(components: &mut Components) -> Value {
+        nullable(T::schema(components))
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+macro_rules! deref_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl<T> Schema for $ty
+            where
+                T: Schema + ?Sized,
+            {
+                fn schema(components: &mut Components) -> Value {
+                    T::schema(components)
+                }
+
+                fn required() -> bool {
+                    T::required()
+                }
+            }
+        )*
+    };
+}
+
+deref_impl!(&T, Box<T>, Rc<T>, Arc<T>);
+
+macro_rules! array_impl {
+    ($unique: literal, $($ty: ty),*) => {
+        $(
+            impl<T> Schema for $ty
+            where
+                T: Schema,
+            {
+                fn schema(components: &mut Components) -> Value {
+                    let items = T::schema(components);
+                    if $unique {
+                        json!({ "type": "array", "items": items, "uniqueItems": true })
+                    } else {
+                        json!({ "type": "array", "items": items })
+                    }
+                }
+            }
+        )*
+    };
+}
+
+array_impl!(false, [T], Vec<T>, VecDeque<T>);
+array_impl!(true, BTreeSet<T>);
+
+impl<T, S> Schema for HashSet<T, S>
+where
+    T: Schema,
+{
+    fn schema(components: &mut Components) -> Value {
+        BTreeSet::<T>::schema(components)
+    }
+}
+
+impl<T, const N: usize> Schema for [T; N]
+where
+    T: Schema,
+{
+    fn schema(components: &mut Components) -> Value {
+        json!({ "type": "array", "items": T::schema(components), "minItems": N, "maxItems": N })
+    }
+}
+
+impl<K, V> Schema for BTreeMap<K, V>
+where
+    V: Schema,
+{
+    fn schema(components: &mut Components) -> Value {
+        json!({ "type": "object", "additionalProperties": V::schema(components) })
+    }
+}
+
+impl<K, V, S> Schema for HashMap<K, V, S>
+where
+    V: Schema,
+{
+    fn schema(components: &mut Components) -> Value {
+        BTreeMap::<K, V>::schema(components)
+    }
+}
@@ -5,6 +5,7 @@ use xitca_http::{
     HttpServiceBuilder,
     body::RequestBody,
     config::{DEFAULT_HEADER_LIMIT, DEFAULT_READ_BUF_LIMIT, DEFAULT_WRITE_BUF_LIMIT, HttpServiceConfig},
+    shutdown::ShutdownToken,
 };
 use xitca_server::{Builder, ServerFuture, net::IntoListener};
 use xitca_service::ServiceExt;
@@ -37,6 +38,8 @@ where
             builder: Builder::new(),
             config: HttpServiceConfig::default(),
         }
+        // trigger shutdown token of worker even when no callback is registered.
+        .on_worker_stop(|| async {})
     }
 }
 
@@ -84,6 +87,17 @@ where
         self
     }
 
+    /// Timeout for graceful workers shutdown in seconds.
+    ///
+    /// After receiving a stop signal, workers have this much time to finish serving requests.
+    /// Workers still alive after the timeout are force dropped.
+    ///
+    /// By default shutdown timeout sets to 30 seconds.
+    pub fn shutdown_timeout(mut self, secs: u64) -> Self {
+        self.builder = self.builder.shutdown_timeout(secs);
+        self
+    }
+
     pub fn backlog(mut self, num: u32) -> Self {
         self.builder = self.builder.backlog(num);
         self
@@ -166,6 +180,35 @@ where
         self
     }
 
+    /// Async callback called on every worker thread when server is stopping.
+    ///
+    /// Before the callback is called [ShutdownToken] of the worker is triggered: Http/1 connections stop
+    /// keep-alive, Http/2 connections send GOAWAY frame and handlers waiting on the token are notified.
+    /// Server then waits for in-flight requests to finish until [HttpServer::shutdown_timeout] elapsed.
+    ///
+    /// [ShutdownToken]: crate::handler::shutdown::ShutdownToken
+    pub fn on_worker_stop<FS, Fut>(mut self, on_stop: FS) -> Self
+    where
+        FS: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+    {
+        self.builder = self.builder.on_worker_stop(move || {
+            ShutdownToken::current().shutdown();
+            on_stop()
+        });
+        self
+    }
+
+    /// Async callback called once when all workers are stopped and before server is fully shutdown.
+    pub fn on_shutdown<FS, Fut>(mut self, on_shutdown: FS) -> Self
+    where
+        FS: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+    {
+        self.builder = self.builder.on_shutdown(on_shutdown);
+        self
+    }
+
     #[cfg(not(target_family = "wasm"))]
     pub fn bind<A, ResB, BE>(mut self, addr: A) -> std::io::Result<Self>
     where
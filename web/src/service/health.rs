@@ -0,0 +1,307 @@
+//! health check service for liveness and readiness probes.
+//!
+//! # Examples
+//! ```rust
+//! # use std::convert::Infallible;
+//! # use xitca_web::{handler::handler_service, service::health::Health, App, WebContext};
+//! let health = Health::new()
+//!     // register async check. it's called on every readiness probe.
+//!     .check("database", || async {
+//!         // ping database connection pool here.
+//!         Ok::<_, Infallible>(())
+//!     });
+//!
+//! App::new()
+//!     // liveness probe always respond with 200 as long as server is running.
+//!     .at("/livez", health.liveness())
+//!     // readiness probe runs registered checks and respond with 503 when any check failed or
+//!     // server is shutting down.
+//!     .at("/readyz", health.readiness())
+//!     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }));
+//! ```
+
+use core::{
+    convert::Infallible,
+    fmt,
+    future::{Future, poll_fn},
+    pin::Pin,
+    task::Poll,
+};
+
+use std::{sync::Arc, time::Instant};
+
+use serde_json::{Map, Value, json};
+use xitca_http::util::service::router::{PathGen, RouteGen, RouterMapErr};
+
+use crate::{
+    body::ResponseBody,
+    context::WebContext,
+    error::Error,
+    http::{
+        StatusCode, WebResponse,
+        const_header_value::JSON,
+        header::{CACHE_CONTROL, CONTENT_TYPE, HeaderValue},
+    },
+    service::Service,
+};
+
+type CheckFuture = Pin<Box<dyn Future<Output = Result<(), String>>>>;
+type CheckFn = Arc<dyn Fn() -> CheckFuture + Send + Sync>;
+
+/// builder type for health check services.
+///
+/// probe services constructed from builder share it's registered checks at the time of construction.
+#[derive(Clone, Default)]
+pub struct Health {
+    checks: Vec<(&'static str, CheckFn)>,
+}
+
+impl fmt::Debug for Health {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let checks = self.checks.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        f.debug_struct("Health").field("checks", &checks).finish()
+    }
+}
+
+impl Health {
+    /// construct a new health builder with no check registered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// register async check with given name. checks are called concurrently on readiness probe and
+    /// error of a check is reported with it's [Display](fmt::Display) format.
+    pub fn check<F, Fut, E>(mut self, name: &'static str, func: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + 'static,
+        E: fmt::Display,
+    {
+        self.checks.push((
+            name,
+            Arc::new(move || {
+                let fut = func();
+                Box::pin(async { fut.await.map_err(|e| e.to_string()) })
+            }),
+        ));
+        self
+    }
+
+    /// construct liveness probe service. it responds with 200 as long as server is running.
+    pub fn liveness(&self) -> Probe {
+        Probe {
+            kind: ProbeKind::Liveness,
+            health: self.clone(),
+        }
+    }
+
+    /// construct readiness probe service. it responds with 200 when all registered checks passed.
+    /// 503 is responded when any check failed or when graceful shutdown of server has begun.
+    pub fn readiness(&self) -> Probe {
+        Probe {
+            kind: ProbeKind::Readiness,
+            health: self.clone(),
+        }
+    }
+
+    async fn report(&self) -> (bool, Value) {
+        if is_shutdown() {
+            return (false, json!({ "status": "down", "shutdown": true }));
+        }
+
+        let mut checks = self
+            .checks
+            .iter()
+            .map(|(name, check)| {
+                let fut = check();
+                let start = Instant::now();
+                let fut: Pin<Box<dyn Future<Output = _>>> = Box::pin(async move {
+                    let res = fut.await;
+                    (*name, res, start.elapsed())
+                });
+                (Some(fut), None)
+            })
+            .collect::<Vec<_>>();
+
+        // poll all checks concurrently.
+        poll_fn(|cx| {
+            let mut pending = false;
+            for (fut, output) in checks.iter_mut() {
+                if let Some(f) = fut {
+                    match f.as_mut().poll(cx) {
+                        Poll::Ready(res) => {
+                            *output = Some(res);
+                            *fut = None;
+                        }
+                        Poll::Pending => pending = true,
+                    }
+                }
+            }
+            if pending { Poll::Pending } else { Poll::Ready(()) }
+        })
+        .await;
+
+        let mut is_up = true;
+        let checks = checks
+            .into_iter()
+            .filter_map(|(_, output)| output)
+            .map(|(name, res, latency)| {
+                let mut check = json!({
+                    "status": "up",
+                    "latency_ms": latency.as_secs_f64() * 1000.0,
+                });
+                if let Err(e) = res {
+                    is_up = false;
+                    check["status"] = Value::from("down");
+                    check["error"] = Value::from(e);
+                }
+                (String::from(name), check)
+            })
+            .collect::<Map<_, _>>();
+
+        let status = if is_up { "up" } else { "down" };
+        (is_up, json!({ "status": status, "checks": checks }))
+    }
+}
+
+// readiness goes down as soon as graceful shutdown of worker has begun.
+fn is_shutdown() -> bool {
+    #[cfg(feature = "__server")]
+    {
+        crate::handler::shutdown::ShutdownToken::current().is_shutdown()
+    }
+
+    #[cfg(not(feature = "__server"))]
+    {
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ProbeKind {
+    Liveness,
+    Readiness,
+}
+
+/// probe service produced by [Health::liveness] and [Health::readiness].
+///
+/// response is a json object with `status` field of `"up"` or `"down"`. readiness probe includes
+/// `checks` field with status, latency in milliseconds and optional error of every registered check.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    kind: ProbeKind,
+    health: Health,
+}
+
+impl PathGen for Probe {}
+
+impl RouteGen for Probe {
+    type Route<R> = RouterMapErr<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        RouterMapErr(route)
+    }
+}
+
+impl Service for Probe {
+    type Response = Self;
+    type Error = Infallible;
+
+    async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for Probe {
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let (is_up, report) = match self.kind {
+            ProbeKind::Liveness => (true, json!({ "status": "up" })),
+            ProbeKind::Readiness => self.health.report().await,
+        };
+
+        let body = serde_json::to_vec(&report).expect("health report must be serializable");
+        let mut res = ctx.into_response(ResponseBody::from(body));
+        if !is_up {
+            *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        }
+        res.headers_mut().insert(CONTENT_TYPE, JSON);
+        res.headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App,
+        http::{Uri, WebRequest},
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    fn health() -> Health {
+        Health::new()
+            .check("ok", || async { Ok::<_, Infallible>(()) })
+            .check("broken", || async { Err("connection refused") })
+    }
+
+    async fn probe(health: Health, path: &'static str) -> (StatusCode, Value) {
+        let service = App::new()
+            .at("/livez", health.liveness())
+            .at("/readyz", health.readiness())
+            .finish()
+            .call(())
+            .await
+            .unwrap();
+
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static(path);
+        let res = service.call(req).await.unwrap();
+        let status = res.status();
+        let body = collect_string_body(res.into_body()).await.unwrap();
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    #[test]
+    fn liveness() {
+        let (status, report) = probe(health(), "/livez").now_or_panic();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report, json!({ "status": "up" }));
+    }
+
+    #[test]
+    fn readiness() {
+        let (status, report) = probe(health(), "/readyz").now_or_panic();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["status"], "down");
+        assert_eq!(report["checks"]["ok"]["status"], "up");
+        assert!(report["checks"]["ok"]["latency_ms"].is_f64());
+        assert_eq!(report["checks"]["broken"]["status"], "down");
+        assert_eq!(report["checks"]["broken"]["error"], "connection refused");
+
+        let health = Health::new().check("ok", || async { Ok::<_, Infallible>(()) });
+        let (status, report) = probe(health, "/readyz").now_or_panic();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["status"], "up");
+    }
+
+    #[cfg(feature = "__server")]
+    #[test]
+    fn readiness_shutdown() {
+        crate::handler::shutdown::ShutdownToken::current().shutdown();
+
+        let (status, report) = probe(Health::new(), "/readyz").now_or_panic();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["shutdown"], true);
+
+        let (status, _) = probe(Health::new(), "/livez").now_or_panic();
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -6,4 +6,7 @@ pub mod tower_http_compat;
 #[cfg(feature = "file-raw")]
 pub mod file;
 
+#[cfg(feature = "health")]
+pub mod health;
+
 pub use xitca_service::*;
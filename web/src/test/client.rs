@@ -0,0 +1,676 @@
+use core::{cell::RefCell, convert::Infallible, fmt};
+
+use std::collections::BTreeMap;
+
+use futures_core::stream::Stream;
+
+use crate::{
+    body::RequestBody,
+    bytes::{BufMut, Bytes, BytesMut},
+    error::{BodyError, Error},
+    http::{
+        HeaderMap, HeaderValue, Method, StatusCode, Uri, WebRequest, WebResponse,
+        header::{AsHeaderName, CONTENT_TYPE, COOKIE, HeaderName, SET_COOKIE},
+    },
+    service::Service,
+};
+
+use super::collect_body;
+
+#[cfg(feature = "websocket")]
+pub use http_ws::Message as WsMessage;
+
+const MULTIPART_BOUNDARY: &str = "xitca-web-test-boundary";
+
+/// in-process client for testing application. requests are passed to application service directly
+/// without any network io.
+///
+/// cookies set by response are stored in client and sent with following requests. the cookie store
+/// is naive and ignores `Path`, `Domain` and `Expires` attributes. cookie is removed when `Max-Age`
+/// attribute is zero or negative.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_unsafe_collection::futures::NowOrPanic;
+/// # use xitca_web::{handler::handler_service, http::StatusCode, route::post, test::TestClient, App};
+/// let client = TestClient::new(App::new().at("/", post(handler_service(|body: String| async { body }))).finish())
+///     .now_or_panic()
+///     .unwrap();
+///
+/// let res = client.post("/").body("hello,world!").send().now_or_panic();
+/// assert_eq!(res.status(), StatusCode::OK);
+/// assert_eq!(res.text().now_or_panic().unwrap(), "hello,world!");
+/// ```
+pub struct TestClient<S> {
+    service: S,
+    cookies: RefCell<BTreeMap<String, String>>,
+}
+
+impl<S> TestClient<S> {
+    /// construct a new client with given application service builder. usually the return type of
+    /// [App::finish].
+    ///
+    /// [App::finish]: crate::App::finish
+    pub async fn new<F, E>(builder: F) -> Result<Self, E>
+    where
+        F: Service<Response = S, Error = E>,
+    {
+        builder.call(()).await.map(|service| Self {
+            service,
+            cookies: RefCell::new(BTreeMap::new()),
+        })
+    }
+
+    /// start a `GET` request with given uri.
+    pub fn get<U>(&self, uri: U) -> TestRequest<'_, S>
+    where
+        U: TryInto<Uri>,
+        U::Error: fmt::Debug,
+    {
+        self.request(Method::GET, uri)
+    }
+
+    /// start a `POST` request with given uri.
+    pub fn post<U>(&self, uri: U) -> TestRequest<'_, S>
+    where
+        U: TryInto<Uri>,
+        U::Error: fmt::Debug,
+    {
+        self.request(Method::POST, uri)
+    }
+
+    /// start a `PUT` request with given uri.
+    pub fn put<U>(&self, uri: U) -> TestRequest<'_, S>
+    where
+        U: TryInto<Uri>,
+        U::Error: fmt::Debug,
+    {
+        self.request(Method::PUT, uri)
+    }
+
+    /// start a `PATCH` request with given uri.
+    pub fn patch<U>(&self, uri: U) -> TestRequest<'_, S>
+    where
+        U: TryInto<Uri>,
+        U::Error: fmt::Debug,
+    {
+        self.request(Method::PATCH, uri)
+    }
+
+    /// start a `DELETE` request with given uri.
+    pub fn delete<U>(&self, uri: U) -> TestRequest<'_, S>
+    where
+        U: TryInto<Uri>,
+        U::Error: fmt::Debug,
+    {
+        self.request(Method::DELETE, uri)
+    }
+
+    /// start a request with given method and uri.
+    ///
+    /// # Panics
+    /// when uri is invalid.
+    pub fn request<U>(&self, method: Method, uri: U) -> TestRequest<'_, S>
+    where
+        U: TryInto<Uri>,
+        U::Error: fmt::Debug,
+    {
+        let mut req = WebRequest::default();
+        *req.method_mut() = method;
+        *req.uri_mut() = uri.try_into().expect("TestClient: invalid uri");
+        TestRequest { client: self, req }
+    }
+
+    /// get value of cookie stored in client.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies.borrow().get(name).cloned()
+    }
+
+    fn add_cookies(&self, headers: &mut HeaderMap) {
+        let cookies = self.cookies.borrow();
+        if cookies.is_empty() {
+            return;
+        }
+
+        let mut value = headers
+            .get(COOKIE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned)
+            .unwrap_or_default();
+
+        for (name, val) in cookies.iter() {
+            if !value.is_empty() {
+                value.push_str("; ");
+            }
+            value.push_str(name);
+            value.push('=');
+            value.push_str(val);
+        }
+
+        if let Ok(value) = HeaderValue::try_from(value) {
+            headers.insert(COOKIE, value);
+        }
+    }
+
+    fn store_cookies(&self, headers: &HeaderMap) {
+        let mut cookies = self.cookies.borrow_mut();
+
+        for value in headers.get_all(SET_COOKIE).iter().filter_map(|v| v.to_str().ok()) {
+            let mut attrs = value.split(';');
+            let Some((name, val)) = attrs.next().and_then(|pair| pair.split_once('=')) else {
+                continue;
+            };
+
+            let expired = attrs.filter_map(|attr| attr.split_once('=')).any(|(k, v)| {
+                k.trim().eq_ignore_ascii_case("max-age") && v.trim().parse::<i64>().is_ok_and(|age| age <= 0)
+            });
+
+            let name = name.trim().to_owned();
+            if expired {
+                cookies.remove(&name);
+            } else {
+                cookies.insert(name, val.trim().to_owned());
+            }
+        }
+    }
+}
+
+/// request builder of [TestClient].
+pub struct TestRequest<'a, S> {
+    client: &'a TestClient<S>,
+    req: WebRequest,
+}
+
+impl<S> TestRequest<'_, S> {
+    /// append a header to request.
+    ///
+    /// # Panics
+    /// when header name or value is invalid.
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: fmt::Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: fmt::Debug,
+    {
+        let name = name.try_into().expect("TestRequest: invalid header name");
+        let value = value.try_into().expect("TestRequest: invalid header value");
+        self.req.headers_mut().append(name, value);
+        self
+    }
+
+    /// set body of request.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        let body = RequestBody::from(body.into());
+        self.req = self.req.map(|ext| ext.map_body(|_| body));
+        self
+    }
+
+    /// set body of request to json serialized value and `Content-Type` header to `application/json`.
+    ///
+    /// # Panics
+    /// when serialization fails.
+    #[cfg(feature = "json")]
+    pub fn json<T>(self, value: &T) -> Self
+    where
+        T: serde_core::Serialize + ?Sized,
+    {
+        let body = serde_json::to_vec(value).expect("TestRequest: json serialization failed");
+        self.content_type("application/json").body(body)
+    }
+
+    /// set body of request to urlencoded serialized value and `Content-Type` header to
+    /// `application/x-www-form-urlencoded`.
+    ///
+    /// # Panics
+    /// when serialization fails.
+    #[cfg(feature = "urlencoded")]
+    pub fn form<T>(self, value: &T) -> Self
+    where
+        T: serde_core::Serialize + ?Sized,
+    {
+        let body = serde_urlencoded::to_string(value).expect("TestRequest: urlencoded serialization failed");
+        self.content_type("application/x-www-form-urlencoded").body(body)
+    }
+
+    /// set body of request to multipart form and `Content-Type` header to `multipart/form-data`.
+    pub fn multipart(self, form: TestMultipart) -> Self {
+        let content_type = format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}");
+        self.content_type(content_type).body(form.finish())
+    }
+
+    fn content_type<V>(mut self, value: V) -> Self
+    where
+        V: TryInto<HeaderValue>,
+        V::Error: fmt::Debug,
+    {
+        let value = value.try_into().expect("TestRequest: invalid content type");
+        self.req.headers_mut().insert(CONTENT_TYPE, value);
+        self
+    }
+
+    /// send request to application service and wait for response.
+    pub async fn send<B>(self) -> TestResponse<B>
+    where
+        S: Service<WebRequest, Response = WebResponse<B>, Error = Infallible>,
+    {
+        let Self { client, mut req } = self;
+        client.add_cookies(req.headers_mut());
+        let res = match client.service.call(req).await {
+            Ok(res) => res,
+            Err(e) => match e {},
+        };
+        client.store_cookies(res.headers());
+        TestResponse { res }
+    }
+
+    /// send request as websocket upgrade request. request should be constructed with [TestClient::get].
+    ///
+    /// on successful upgrade a [TestWebSocket] is returned. otherwise the response of application
+    /// service is returned as error.
+    ///
+    /// websocket handler is spawned with [tokio::task::spawn_local] therefore this method must be
+    /// called inside [tokio::task::LocalSet].
+    #[cfg(feature = "websocket")]
+    pub async fn websocket<B>(self) -> Result<TestWebSocket<B>, TestResponse<B>>
+    where
+        S: Service<WebRequest, Response = WebResponse<B>, Error = Infallible>,
+    {
+        let Self { client, mut req } = self;
+
+        let uri = req.uri().clone();
+        let handshake = http_ws::client_request_from_uri::<_, Infallible>(uri, crate::http::Version::HTTP_11)
+            .unwrap_or_else(|e| match e {});
+        req.headers_mut().extend(handshake.into_parts().0.headers);
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let body = RequestBody::from(crate::body::BoxBody::new(ChannelBody(rx)));
+        let req = req.map(|ext| ext.map_body(|_| body));
+
+        let res = (TestRequest { client, req }).send().await;
+
+        if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(res);
+        }
+
+        Ok(TestWebSocket {
+            tx,
+            body: Box::pin(res.res.into_body()),
+            encode: http_ws::Codec::new().client_mode(),
+            decode: http_ws::Codec::new().client_mode(),
+            buf: BytesMut::new(),
+        })
+    }
+}
+
+/// response type of [TestClient].
+pub struct TestResponse<B> {
+    res: WebResponse<B>,
+}
+
+impl<B> TestResponse<B> {
+    /// status code of response.
+    pub fn status(&self) -> StatusCode {
+        self.res.status()
+    }
+
+    /// headers of response.
+    pub fn headers(&self) -> &HeaderMap {
+        self.res.headers()
+    }
+
+    /// get string value of header with given name. return None when header is absent or it's not
+    /// valid utf-8.
+    pub fn header<K>(&self, name: K) -> Option<&str>
+    where
+        K: AsHeaderName,
+    {
+        self.res.headers().get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// get inner response type.
+    pub fn into_inner(self) -> WebResponse<B> {
+        self.res
+    }
+
+    /// collect response body to Vec.
+    pub async fn body<T, E>(self) -> Result<Vec<u8>, Error>
+    where
+        B: Stream<Item = Result<T, E>>,
+        T: AsRef<[u8]>,
+        E: Into<BodyError>,
+    {
+        collect_body(self.res.into_body())
+            .await
+            .map_err(|e| Error::from(e.into()))
+    }
+
+    /// collect response body and parse it to String.
+    pub async fn text<T, E>(self) -> Result<String, Error>
+    where
+        B: Stream<Item = Result<T, E>>,
+        T: AsRef<[u8]>,
+        E: Into<BodyError>,
+    {
+        let body = self.body().await?;
+        String::from_utf8(body).map_err(Error::from_service)
+    }
+
+    /// collect response body and deserialize it from json.
+    #[cfg(feature = "json")]
+    pub async fn json<D, T, E>(self) -> Result<D, Error>
+    where
+        D: for<'de> serde_core::Deserialize<'de>,
+        B: Stream<Item = Result<T, E>>,
+        T: AsRef<[u8]>,
+        E: Into<BodyError>,
+    {
+        let body = self.body().await?;
+        serde_json::from_slice(&body).map_err(Error::from)
+    }
+}
+
+/// multipart form body of [TestRequest].
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::test::TestMultipart;
+/// let form = TestMultipart::new()
+///     .text("name", "foo")
+///     .file("avatar", "foo.png", "image/png", &b"\x89PNG"[..]);
+/// ```
+#[derive(Default)]
+pub struct TestMultipart {
+    buf: BytesMut,
+}
+
+impl TestMultipart {
+    /// construct an empty multipart form.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a text field to form.
+    pub fn text(mut self, name: &str, value: impl AsRef<[u8]>) -> Self {
+        self.part_header(name, None, None);
+        self.part_body(value.as_ref());
+        self
+    }
+
+    /// add a file field to form.
+    pub fn file(mut self, name: &str, file_name: &str, content_type: &str, content: impl AsRef<[u8]>) -> Self {
+        self.part_header(name, Some(file_name), Some(content_type));
+        self.part_body(content.as_ref());
+        self
+    }
+
+    fn part_header(&mut self, name: &str, file_name: Option<&str>, content_type: Option<&str>) {
+        self.buf.put_slice(b"--");
+        self.buf.put_slice(MULTIPART_BOUNDARY.as_bytes());
+        self.buf.put_slice(b"\r\nContent-Disposition: form-data; name=\"");
+        self.buf.put_slice(name.as_bytes());
+        self.buf.put_u8(b'"');
+        if let Some(file_name) = file_name {
+            self.buf.put_slice(b"; filename=\"");
+            self.buf.put_slice(file_name.as_bytes());
+            self.buf.put_u8(b'"');
+        }
+        if let Some(content_type) = content_type {
+            self.buf.put_slice(b"\r\nContent-Type: ");
+            self.buf.put_slice(content_type.as_bytes());
+        }
+        self.buf.put_slice(b"\r\n\r\n");
+    }
+
+    fn part_body(&mut self, body: &[u8]) {
+        self.buf.put_slice(body);
+        self.buf.put_slice(b"\r\n");
+    }
+
+    fn finish(mut self) -> Bytes {
+        self.buf.put_slice(b"--");
+        self.buf.put_slice(MULTIPART_BOUNDARY.as_bytes());
+        self.buf.put_slice(b"--\r\n");
+        self.buf.freeze()
+    }
+}
+
+#[cfg(feature = "websocket")]
+pub use websocket::TestWebSocket;
+
+#[cfg(feature = "websocket")]
+use websocket::ChannelBody;
+
+#[cfg(feature = "websocket")]
+mod websocket {
+    use core::{
+        future::poll_fn,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use http_ws::{Codec, ProtocolError};
+    use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+    use super::*;
+
+    /// client side of in-process websocket connection. returned by [TestRequest::websocket].
+    ///
+    /// dropping it would close the request body stream of websocket connection.
+    pub struct TestWebSocket<B> {
+        pub(super) tx: UnboundedSender<Bytes>,
+        pub(super) body: Pin<Box<B>>,
+        pub(super) encode: Codec,
+        pub(super) decode: Codec,
+        pub(super) buf: BytesMut,
+    }
+
+    impl<B> TestWebSocket<B> {
+        /// send message to server.
+        pub fn send(&mut self, msg: WsMessage) -> Result<(), Error> {
+            let mut buf = BytesMut::new();
+            self.encode.encode(msg, &mut buf).map_err(protocol_error)?;
+            self.tx
+                .send(buf.freeze())
+                .map_err(|_| protocol_error(ProtocolError::Closed))
+        }
+
+        /// receive message from server. return None when server closed the connection.
+        pub async fn recv<T, E>(&mut self) -> Result<Option<WsMessage>, Error>
+        where
+            B: Stream<Item = Result<T, E>>,
+            T: AsRef<[u8]>,
+            E: Into<BodyError>,
+        {
+            loop {
+                if let Some(msg) = self.decode.decode(&mut self.buf).map_err(protocol_error)? {
+                    return Ok(Some(msg));
+                }
+
+                match poll_fn(|cx| self.body.as_mut().poll_next(cx)).await {
+                    Some(chunk) => {
+                        let chunk = chunk.map_err(|e| Error::from(e.into()))?;
+                        self.buf.extend_from_slice(chunk.as_ref());
+                    }
+                    None => return Ok(None),
+                }
+            }
+        }
+    }
+
+    fn protocol_error(e: ProtocolError) -> Error {
+        Error::from(BodyError::from(e))
+    }
+
+    pub(super) struct ChannelBody(pub(super) UnboundedReceiver<Bytes>);
+
+    impl Stream for ChannelBody {
+        type Item = Result<Bytes, Infallible>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut().0.poll_recv(cx).map(|bytes| bytes.map(Ok))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        App, WebContext,
+        handler::handler_service,
+        http::WebResponse,
+        route::{get, post},
+    };
+
+    use super::*;
+
+    // echo value of given request header.
+    fn echo_header(name: HeaderName) -> impl Fn(&WebContext<'_>) -> core::future::Ready<String> + Clone {
+        move |ctx| {
+            let value = ctx
+                .req()
+                .headers()
+                .get(&name)
+                .map(|v| v.to_str().unwrap().to_owned())
+                .unwrap_or_default();
+            core::future::ready(value)
+        }
+    }
+
+    #[test]
+    fn request() {
+        let client = TestClient::new(
+            App::new()
+                .at("/echo", post(handler_service(|body: String| async { body })))
+                .at("/type", post(handler_service(echo_header(CONTENT_TYPE))))
+                .finish(),
+        )
+        .now_or_panic()
+        .unwrap();
+
+        let res = client.post("/echo").body("996").send().now_or_panic();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().now_or_panic().unwrap(), "996");
+
+        let res = client
+            .post("/type")
+            .multipart(TestMultipart::new().text("foo", "bar"))
+            .send()
+            .now_or_panic();
+        assert_eq!(
+            res.text().now_or_panic().unwrap(),
+            format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}")
+        );
+
+        let res = client.get("/echo").send().now_or_panic();
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn cookie() {
+        let client = TestClient::new(
+            App::new()
+                .at(
+                    "/login",
+                    get(handler_service(|| async {
+                        let mut res = WebResponse::default();
+                        res.headers_mut()
+                            .append(SET_COOKIE, HeaderValue::from_static("id=996; Path=/; HttpOnly"));
+                        res.headers_mut()
+                            .append(SET_COOKIE, HeaderValue::from_static("foo=bar"));
+                        res
+                    })),
+                )
+                .at(
+                    "/logout",
+                    get(handler_service(|| async {
+                        let mut res = WebResponse::default();
+                        res.headers_mut()
+                            .append(SET_COOKIE, HeaderValue::from_static("id=; Max-Age=0"));
+                        res
+                    })),
+                )
+                .at("/", get(handler_service(echo_header(COOKIE))))
+                .finish(),
+        )
+        .now_or_panic()
+        .unwrap();
+
+        client.get("/login").send().now_or_panic();
+        assert_eq!(client.cookie("id").as_deref(), Some("996"));
+
+        let res = client.get("/").header(COOKIE, "a=b").send().now_or_panic();
+        assert_eq!(res.text().now_or_panic().unwrap(), "a=b; foo=bar; id=996");
+
+        client.get("/logout").send().now_or_panic();
+        assert!(client.cookie("id").is_none());
+
+        let res = client.get("/").send().now_or_panic();
+        assert_eq!(res.text().now_or_panic().unwrap(), "foo=bar");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        use crate::handler::json::Json;
+
+        let client = TestClient::new(
+            App::new()
+                .at(
+                    "/",
+                    post(handler_service(|Json(v): Json<Vec<u32>>| async move {
+                        Json(v.into_iter().sum::<u32>())
+                    })),
+                )
+                .finish(),
+        )
+        .now_or_panic()
+        .unwrap();
+
+        let res = client.post("/").json(&[1, 2, 3]).send().now_or_panic();
+        assert_eq!(res.header(CONTENT_TYPE), Some("application/json"));
+        assert_eq!(res.json::<u32, _, _>().now_or_panic().unwrap(), 6);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn websocket() {
+        use crate::handler::websocket::{Message, WebSocket};
+
+        async fn handler(mut ws: WebSocket) -> WebSocket {
+            ws.on_msg(|tx, msg| {
+                Box::pin(async move {
+                    if let Message::Text(txt) = msg {
+                        let _ = tx.text(txt.to_string()).await;
+                    }
+                })
+            });
+            ws
+        }
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let client = TestClient::new(App::new().at("/", get(handler_service(handler))).finish())
+                    .await
+                    .unwrap();
+
+                let res = client.post("/").websocket().await.err().unwrap();
+                assert_ne!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+
+                let mut ws = client.get("/").websocket().await.ok().unwrap();
+
+                ws.send(WsMessage::Text(Bytes::from("996"))).unwrap();
+                assert_eq!(ws.recv().await.unwrap(), Some(WsMessage::Text(Bytes::from("996"))));
+
+                ws.send(WsMessage::Ping(Bytes::from("ping"))).unwrap();
+                assert_eq!(ws.recv().await.unwrap(), Some(WsMessage::Pong(Bytes::from("ping"))));
+
+                ws.send(WsMessage::Close(None)).unwrap();
+                assert_eq!(ws.recv().await.unwrap(), Some(WsMessage::Close(None)));
+                assert_eq!(ws.recv().await.unwrap(), None);
+            })
+            .await;
+    }
+}
@@ -1,5 +1,12 @@
 //! utilities for testing web application
 
+mod client;
+
+pub use client::{TestClient, TestMultipart, TestRequest, TestResponse};
+
+#[cfg(feature = "websocket")]
+pub use client::{TestWebSocket, WsMessage};
+
 use core::{future::poll_fn, pin::pin};
 
 use futures_core::stream::Stream;